@@ -117,8 +117,8 @@ fn attach_null() {
     let compositor = manager
         .instantiate_exact::<wayc::protocol::wl_compositor::WlCompositor>(1)
         .unwrap();
-    let surface = compositor.create_surface();
-    surface.attach(None, 0, 0);
+    let surface = compositor.create_surface().unwrap();
+    surface.attach(None, 0, 0).unwrap();
 
     roundtrip(&mut client, &mut server).unwrap();
 
@@ -148,14 +148,14 @@ fn attach_buffer() {
     let mut file = tempfile::tempfile().unwrap();
     write!(file, "I like trains!").unwrap();
     file.flush().unwrap();
-    let pool = shm.create_pool(file.as_raw_fd(), 42);
-    let buffer = pool.create_buffer(0, 0, 0, 0, Format::Argb8888);
+    let pool = shm.create_pool(file.as_raw_fd(), 42).unwrap();
+    let buffer = pool.create_buffer(0, 0, 0, 0, Format::Argb8888).unwrap();
 
     let compositor = manager
         .instantiate_exact::<wayc::protocol::wl_compositor::WlCompositor>(1)
         .unwrap();
-    let surface = compositor.create_surface();
-    surface.attach(Some(&buffer), 0, 0);
+    let surface = compositor.create_surface().unwrap();
+    surface.attach(Some(&buffer), 0, 0).unwrap();
 
     roundtrip(&mut client, &mut server).unwrap();
 
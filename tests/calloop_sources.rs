@@ -0,0 +1,61 @@
+extern crate calloop;
+
+mod helpers;
+
+use helpers::{wayc, ways, TestClient, TestServer};
+
+use wayc::protocol::wl_display::WlDisplay;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+fn sync_n_times(display: &wayc::Attached<WlDisplay>, count: Rc<Cell<u32>>, n: u32) {
+    let display2 = display.clone();
+    display.sync().unwrap().assign_mono(move |_, _| {
+        count.set(count.get() + 1);
+        if count.get() < n {
+            sync_n_times(&display2, count.clone(), n);
+        }
+    });
+}
+
+#[test]
+fn calloop_roundtrip() {
+    let server = TestServer::new();
+    let client = TestClient::new(&server.socket_name);
+
+    let mut event_loop = calloop::EventLoop::<()>::new().unwrap();
+    let handle = event_loop.handle();
+
+    let server_dispatched = Rc::new(Cell::new(false));
+    let server_dispatched2 = server_dispatched.clone();
+    handle
+        .insert_source(ways::WaylandSource::new(server.display), move |(), _, _| {
+            server_dispatched2.set(true);
+        })
+        .unwrap();
+    handle
+        .insert_source(wayc::WaylandSource::new(client.event_queue), |(), queue, _| {
+            queue.dispatch_pending(|_, _| {}).unwrap();
+        })
+        .unwrap();
+
+    // every sync is sent from the callback of the previous one, and thus relies
+    // on the source flushing the requests before going back to sleep
+    let count = Rc::new(Cell::new(0));
+    sync_n_times(&client.display_proxy, count.clone(), 3);
+    client.display.flush().unwrap();
+
+    for _ in 0..20 {
+        if count.get() == 3 {
+            break;
+        }
+        event_loop
+            .dispatch(Some(Duration::from_millis(100)), &mut ())
+            .unwrap();
+    }
+
+    assert!(server_dispatched.get());
+    assert_eq!(count.get(), 3);
+}
@@ -18,8 +18,8 @@ fn constructor_dead() {
 
     let seat = manager.instantiate_exact::<wl_seat::WlSeat>(5).unwrap();
 
-    seat.release();
-    assert!(!seat.get_pointer().as_ref().is_alive());
+    seat.release().unwrap();
+    assert_eq!(seat.get_pointer().err(), Some(wayc::SendError::DeadObject));
 }
 
 #[test]
@@ -39,3 +39,24 @@ fn send_constructor_wrong_type() {
         .as_ref()
         .send::<wl_keyboard::WlKeyboard>(wl_seat::Request::GetPointer {}, None);
 }
+
+#[test]
+fn version_too_low() {
+    let mut server = TestServer::new();
+    server.display.create_global::<ServerSeat, _>(5, |_, _| {});
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let seat = manager.instantiate_exact::<wl_seat::WlSeat>(1).unwrap();
+
+    // release is only available since version 5, it is not sent
+    assert_eq!(
+        seat.release(),
+        Err(wayc::SendError::VersionTooLow { since: 5, version: 1 })
+    );
+    roundtrip(&mut client, &mut server).unwrap();
+    assert!(seat.as_ref().is_alive());
+}
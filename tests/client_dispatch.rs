@@ -71,6 +71,7 @@ fn client_dispatch() {
     client
         .display_proxy
         .sync()
+        .unwrap()
         .assign_mono(move |_, _| done2.set(true));
     while !done.get() {
         client.event_queue.dispatch(|_, _| unreachable!()).unwrap();
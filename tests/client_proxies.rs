@@ -128,7 +128,7 @@ fn proxy_create_unattached() {
     let compositor = (**compositor).clone();
 
     let ret = ::std::thread::spawn(move || {
-        compositor.create_surface(); // should panic
+        compositor.create_surface().unwrap(); // should panic
     })
     .join();
 
@@ -157,7 +157,7 @@ fn proxy_create_attached() {
     ::std::thread::spawn(move || {
         let evq2 = display2.create_event_queue();
         let compositor_wrapper = compositor.as_ref().clone().attach(evq2.get_token());
-        compositor_wrapper.create_surface(); // should not panic
+        compositor_wrapper.create_surface().unwrap(); // should not panic
     })
     .join()
     .unwrap();
@@ -184,7 +184,7 @@ fn dead_proxies() {
     assert!(output2.as_ref().is_alive());
 
     // kill the output
-    output.release();
+    output.release().unwrap();
 
     // dead proxies are never equal
     assert!(output != output2);
@@ -0,0 +1,48 @@
+mod helpers;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::wl_seat::WlSeat as ServerSeat;
+
+use wayc::protocol::wl_seat;
+
+// the send errors panic in all the tests of this file
+
+#[test]
+#[should_panic(expected = "Cannot send request get_pointer on a dead proxy of wl_seat.")]
+fn dead_object_panics() {
+    wayc::panic_on_send_errors(true);
+
+    let mut server = TestServer::new();
+    server.display.create_global::<ServerSeat, _>(5, |_, _| {});
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let seat = manager.instantiate_exact::<wl_seat::WlSeat>(5).unwrap();
+
+    seat.release().unwrap();
+    let _ = seat.get_pointer();
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot send request release which requires version >= 5 on proxy wl_seat@3 which is version 1."
+)]
+fn version_too_low_panics() {
+    wayc::panic_on_send_errors(true);
+
+    let mut server = TestServer::new();
+    server.display.create_global::<ServerSeat, _>(5, |_, _| {});
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let seat = manager.instantiate_exact::<wl_seat::WlSeat>(1).unwrap();
+
+    let _ = seat.release();
+}
@@ -0,0 +1,119 @@
+mod helpers;
+
+use helpers::{find_buffer, roundtrip, wayc, ways, TestClient, TestServer};
+
+use wayc::protocol::wl_shm::{Format, WlShm};
+use wayc::shm::ShmPool;
+
+use ways::shm::{init_shm_global, with_buffer_contents};
+
+#[test]
+fn shm_pool_buffers() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let mut pool = ShmPool::new(&shm).unwrap();
+
+    let (canvas, buffer) = pool.buffer(4, 4, 16, Format::Argb8888).unwrap();
+    assert_eq!(canvas.len(), 64);
+    for (i, byte) in canvas.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_buffer = find_buffer(&server, buffer.as_ref().id());
+    let ret = with_buffer_contents(&server_buffer, |slice, data| {
+        assert_eq!((data.width, data.height, data.stride), (4, 4, 16));
+        slice.to_vec()
+    });
+    assert_eq!(ret.unwrap(), (0..64).collect::<Vec<u8>>());
+
+    // the first buffer is in use, a second one is created
+    let (_, buffer2) = pool.buffer(4, 4, 16, Format::Argb8888).unwrap();
+    assert!(buffer2 != buffer);
+    assert_eq!(pool.busy_buffers(), 2);
+
+    // once released, the first buffer is reused
+    server_buffer.release();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(pool.busy_buffers(), 1);
+    let (canvas, buffer3) = pool.buffer(4, 4, 16, Format::Argb8888).unwrap();
+    assert!(buffer3 == buffer);
+    assert_eq!(canvas[42], 42);
+}
+
+#[test]
+fn shm_pool_grow() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let mut pool = ShmPool::new(&shm).unwrap();
+
+    let (_, small) = pool.buffer(16, 16, 64, Format::Argb8888).unwrap();
+    // does not fit in the initial pool
+    let (canvas, large) = pool.buffer(64, 64, 256, Format::Xrgb8888).unwrap();
+    for byte in canvas.iter_mut() {
+        *byte = 0xAB;
+    }
+    assert!(pool.size() >= 1024 + 16384);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let ret = with_buffer_contents(&find_buffer(&server, large.as_ref().id()), |slice, _| {
+        slice.len() == 16384 && slice.iter().all(|&b| b == 0xAB)
+    });
+    assert_eq!(ret, Ok(true));
+    let ret = with_buffer_contents(&find_buffer(&server, small.as_ref().id()), |slice, _| slice.len());
+    assert_eq!(ret, Ok(1024));
+
+    // invalid dimensions are refused
+    assert!(pool.buffer(0, 16, 64, Format::Argb8888).is_err());
+}
+
+#[test]
+fn shm_pool_reuse_free_space() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let mut pool = ShmPool::new(&shm).unwrap();
+
+    // a buffer growing at each frame, as during an interactive resize
+    for i in 1..=16 {
+        let (_, buffer) = pool.buffer(16 * i, 16, 64 * i, Format::Argb8888).unwrap();
+        roundtrip(&mut client, &mut server).unwrap();
+        find_buffer(&server, buffer.as_ref().id()).release();
+        roundtrip(&mut client, &mut server).unwrap();
+    }
+    assert_eq!(pool.busy_buffers(), 0);
+    // the last buffer reuses the space of the previous ones
+    assert!(pool.size() <= 2 * 16 * 1024);
+
+    // adjacent free buffers are merged to fit a larger one
+    let mut pool = ShmPool::new(&shm).unwrap();
+    let (_, first) = pool.buffer(16, 16, 64, Format::Argb8888).unwrap();
+    let (_, second) = pool.buffer(16, 16, 64, Format::Argb8888).unwrap();
+    let _third = pool.buffer(16, 16, 64, Format::Argb8888).unwrap();
+    let size = pool.size();
+    roundtrip(&mut client, &mut server).unwrap();
+    find_buffer(&server, first.as_ref().id()).release();
+    find_buffer(&server, second.as_ref().id()).release();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(pool.busy_buffers(), 1);
+    let (canvas, _) = pool.buffer(32, 16, 128, Format::Argb8888).unwrap();
+    assert_eq!(canvas.len(), 2048);
+    assert_eq!(pool.size(), size);
+}
@@ -0,0 +1,189 @@
+extern crate tempfile;
+extern crate wayland_cursor;
+
+mod helpers;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use helpers::cursor::write_cursor;
+use helpers::{find_surface, roundtrip, wayc, ways, TestClient, TestServer};
+
+use wayc::protocol::wl_compositor::WlCompositor;
+use wayc::protocol::wl_seat::WlSeat;
+use wayc::protocol::wl_shm::WlShm;
+use wayland_cursor::{load_theme, AnimatedCursor, Clock};
+
+use ways::compositor::{init_compositor, surface_states, BufferAssignment, SurfaceAttributes};
+use ways::protocol::{wl_callback::WlCallback, wl_pointer, wl_seat};
+use ways::shm::{init_shm_global, with_buffer_contents};
+
+// the set_cursor requests received by the server: (serial, surface id, hotspot)
+type CursorRequests = Rc<RefCell<Vec<(u32, u32, (i32, i32))>>>;
+
+struct Env {
+    server: TestServer,
+    client: TestClient,
+    compositor: wayc::Main<WlCompositor>,
+    shm: wayc::Main<WlShm>,
+    seat: wayc::Main<WlSeat>,
+    requests: CursorRequests,
+    callbacks: Rc<RefCell<Vec<WlCallback>>>,
+    _dir: tempfile::TempDir,
+}
+
+fn setup() -> Env {
+    // an animated cursor, whose hotspot changes between its two frames
+    let dir = tempfile::tempdir().unwrap();
+    write_cursor(
+        dir.path(),
+        "default",
+        "wait",
+        &[(24, 8, 8, 100, 0), (24, 16, 16, 50, 0), (48, 32, 32, 0, 0)],
+    );
+    write_cursor(dir.path(), "default", "left_ptr", &[(24, 4, 4, 0, 0)]);
+    std::env::set_var("XCURSOR_PATH", dir.path());
+
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+    // keep the frame callbacks of the surfaces, for the tests to send them
+    let callbacks = Rc::new(RefCell::new(Vec::new()));
+    let callbacks2 = callbacks.clone();
+    init_compositor(&mut server.display, move |surface| {
+        let states = surface_states(surface).unwrap();
+        let mut attributes = states.current::<SurfaceAttributes>();
+        callbacks2
+            .borrow_mut()
+            .extend(attributes.frame_callbacks.drain(..));
+    });
+    let requests = CursorRequests::default();
+    let requests2 = requests.clone();
+    server
+        .display
+        .create_global::<wl_seat::WlSeat, _>(5, move |seat: ways::Main<wl_seat::WlSeat>, _| {
+            let requests = requests2.clone();
+            seat.assign_mono(move |_, request| {
+                if let wl_seat::Request::GetPointer { id } = request {
+                    let requests = requests.clone();
+                    id.assign_mono(move |_, request| {
+                        if let wl_pointer::Request::SetCursor {
+                            serial,
+                            surface,
+                            hotspot_x,
+                            hotspot_y,
+                        } = request
+                        {
+                            let id = surface.map(|s| s.as_ref().id()).unwrap_or(0);
+                            requests.borrow_mut().push((serial, id, (hotspot_x, hotspot_y)));
+                        }
+                    });
+                }
+            });
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+    let compositor = manager.instantiate_exact::<WlCompositor>(4).unwrap();
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let seat = manager.instantiate_exact::<WlSeat>(5).unwrap();
+
+    Env {
+        server,
+        client,
+        compositor,
+        shm,
+        seat,
+        requests,
+        callbacks,
+        _dir: dir,
+    }
+}
+
+// the width and buffer scale of the buffer currently attached to a surface
+fn current_buffer(server: &TestServer, id: u32) -> (u32, i32) {
+    let surface = find_surface(server, id);
+    let states = surface_states(&surface).unwrap();
+    let attributes = states.current::<SurfaceAttributes>();
+    match attributes.buffer {
+        Some(BufferAssignment::NewBuffer { ref buffer, .. }) => (
+            with_buffer_contents(buffer, |_, data| data.width as u32).unwrap(),
+            attributes.buffer_scale,
+        ),
+        _ => panic!("No buffer attached."),
+    }
+}
+
+#[test]
+fn animated_cursor() {
+    let mut env = setup();
+    let theme = Arc::new(load_theme(None, 24, &env.shm));
+    let pointer = env.seat.get_pointer().unwrap();
+
+    // manual clock
+    let cursor = AnimatedCursor::new(theme.clone(), &env.compositor, Clock::Manual).unwrap();
+    let surface_id = cursor.surface().as_ref().id();
+    assert!(!cursor.set_cursor("unknown"));
+    assert!(cursor.set_cursor("wait"));
+    cursor.pointer_enter(&pointer, 42);
+    roundtrip(&mut env.client, &mut env.server).unwrap();
+    assert_eq!(*env.requests.borrow(), vec![(42, surface_id, (4, 4))]);
+    assert_eq!(current_buffer(&env.server, surface_id), (8, 1));
+
+    // the animation starts at the first time given
+    assert_eq!(cursor.advance(1000), Some(100));
+    assert_eq!(cursor.advance(1120), Some(30));
+    roundtrip(&mut env.client, &mut env.server).unwrap();
+    assert_eq!(current_buffer(&env.server, surface_id), (16, 1));
+    // the new hotspot was given to the pointer
+    assert_eq!(env.requests.borrow().last(), Some(&(42, surface_id, (8, 8))));
+
+    // the cursor is set again on each enter, restarting the animation
+    cursor.process_pointer_event(
+        &pointer,
+        &wayc::protocol::wl_pointer::Event::Enter {
+            serial: 43,
+            surface: cursor.surface(),
+            surface_x: 0.0,
+            surface_y: 0.0,
+        },
+    );
+    roundtrip(&mut env.client, &mut env.server).unwrap();
+    assert_eq!(env.requests.borrow().last(), Some(&(43, surface_id, (4, 4))));
+    assert_eq!(current_buffer(&env.server, surface_id), (8, 1));
+
+    // larger images are used on scaled outputs
+    cursor.set_scale(2);
+    assert_eq!(cursor.advance(0), None);
+    roundtrip(&mut env.client, &mut env.server).unwrap();
+    assert_eq!(current_buffer(&env.server, surface_id), (32, 2));
+    assert_eq!(env.requests.borrow().last(), Some(&(43, surface_id, (8, 8))));
+
+    // static cursors are not animated
+    assert!(cursor.set_cursor("left_ptr"));
+    assert_eq!(cursor.advance(0), None);
+    drop(cursor);
+    roundtrip(&mut env.client, &mut env.server).unwrap();
+
+    // frame callbacks
+    env.requests.borrow_mut().clear();
+    let cursor = AnimatedCursor::new(theme.clone(), &env.compositor, Clock::FrameCallbacks).unwrap();
+    let surface_id = cursor.surface().as_ref().id();
+    cursor.set_cursor("wait");
+    cursor.pointer_enter(&pointer, 44);
+    roundtrip(&mut env.client, &mut env.server).unwrap();
+    assert_eq!(*env.requests.borrow(), vec![(44, surface_id, (4, 4))]);
+
+    // the first callback starts the animation, each frame requests a new callback
+    for &(time, width) in &[(500, 8), (580, 8), (620, 16), (660, 8)] {
+        let callbacks = ::std::mem::replace(&mut *env.callbacks.borrow_mut(), Vec::new());
+        assert_eq!(callbacks.len(), 1);
+        callbacks[0].done(time);
+        // the client handles the callback, then the server its new requests
+        roundtrip(&mut env.client, &mut env.server).unwrap();
+        roundtrip(&mut env.client, &mut env.server).unwrap();
+        assert_eq!(current_buffer(&env.server, surface_id).0, width);
+    }
+    assert_eq!(env.requests.borrow().last(), Some(&(44, surface_id, (4, 4))));
+}
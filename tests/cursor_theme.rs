@@ -0,0 +1,138 @@
+extern crate tempfile;
+extern crate wayland_cursor;
+
+mod helpers;
+
+use std::fs;
+
+use helpers::cursor::write_cursor;
+use helpers::{find_buffer, roundtrip, wayc, ways, TestClient, TestServer};
+
+use wayc::protocol::wl_shm::WlShm;
+use wayland_cursor::load_theme;
+
+use ways::shm::{init_shm_global, with_buffer_contents};
+
+// the environment is shared by the whole process, so everything is tested in a single test
+#[test]
+fn cursor_theme() {
+    let dir1 = tempfile::tempdir().unwrap();
+    let dir2 = tempfile::tempdir().unwrap();
+
+    write_cursor(dir1.path(), "child", "text", &[(24, 2, 8, 0, 0xff11_2233)]);
+    fs::write(
+        dir1.path().join("child").join("index.theme"),
+        "[Icon Theme]\nName=Child\nInherits=missing, base\n",
+    )
+    .unwrap();
+    // the theme can be spread over several directories
+    write_cursor(dir2.path(), "child", "hand", &[(24, 4, 4, 0, 0)]);
+    write_cursor(
+        dir2.path(),
+        "base",
+        "left_ptr",
+        &[(24, 24, 24, 0, 0xff00_0000), (48, 48, 48, 0, 0xffff_ffff)],
+    );
+    write_cursor(
+        dir2.path(),
+        "base",
+        "wait",
+        &[(24, 8, 8, 100, 0), (24, 8, 8, 50, 0), (32, 16, 16, 100, 0)],
+    );
+    // an inheritance cycle
+    fs::write(dir2.path().join("base").join("index.theme"), "Inherits=child\n").unwrap();
+    write_cursor(dir1.path(), "default", "crosshair", &[(24, 6, 6, 0, 0)]);
+
+    std::env::set_var(
+        "XCURSOR_PATH",
+        format!("{}:{}", dir1.path().display(), dir2.path().display()),
+    );
+
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+
+    let theme = load_theme(Some("child"), 24, &shm);
+
+    // provided by the theme itself
+    let text = theme.get_cursor("text").unwrap();
+    assert_eq!(text.name(), "text");
+    assert_eq!(text.image_count(), 1);
+    assert_eq!(text.frame_info(0), Some((2, 8, 1, 4, 0)));
+    assert_eq!(text.frame_and_duration(1000), (0, 0));
+    assert!(text.frame_info(1).is_none());
+    assert!(text.frame_buffer(1).is_none());
+    assert!(theme.get_cursor("hand").is_some());
+
+    // inherited
+    let left_ptr = theme.get_cursor("left_ptr").unwrap();
+    assert_eq!(left_ptr.frame_info(0), Some((24, 24, 12, 12, 0)));
+
+    // animated, only the frames of the requested size are loaded
+    let wait = theme.get_cursor("wait").unwrap();
+    assert_eq!(wait.image_count(), 2);
+    assert_eq!(wait.frame_and_duration(0), (0, 100));
+    assert_eq!(wait.frame_and_duration(120), (1, 30));
+    assert_eq!(wait.frame(160), 0);
+
+    // fallback to the default theme
+    assert!(theme.get_cursor("crosshair").is_some());
+    assert!(theme.get_cursor("unknown").is_none());
+
+    // the aliases of the cursors are tried in order
+    assert_eq!(text.resolved_name(), "text");
+    let cursor = theme.get_cursor("xterm").unwrap();
+    assert_eq!(
+        (cursor.name().as_str(), cursor.resolved_name()),
+        ("xterm", "text")
+    );
+    assert_eq!(theme.get_cursor("default").unwrap().resolved_name(), "left_ptr");
+    // an alias in the theme is preferred to the cursor of the default theme
+    write_cursor(dir1.path(), "default", "ibeam", &[(24, 6, 6, 0, 0)]);
+    assert_eq!(theme.get_cursor("ibeam").unwrap().resolved_name(), "text");
+    assert_eq!(theme.get_cursor("cross").unwrap().resolved_name(), "crosshair");
+    assert_eq!(theme.get_cursor("pointer").unwrap().resolved_name(), "hand");
+    assert!(theme.get_cursor("zoom-in").is_none());
+
+    // the images are uploaded to the server
+    let text_buffer = text.frame_buffer(0).unwrap();
+    let left_ptr_buffer = left_ptr.frame_buffer(0).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    let ret = with_buffer_contents(&find_buffer(&server, text_buffer.as_ref().id()), |slice, data| {
+        assert_eq!((data.width, data.height, data.stride), (2, 8, 8));
+        slice.chunks(4).all(|pixel| pixel == [0x33, 0x22, 0x11, 0xff])
+    });
+    assert_eq!(ret, Ok(true));
+    let ret = with_buffer_contents(
+        &find_buffer(&server, left_ptr_buffer.as_ref().id()),
+        |slice, _| slice.chunks(4).all(|pixel| pixel == [0, 0, 0, 0xff]),
+    );
+    assert_eq!(ret, Ok(true));
+
+    // the images of the scaled cursors are loaded at the scaled size
+    let frame = left_ptr.scaled_frame(0, 1);
+    assert_eq!((frame.buffer_scale, frame.width, frame.hotspot_x), (1, 24, 12));
+    let frame = left_ptr.scaled_frame(0, 2);
+    assert_eq!((frame.buffer_scale, frame.width, frame.hotspot_x), (2, 24, 12));
+    assert!(*frame.buffer != *left_ptr_buffer);
+    // or at the nearest size, with a buffer scale matching it
+    let frame = left_ptr.scaled_frame(0, 3);
+    assert_eq!((frame.buffer_scale, frame.width, frame.height), (2, 24, 24));
+    let frame = text.scaled_frame(0, 2);
+    assert_eq!((frame.buffer_scale, frame.width, frame.hotspot_y), (1, 2, 4));
+    // the animations may differ between the sizes
+    let frame = wait.scaled_frame(120, 1);
+    assert_eq!((frame.frame, frame.duration, frame.width), (1, 30, 8));
+    let frame = wait.scaled_frame(120, 2);
+    assert_eq!((frame.frame, frame.duration, frame.width), (0, 0, 16));
+    roundtrip(&mut client, &mut server).unwrap();
+
+    // the nearest size is selected
+    let large_theme = load_theme(Some("child"), 40, &shm);
+    let left_ptr = large_theme.get_cursor("left_ptr").unwrap();
+    assert_eq!(left_ptr.frame_info(0), Some((48, 48, 24, 24, 0)));
+    roundtrip(&mut client, &mut server).unwrap();
+}
@@ -33,7 +33,7 @@ fn resource_destructor_request() {
 
     roundtrip(&mut client, &mut server).unwrap();
 
-    output.release();
+    output.release().unwrap();
 
     roundtrip(&mut client, &mut server).unwrap();
 
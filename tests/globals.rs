@@ -148,12 +148,12 @@ fn wrong_global() {
     server.display.create_global::<ServerCompositor, _>(1, |_, _| {});
 
     let mut client = TestClient::new(&server.socket_name);
-    let registry = client.display_proxy.get_registry();
+    let registry = client.display_proxy.get_registry().unwrap();
 
     // instantiate a wrong global, this should kill the client
     // but currently does not fail on native_lib
 
-    registry.bind::<WlOutput>(1, 1);
+    registry.bind::<WlOutput>(1, 1).unwrap();
 
     assert!(roundtrip(&mut client, &mut server).is_err());
 }
@@ -166,11 +166,11 @@ fn wrong_global_version() {
     server.display.create_global::<ServerCompositor, _>(1, |_, _| {});
 
     let mut client = TestClient::new(&server.socket_name);
-    let registry = client.display_proxy.get_registry();
+    let registry = client.display_proxy.get_registry().unwrap();
 
     // instantiate a global with wrong version, this should kill the client
 
-    registry.bind::<WlCompositor>(2, 1);
+    registry.bind::<WlCompositor>(2, 1).unwrap();
     assert!(roundtrip(&mut client, &mut server).is_err());
 }
 
@@ -182,11 +182,11 @@ fn invalid_global_version() {
     server.display.create_global::<ServerCompositor, _>(1, |_, _| {});
 
     let mut client = TestClient::new(&server.socket_name);
-    let registry = client.display_proxy.get_registry();
+    let registry = client.display_proxy.get_registry().unwrap();
 
     // instantiate a global with version 0, which is invalid this should kill the client
 
-    registry.bind::<WlCompositor>(0, 1);
+    registry.bind::<WlCompositor>(0, 1).unwrap();
 
     assert!(roundtrip(&mut client, &mut server).is_err());
 }
@@ -199,11 +199,11 @@ fn wrong_global_id() {
     server.display.create_global::<ServerCompositor, _>(1, |_, _| {});
 
     let mut client = TestClient::new(&server.socket_name);
-    let registry = client.display_proxy.get_registry();
+    let registry = client.display_proxy.get_registry().unwrap();
 
     // instantiate a global with wrong id, this should kill the client
 
-    registry.bind::<WlCompositor>(1, 3);
+    registry.bind::<WlCompositor>(1, 3).unwrap();
 
     assert!(roundtrip(&mut client, &mut server).is_err());
 }
@@ -0,0 +1,139 @@
+mod helpers;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use std::sync::{Arc, Mutex};
+
+use ways::protocol::{
+    wl_compositor as server_compositor, wl_seat as server_seat, wl_surface as server_surface,
+};
+
+use wayc::protocol::{wl_compositor, wl_display, wl_seat};
+
+struct SeatHandler {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+// only handles some of the events
+impl wl_seat::EventHandler for SeatHandler {
+    fn capabilities(&mut self, _: wayc::Main<wl_seat::WlSeat>, capabilities: wl_seat::Capability) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("capabilities {:?}", capabilities));
+    }
+
+    fn name(&mut self, _: wayc::Main<wl_seat::WlSeat>, name: String) {
+        self.events.lock().unwrap().push(format!("name {}", name));
+    }
+}
+
+#[test]
+fn client_event_handler() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .create_global::<server_seat::WlSeat, _>(5, |seat, _| {
+            seat.capabilities(server_seat::Capability::Keyboard);
+            seat.name("seat0".into());
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seat = manager.instantiate_exact::<wl_seat::WlSeat>(5).unwrap();
+    seat.assign_handler(SeatHandler {
+        events: events.clone(),
+    });
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["capabilities Keyboard".to_owned(), "name seat0".to_owned()]
+    );
+}
+
+struct CompositorHandler {
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl server_compositor::RequestHandler for CompositorHandler {
+    fn create_surface(
+        &mut self,
+        _: ways::Main<server_compositor::WlCompositor>,
+        id: ways::Main<server_surface::WlSurface>,
+    ) {
+        id.assign_handler(SurfaceHandler {
+            requests: self.requests.clone(),
+        });
+    }
+}
+
+// does not handle `destroy` nor `set_buffer_scale`
+struct SurfaceHandler {
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl server_surface::RequestHandler for SurfaceHandler {
+    fn damage(&mut self, _: ways::Main<server_surface::WlSurface>, x: i32, y: i32, width: i32, height: i32) {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(format!("damage {} {} {} {}", x, y, width, height));
+    }
+}
+
+#[test]
+fn server_request_handler() {
+    let mut server = TestServer::new();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let compositor_requests = requests.clone();
+    server
+        .display
+        .create_global::<server_compositor::WlCompositor, _>(4, move |compositor, _| {
+            compositor.assign_handler(CompositorHandler {
+                requests: compositor_requests.clone(),
+            });
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager
+        .instantiate_exact::<wl_compositor::WlCompositor>(4)
+        .unwrap();
+    let surface = compositor.create_surface().unwrap();
+    surface.damage(1, 2, 3, 4).unwrap();
+    // the destructors are no-ops by default
+    compositor.create_surface().unwrap().destroy().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(*requests.lock().unwrap(), vec!["damage 1 2 3 4".to_owned()]);
+
+    // the other requests are invalid_method errors of the display by default
+    surface.set_buffer_scale(2).unwrap();
+    let ret = roundtrip(&mut client, &mut server);
+    #[cfg(not(feature = "client_native"))]
+    {
+        assert!(ret.is_err());
+        let error = client.display.protocol_error().unwrap();
+        assert_eq!(error.code, wl_display::Error::InvalidMethod.to_raw());
+        assert_eq!(error.object_id, 1);
+        assert_eq!(error.object_interface, "wl_display");
+        assert_eq!(
+            error.message,
+            format!(
+                "wl_surface@{}.set_buffer_scale is not implemented",
+                surface.as_ref().id()
+            )
+        );
+    }
+    // the native lib reports the errors of the display as EINVAL rather than protocol errors
+    #[cfg(feature = "client_native")]
+    {
+        assert_eq!(ret.unwrap_err().kind(), ::std::io::ErrorKind::InvalidInput);
+        assert!(client.display.protocol_error().is_none());
+    }
+}
@@ -0,0 +1,49 @@
+// Fixture XCursor files, for the tests of wayland-cursor
+
+use std::fs;
+use std::path::Path;
+
+// an image of a cursor file: (nominal size, width, height, delay, pixel)
+pub type Image = (u32, u32, u32, u32, u32);
+
+pub fn xcursor(images: &[Image]) -> Vec<u8> {
+    fn push(data: &mut Vec<u8>, values: &[u32]) {
+        for v in values {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    let mut data = b"Xcur".to_vec();
+    push(&mut data, &[16, 0x1_0000, images.len() as u32]);
+    let mut position = 16 + 12 * images.len() as u32;
+    for &(size, width, height, _, _) in images {
+        push(&mut data, &[0xfffd_0002, size, position]);
+        position += 36 + 4 * width * height;
+    }
+    for &(size, width, height, delay, pixel) in images {
+        // the hotspot is at the center of the image
+        push(
+            &mut data,
+            &[
+                36,
+                0xfffd_0002,
+                size,
+                1,
+                width,
+                height,
+                width / 2,
+                height / 2,
+                delay,
+            ],
+        );
+        for _ in 0..width * height {
+            push(&mut data, &[pixel]);
+        }
+    }
+    data
+}
+
+pub fn write_cursor(dir: &Path, theme: &str, name: &str, images: &[Image]) {
+    let cursors = dir.join(theme).join("cursors");
+    fs::create_dir_all(&cursors).unwrap();
+    fs::write(cursors.join(name), xcursor(images)).unwrap();
+}
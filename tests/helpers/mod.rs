@@ -6,6 +6,8 @@
 pub extern crate wayland_client as wayc;
 pub extern crate wayland_server as ways;
 
+pub mod cursor;
+
 use std::cell::Cell;
 use std::ffi::{OsStr, OsString};
 use std::io;
@@ -41,6 +43,27 @@ impl TestServer {
     }
 }
 
+// find the resource with given id among the clients of the server
+fn find_resource<I>(server: &TestServer, id: u32) -> I
+where
+    I: self::ways::Interface + From<self::ways::Resource<I>> + AsRef<self::ways::Resource<I>>,
+{
+    server
+        .display
+        .clients()
+        .filter_map(|client| client.get_resource::<I>(id))
+        .next()
+        .unwrap()
+}
+
+pub fn find_buffer(server: &TestServer, id: u32) -> self::ways::protocol::wl_buffer::WlBuffer {
+    find_resource(server, id)
+}
+
+pub fn find_surface(server: &TestServer, id: u32) -> self::ways::protocol::wl_surface::WlSurface {
+    find_resource(server, id)
+}
+
 pub struct TestClient {
     pub display: Arc<self::wayc::Display>,
     pub display_proxy: self::wayc::Attached<self::wayc::protocol::wl_display::WlDisplay>,
@@ -90,6 +113,7 @@ pub fn roundtrip(client: &mut TestClient, server: &mut TestServer) -> io::Result
     client
         .display_proxy
         .sync()
+        .unwrap()
         .assign_mono(move |_, _| done2.set(true));
     while !done.get() {
         match client.display.flush() {
@@ -0,0 +1,134 @@
+mod helpers;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use std::sync::{Arc, Mutex};
+
+use ways::protocol::{wl_compositor as server_compositor, wl_seat as server_seat};
+
+use wayc::protocol::{wl_compositor, wl_keyboard, wl_output, wl_registry, wl_seat, wl_surface};
+
+#[test]
+fn received_messages() {
+    let mut server = TestServer::new();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let seat_requests = requests.clone();
+    server
+        .display
+        .create_global::<server_seat::WlSeat, _>(5, move |seat, _| {
+            let requests = seat_requests.clone();
+            seat.assign_mono(move |_, request| {
+                requests.lock().unwrap().push(format!("{:?}", request));
+            });
+            seat.capabilities(server_seat::Capability::Pointer | server_seat::Capability::Keyboard);
+            seat.name("seat0".into());
+        });
+    let compositor_requests = requests.clone();
+    server
+        .display
+        .create_global::<server_compositor::WlCompositor, _>(4, move |compositor, _| {
+            let requests = compositor_requests.clone();
+            compositor.assign_mono(move |_, request| {
+                requests.lock().unwrap().push(format!("{:?}", request));
+                if let server_compositor::Request::CreateSurface { id } = request {
+                    let requests = requests.clone();
+                    id.assign_mono(move |_, request| {
+                        requests.lock().unwrap().push(format!("{:?}", request));
+                    });
+                }
+            });
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seat_events = events.clone();
+    let seat = manager.instantiate_exact::<wl_seat::WlSeat>(5).unwrap();
+    seat.assign_mono(move |_, event| {
+        seat_events.lock().unwrap().push(format!("{:?}", event));
+    });
+    let compositor = manager
+        .instantiate_exact::<wl_compositor::WlCompositor>(4)
+        .unwrap();
+    let surface = compositor.create_surface().unwrap();
+    surface.attach(None, 1, -2).unwrap();
+    surface.damage(0, 0, 10, 20).unwrap();
+    let pointer = seat.get_pointer().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "wl_seat.capabilities(capabilities: Pointer | Keyboard)".to_owned(),
+            "wl_seat.name(name: \"seat0\")".to_owned(),
+        ]
+    );
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            format!(
+                "wl_compositor.create_surface(id: new id wl_surface@{})",
+                surface.as_ref().id()
+            ),
+            "wl_surface.attach(buffer: nil, x: 1, y: -2)".to_owned(),
+            "wl_surface.damage(x: 0, y: 0, width: 10, height: 20)".to_owned(),
+            format!(
+                "wl_seat.get_pointer(id: new id wl_pointer@{})",
+                pointer.as_ref().id()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn message_arguments() {
+    assert_eq!(
+        format!("{:?}", wl_compositor::Request::CreateSurface {}),
+        "wl_compositor.create_surface(id: new id wl_surface)"
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            wl_registry::Request::Bind {
+                name: 3,
+                id: ("wl_seat".into(), 5)
+            }
+        ),
+        "wl_registry.bind(name: 3, id: new id wl_seat (version 5))"
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            wl_output::Event::Geometry {
+                x: 0,
+                y: 10,
+                physical_width: 300,
+                physical_height: 200,
+                subpixel: wl_output::Subpixel::HorizontalRgb,
+                make: "make".into(),
+                model: "model".into(),
+                transform: wl_output::Transform::_90,
+            }
+        ),
+        "wl_output.geometry(x: 0, y: 10, physical_width: 300, physical_height: 200, \
+         subpixel: HorizontalRgb, make: \"make\", model: \"model\", transform: _90)"
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            wl_keyboard::Event::Keymap {
+                format: wl_keyboard::KeymapFormat::XkbV1,
+                fd: 7,
+                size: 42,
+            }
+        ),
+        "wl_keyboard.keymap(format: XkbV1, fd: fd 7, size: 42)"
+    );
+    assert_eq!(
+        format!("{:?}", wl_surface::Request::Destroy),
+        "wl_surface.destroy()"
+    );
+}
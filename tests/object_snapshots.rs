@@ -0,0 +1,174 @@
+mod helpers;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::wl_compositor;
+use ways::protocol::wl_data_device_manager::{Request as SDDMReq, WlDataDeviceManager as ServerDDMgr};
+use ways::protocol::wl_data_offer::WlDataOffer as ServerDO;
+use ways::protocol::wl_seat::WlSeat as ServerSeat;
+
+use wayc::protocol::wl_compositor::WlCompositor as ClientCompositor;
+use wayc::protocol::wl_data_device_manager::WlDataDeviceManager as ClientDDMgr;
+use wayc::protocol::wl_seat::WlSeat as ClientSeat;
+
+use std::sync::{Arc, Mutex};
+
+struct SurfaceData;
+
+#[test]
+fn server_snapshot_diff() {
+    let mut server = TestServer::new();
+    let clients = Arc::new(Mutex::new(Vec::new()));
+
+    server
+        .display
+        .create_global::<wl_compositor::WlCompositor, _>(1, {
+            let clients = clients.clone();
+            move |compositor, _| {
+                clients
+                    .lock()
+                    .unwrap()
+                    .push(compositor.as_ref().client().unwrap());
+                compositor.assign_mono(|_, request| {
+                    if let wl_compositor::Request::CreateSurface { id } = request {
+                        id.assign_mono(|_, _| {});
+                        id.as_ref().user_data().set(|| SurfaceData);
+                    }
+                });
+            }
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager.instantiate_exact::<ClientCompositor>(1).unwrap();
+    let surface = compositor.create_surface().unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_client = clients.lock().unwrap()[0].clone();
+    let before = server_client.snapshot_objects();
+    let info = before.find(surface.as_ref().id()).unwrap();
+    assert_eq!(info.interface, "wl_surface");
+    assert_eq!(info.version, 1);
+    assert!(info.age.is_some());
+    // the type of the user data is only recorded when built with rust 1.38 or later
+    if let Some(ref name) = info.user_data {
+        assert!(name.ends_with("SurfaceData"));
+    }
+    #[cfg(not(feature = "server_native"))]
+    {
+        let creator = info.creator.as_ref().unwrap();
+        assert_eq!(
+            creator.to_string(),
+            format!("wl_compositor@{}.create_surface", compositor.as_ref().id())
+        );
+        assert_eq!(before.children(compositor.as_ref().id()).count(), 1);
+    }
+
+    // leak two surfaces and destroy the first one
+    compositor.create_surface().unwrap();
+    compositor.create_surface().unwrap();
+    surface.destroy().unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let after = server_client.snapshot_objects();
+    let diff = after.diff(&before);
+    assert_eq!(diff.created.len(), 2);
+    assert_eq!(diff.destroyed.len(), 1);
+    assert_eq!(diff.destroyed[0].interface, "wl_surface");
+    assert_eq!(diff.growing_interfaces(), vec![("wl_surface", 1)]);
+    assert_eq!(after.count_by_interface()["wl_surface"], 2);
+}
+
+#[test]
+#[cfg(not(feature = "client_native"))]
+fn client_snapshot() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .create_global::<wl_compositor::WlCompositor, _>(1, |compositor, _| {
+            compositor.assign_mono(|_, _| {});
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager.instantiate_exact::<ClientCompositor>(1).unwrap();
+    let surface = compositor.create_surface().unwrap();
+    surface.as_ref().user_data().set(|| SurfaceData);
+
+    let snapshot = client.display.snapshot_objects();
+    assert_eq!(snapshot.find(1).unwrap().interface, "wl_display");
+    let info = snapshot.find(surface.as_ref().id()).unwrap();
+    assert_eq!(info.interface, "wl_surface");
+    // the type of the user data is only recorded when built with rust 1.38 or later
+    if let Some(ref name) = info.user_data {
+        assert!(name.ends_with("SurfaceData"));
+    }
+    assert_eq!(
+        info.creator.as_ref().unwrap().to_string(),
+        format!("wl_compositor@{}.create_surface", compositor.as_ref().id())
+    );
+    let compositor_info = snapshot.find(compositor.as_ref().id()).unwrap();
+    assert_eq!(compositor_info.creator.as_ref().unwrap().message, "bind");
+
+    let surface_id = surface.as_ref().id();
+    surface.destroy().unwrap();
+    let snapshot = client.display.snapshot_objects();
+    assert!(snapshot.find(surface_id).is_none());
+}
+
+#[test]
+fn client_snapshot_per_connection() {
+    let mut server = TestServer::new();
+    server.display.create_global::<ServerSeat, _>(1, |_, _| {});
+    server.display.create_global::<ServerDDMgr, _>(3, |ddmgr, _| {
+        ddmgr.assign_mono(|_, request| {
+            if let SDDMReq::GetDataDevice { id: ddevice, .. } = request {
+                ddevice.assign_mono(|_, _| {});
+                let offer = ddevice
+                    .as_ref()
+                    .client()
+                    .unwrap()
+                    .create_resource::<ServerDO>(ddevice.as_ref().version())
+                    .unwrap();
+                offer.assign_mono(|_, _| {});
+                ddevice.data_offer(&offer);
+            }
+        });
+    });
+
+    let mut client1 = TestClient::new(&server.socket_name);
+    let manager1 = wayc::GlobalManager::new(&client1.display_proxy);
+    let mut client2 = TestClient::new(&server.socket_name);
+    let manager2 = wayc::GlobalManager::new(&client2.display_proxy);
+
+    roundtrip(&mut client1, &mut server).unwrap();
+    roundtrip(&mut client2, &mut server).unwrap();
+
+    let seat = manager1.instantiate_exact::<ClientSeat>(1).unwrap();
+    let ddmgr = manager1.instantiate_exact::<ClientDDMgr>(3).unwrap();
+    let ddevice = ddmgr.get_data_device(&seat).unwrap();
+    ddevice.assign_mono(|_, _| {});
+    manager2.instantiate_exact::<ClientSeat>(1).unwrap();
+
+    roundtrip(&mut client1, &mut server).unwrap();
+    roundtrip(&mut client2, &mut server).unwrap();
+
+    // the objects created by the server are listed as well
+    let snapshot1 = client1.display.snapshot_objects();
+    assert_eq!(snapshot1.find(0xFF000000).unwrap().interface, "wl_data_offer");
+    assert_eq!(snapshot1.count_by_interface()["wl_data_device"], 1);
+
+    // but only in the snapshot of their own connection
+    let snapshot2 = client2.display.snapshot_objects();
+    assert!(snapshot2.find(0xFF000000).is_none());
+    assert!(!snapshot2.count_by_interface().contains_key("wl_data_device"));
+    assert_eq!(snapshot2.count_by_interface()["wl_seat"], 1);
+}
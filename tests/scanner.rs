@@ -9,7 +9,7 @@ use std::path::Path;
 use std::process::Command;
 
 use difference::{Changeset, Difference};
-use wayland_scanner::Side;
+use wayland_scanner::{Docs, Error, Mode, Scanner, Side};
 
 const PROTOCOL: &'static str = include_str!("./scanner_assets/protocol.xml");
 
@@ -132,3 +132,121 @@ fn server_code_generation() {
     );
     run_codegen_test(tempfile.path(), SERVER_CODE_TARGET);
 }
+
+#[test]
+fn rust_only_code_generation() {
+    for &side in &[Side::Client, Side::Server] {
+        let dir = tempfile::tempdir().unwrap();
+        let protocol = dir.path().join("protocol.xml");
+        let target = dir.path().join("protocol_api.rs");
+        std::fs::write(&protocol, PROTOCOL).unwrap();
+        wayland_scanner::generate_code_with_mode(&protocol, &target, side, &[], Mode::RustOnly);
+        let mut code = String::new();
+        File::open(&target).unwrap().read_to_string(&mut code).unwrap();
+        assert!(code.contains("impl super::MessageGroup for Request"));
+        for c_item in &[
+            "wl_interface",
+            "from_raw_c",
+            "as_raw_c_in",
+            "c_interface",
+            "sys::",
+        ] {
+            assert!(
+                !code.contains(c_item),
+                "`{}` generated in {:?} rust-only code",
+                c_item,
+                side
+            );
+        }
+    }
+}
+
+#[test]
+fn builder_code_generation() {
+    // the builder generates the same code as the free functions by default
+    let mut expected = Vec::new();
+    wayland_scanner::generate_code_streams(Cursor::new(PROTOCOL.as_bytes()), &mut expected, Side::Client);
+    let mut generated = Vec::new();
+    Scanner::from_stream(Cursor::new(PROTOCOL.as_bytes()), Side::Client)
+        .output_stream(&mut generated)
+        .generate()
+        .unwrap();
+    assert_eq!(generated, expected);
+
+    let docs = |docs| {
+        Scanner::from_stream(Cursor::new(PROTOCOL.as_bytes()), Side::Client)
+            .docs(docs)
+            .generate_tokens()
+            .unwrap()
+            .to_string()
+    };
+    let full = docs(Docs::Full);
+    let summary = docs(Docs::Summary);
+    let none = docs(Docs::None);
+    assert!(full.contains("core global object"));
+    assert!(full.contains("This global is special"));
+    assert!(summary.contains("core global object"));
+    assert!(!summary.contains("This global is special"));
+    assert!(!none.contains("core global object"));
+    assert!(none.len() < summary.len() && summary.len() < full.len());
+
+    match Scanner::from_stream(Cursor::new(PROTOCOL.as_bytes()), Side::Client).generate() {
+        Err(Error::Io(_)) => {}
+        _ => panic!("The code should not be generated without an output."),
+    }
+    match Scanner::new("./tests/scanner_assets/missing.xml", Side::Client).generate_tokens() {
+        Err(Error::Io(_)) => {}
+        _ => panic!("The protocol file should not be found."),
+    }
+}
+
+#[test]
+fn handler_code_generation() {
+    let generate = |side, handlers| {
+        Scanner::from_stream(Cursor::new(PROTOCOL.as_bytes()), side)
+            .handlers(handlers)
+            .generate_tokens()
+            .unwrap()
+            .to_string()
+    };
+    let client = generate(Side::Client, true);
+    assert!(client.contains("pub trait EventHandler"));
+    assert!(client.contains("impl < H : EventHandler > super :: HandledBy < H > for WlFoo"));
+    let server = generate(Side::Server, true);
+    assert!(server.contains("pub trait RequestHandler"));
+    assert!(server.contains("post_invalid_method"));
+    assert!(server.contains("\"wl_foo@{}.foo_it is not implemented\""));
+    assert!(!generate(Side::Client, false).contains("HandledBy"));
+    assert!(!generate(Side::Server, false).contains("HandledBy"));
+}
+
+#[test]
+fn builder_check() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("protocol_api.rs");
+    let scanner = || Scanner::from_stream(Cursor::new(PROTOCOL.as_bytes()), Side::Client).output(&target);
+
+    // a missing output is not up to date
+    assert!(!scanner().check().unwrap());
+    scanner().generate().unwrap();
+    assert!(scanner().check().unwrap());
+    // neither is an output generated with other options
+    assert!(!scanner().mode(Mode::RustOnly).check().unwrap());
+    // the check leaves the directory untouched
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // the code is formatted with the configuration of the directory of the output
+    std::fs::write(dir.path().join("rustfmt.toml"), "max_width = 60\n").unwrap();
+    assert!(!scanner().check().unwrap());
+    scanner().generate().unwrap();
+    assert!(scanner().check().unwrap());
+
+    let mut generated = Vec::new();
+    match Scanner::from_stream(Cursor::new(PROTOCOL.as_bytes()), Side::Client)
+        .output_stream(&mut generated)
+        .check()
+    {
+        Err(Error::Io(_)) => {}
+        _ => panic!("Only an output file can be checked."),
+    }
+}
@@ -16,7 +16,7 @@ pub mod wl_foo {
     use super::sys::common::{wl_argument, wl_array, wl_interface, wl_message};
     use super::{
         smallvec, types_null, AnonymousObject, Argument, ArgumentType, Interface, Main, Message, MessageDesc,
-        MessageGroup, Object, ObjectMetadata, Proxy, NULLPTR,
+        MessageGroup, Object, ObjectMetadata, Proxy, SendError, NULLPTR,
     };
     use std::os::raw::c_char;
     #[doc = "Possible cake kinds\n\nList of the possible kind of cake supported by the protocol."]
@@ -68,6 +68,39 @@ pub mod wl_foo {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+                Request::FooIt {
+                    ref number,
+                    ref unumber,
+                    ref text,
+                    ref float,
+                    ref file,
+                } => {
+                    f.write_str("wl_foo.foo_it(")?;
+                    f.write_str("number: ")?;
+                    write!(f, "{}", number)?;
+                    f.write_str(", unumber: ")?;
+                    write!(f, "{}", unumber)?;
+                    f.write_str(", text: ")?;
+                    write!(f, "{:?}", text)?;
+                    f.write_str(", float: ")?;
+                    write!(f, "{}", float)?;
+                    f.write_str(", file: ")?;
+                    write!(f, "fd {}", file)?;
+                    f.write_str(")")
+                }
+                Request::CreateBar { .. } => {
+                    f.write_str("wl_foo.create_bar(")?;
+                    f.write_str("id: ")?;
+                    f.write_str("new id wl_bar")?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[
             super::MessageDesc {
@@ -194,6 +227,21 @@ pub mod wl_foo {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+                Event::Cake { ref kind, ref amount } => {
+                    f.write_str("wl_foo.cake(")?;
+                    f.write_str("kind: ")?;
+                    write!(f, "{:?}", kind)?;
+                    f.write_str(", amount: ")?;
+                    write!(f, "{}", amount)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "cake",
@@ -313,7 +361,7 @@ pub mod wl_foo {
             text: String,
             float: f64,
             file: ::std::os::unix::io::RawFd,
-        ) -> () {
+        ) -> Result<(), SendError> {
             let msg = Request::FooIt {
                 number: number,
                 unumber: unumber,
@@ -321,12 +369,12 @@ pub mod wl_foo {
                 float: float,
                 file: file,
             };
-            self.0.send::<AnonymousObject>(msg, None);
+            self.0.send::<AnonymousObject>(msg, None).map(|_| ())
         }
         #[doc = "create a bar\n\nCreate a bar which will do its bar job."]
-        pub fn create_bar(&self) -> Main<super::wl_bar::WlBar> {
+        pub fn create_bar(&self) -> Result<Main<super::wl_bar::WlBar>, SendError> {
             let msg = Request::CreateBar {};
-            self.0.send(msg, None).unwrap()
+            self.0.send(msg, None).map(|o| o.unwrap())
         }
     }
     #[doc = r" The minimal object version supporting this request"]
@@ -372,7 +420,7 @@ pub mod wl_bar {
     use super::sys::common::{wl_argument, wl_array, wl_interface, wl_message};
     use super::{
         smallvec, types_null, AnonymousObject, Argument, ArgumentType, Interface, Main, Message, MessageDesc,
-        MessageGroup, Object, ObjectMetadata, Proxy, NULLPTR,
+        MessageGroup, Object, ObjectMetadata, Proxy, SendError, NULLPTR,
     };
     use std::os::raw::c_char;
     pub enum Request {
@@ -383,7 +431,7 @@ pub mod wl_bar {
             metadata: Vec<u8>,
             metametadata: Option<Vec<u8>>,
         },
-        #[doc = "release this bar\n\nNotify the compositor that you have finished using this bar.\n\nThis is a destructor, once sent this object cannot be used any longer."]
+        #[doc = "release this bar\n\nNotify the compositor that you have finished using this bar.\n\nThis is a destructor, once sent this object cannot be used any longer.\nOnly available since version 2 of the interface"]
         Release,
         #[doc = "ask for erronous bindings from wayland-scanner\n\nThis request tests argument names which can break wayland-scanner.\n\nOnly available since version 2 of the interface"]
         _Self {
@@ -399,6 +447,68 @@ pub mod wl_bar {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+                Request::BarDelivery {
+                    ref kind,
+                    ref target,
+                    ref metadata,
+                    ref metametadata,
+                } => {
+                    f.write_str("wl_bar.bar_delivery(")?;
+                    f.write_str("kind: ")?;
+                    write!(f, "{:?}", kind)?;
+                    f.write_str(", target: ")?;
+                    write!(f, "{}@{}", "wl_foo", target.as_ref().id())?;
+                    f.write_str(", metadata: ")?;
+                    write!(f, "array[{}]", metadata.len())?;
+                    f.write_str(", metametadata: ")?;
+                    match *metametadata {
+                        Some(ref metametadata) => {
+                            write!(f, "array[{}]", metametadata.len())?;
+                        }
+                        None => f.write_str("nil")?,
+                    }
+                    f.write_str(")")
+                }
+                Request::Release => {
+                    f.write_str("wl_bar.release(")?;
+                    f.write_str(")")
+                }
+                Request::_Self {
+                    ref _self,
+                    ref _mut,
+                    ref object,
+                    ref ___object,
+                    ref handler,
+                    ref ___handler,
+                    ref request,
+                    ref event,
+                } => {
+                    f.write_str("wl_bar.self(")?;
+                    f.write_str("self: ")?;
+                    write!(f, "{}", _self)?;
+                    f.write_str(", mut: ")?;
+                    write!(f, "{}", _mut)?;
+                    f.write_str(", object: ")?;
+                    write!(f, "{}", object)?;
+                    f.write_str(", __object: ")?;
+                    write!(f, "{}", ___object)?;
+                    f.write_str(", handler: ")?;
+                    write!(f, "{}", handler)?;
+                    f.write_str(", __handler: ")?;
+                    write!(f, "{}", ___handler)?;
+                    f.write_str(", request: ")?;
+                    write!(f, "{}", request)?;
+                    f.write_str(", event: ")?;
+                    write!(f, "{}", event)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[
             super::MessageDesc {
@@ -414,7 +524,7 @@ pub mod wl_bar {
             },
             super::MessageDesc {
                 name: "release",
-                since: 1,
+                since: 2,
                 signature: &[],
                 destructor: true,
             },
@@ -454,7 +564,7 @@ pub mod wl_bar {
             match *self {
                 Request::__nonexhaustive => unreachable!(),
                 Request::BarDelivery { .. } => 2,
-                Request::Release => 1,
+                Request::Release => 2,
                 Request::_Self { .. } => 2,
             }
         }
@@ -596,6 +706,42 @@ pub mod wl_bar {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+                Event::_Self {
+                    ref _self,
+                    ref _mut,
+                    ref object,
+                    ref ___object,
+                    ref handler,
+                    ref ___handler,
+                    ref request,
+                    ref event,
+                } => {
+                    f.write_str("wl_bar.self(")?;
+                    f.write_str("self: ")?;
+                    write!(f, "{}", _self)?;
+                    f.write_str(", mut: ")?;
+                    write!(f, "{}", _mut)?;
+                    f.write_str(", object: ")?;
+                    write!(f, "{}", object)?;
+                    f.write_str(", __object: ")?;
+                    write!(f, "{}", ___object)?;
+                    f.write_str(", handler: ")?;
+                    write!(f, "{}", handler)?;
+                    f.write_str(", __handler: ")?;
+                    write!(f, "{}", ___handler)?;
+                    f.write_str(", request: ")?;
+                    write!(f, "{}", request)?;
+                    f.write_str(", event: ")?;
+                    write!(f, "{}", event)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "self",
@@ -758,7 +904,7 @@ pub mod wl_bar {
         type Request = Request;
         type Event = Event;
         const NAME: &'static str = "wl_bar";
-        const VERSION: u32 = 1;
+        const VERSION: u32 = 2;
         fn c_interface() -> *const wl_interface {
             unsafe { &wl_bar_interface }
         }
@@ -771,19 +917,19 @@ pub mod wl_bar {
             target: &super::wl_foo::WlFoo,
             metadata: Vec<u8>,
             metametadata: Option<Vec<u8>>,
-        ) -> () {
+        ) -> Result<(), SendError> {
             let msg = Request::BarDelivery {
                 kind: kind,
                 target: target.clone(),
                 metadata: metadata,
                 metametadata: metametadata,
             };
-            self.0.send::<AnonymousObject>(msg, None);
+            self.0.send::<AnonymousObject>(msg, None).map(|_| ())
         }
-        #[doc = "release this bar\n\nNotify the compositor that you have finished using this bar.\n\nThis is a destructor, you cannot send requests to this object any longer once this method is called."]
-        pub fn release(&self) -> () {
+        #[doc = "release this bar\n\nNotify the compositor that you have finished using this bar.\n\nThis is a destructor, you cannot send requests to this object any longer once this method is called.\nOnly available since version 2 of the interface."]
+        pub fn release(&self) -> Result<(), SendError> {
             let msg = Request::Release;
-            self.0.send::<AnonymousObject>(msg, None);
+            self.0.send::<AnonymousObject>(msg, None).map(|_| ())
         }
         #[doc = "ask for erronous bindings from wayland-scanner\n\nThis request tests argument names which can break wayland-scanner.\n\nOnly available since version 2 of the interface."]
         pub fn _self(
@@ -796,7 +942,7 @@ pub mod wl_bar {
             ___handler: u32,
             request: u32,
             event: u32,
-        ) -> () {
+        ) -> Result<(), SendError> {
             let msg = Request::_Self {
                 _self: _self,
                 _mut: _mut,
@@ -807,13 +953,13 @@ pub mod wl_bar {
                 request: request,
                 event: event,
             };
-            self.0.send::<AnonymousObject>(msg, None);
+            self.0.send::<AnonymousObject>(msg, None).map(|_| ())
         }
     }
     #[doc = r" The minimal object version supporting this request"]
     pub const REQ_BAR_DELIVERY_SINCE: u32 = 2u32;
     #[doc = r" The minimal object version supporting this request"]
-    pub const REQ_RELEASE_SINCE: u32 = 1u32;
+    pub const REQ_RELEASE_SINCE: u32 = 2u32;
     #[doc = r" The minimal object version supporting this request"]
     pub const REQ_SELF_SINCE: u32 = 2u32;
     #[doc = r" The minimal object version supporting this event"]
@@ -833,7 +979,7 @@ pub mod wl_bar {
         },
         wl_message {
             name: b"release\0" as *const u8 as *const c_char,
-            signature: b"\0" as *const u8 as *const c_char,
+            signature: b"2\0" as *const u8 as *const c_char,
             types: unsafe { &types_null as *const _ },
         },
         wl_message {
@@ -851,7 +997,7 @@ pub mod wl_bar {
     #[doc = r" C representation of this interface, for interop"]
     pub static mut wl_bar_interface: wl_interface = wl_interface {
         name: b"wl_bar\0" as *const u8 as *const c_char,
-        version: 1,
+        version: 2,
         request_count: 3,
         requests: unsafe { &wl_bar_requests as *const _ },
         event_count: 1,
@@ -864,13 +1010,20 @@ pub mod wl_display {
     use super::sys::common::{wl_argument, wl_array, wl_interface, wl_message};
     use super::{
         smallvec, types_null, AnonymousObject, Argument, ArgumentType, Interface, Main, Message, MessageDesc,
-        MessageGroup, Object, ObjectMetadata, Proxy, NULLPTR,
+        MessageGroup, Object, ObjectMetadata, Proxy, SendError, NULLPTR,
     };
     use std::os::raw::c_char;
     pub enum Request {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[];
         type Map = super::ProxyMap;
@@ -922,6 +1075,13 @@ pub mod wl_display {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[];
         type Map = super::ProxyMap;
@@ -1015,7 +1175,7 @@ pub mod wl_registry {
     use super::sys::common::{wl_argument, wl_array, wl_interface, wl_message};
     use super::{
         smallvec, types_null, AnonymousObject, Argument, ArgumentType, Interface, Main, Message, MessageDesc,
-        MessageGroup, Object, ObjectMetadata, Proxy, NULLPTR,
+        MessageGroup, Object, ObjectMetadata, Proxy, SendError, NULLPTR,
     };
     use std::os::raw::c_char;
     pub enum Request {
@@ -1024,6 +1184,21 @@ pub mod wl_registry {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+                Request::Bind { ref name, ref id } => {
+                    f.write_str("wl_registry.bind(")?;
+                    f.write_str("name: ")?;
+                    write!(f, "{}", name)?;
+                    f.write_str(", id: ")?;
+                    write!(f, "new id {} (version {})", id.0, id.1)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "bind",
@@ -1104,6 +1279,13 @@ pub mod wl_registry {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[];
         type Map = super::ProxyMap;
@@ -1186,12 +1368,12 @@ pub mod wl_registry {
             &self,
             version: u32,
             name: u32,
-        ) -> Main<T> {
+        ) -> Result<Main<T>, SendError> {
             let msg = Request::Bind {
                 name: name,
                 id: (T::NAME.into(), version),
             };
-            self.0.send(msg, Some(version)).unwrap()
+            self.0.send(msg, Some(version)).map(|o| o.unwrap())
         }
     }
     #[doc = r" The minimal object version supporting this request"]
@@ -1218,13 +1400,20 @@ pub mod wl_callback {
     use super::sys::common::{wl_argument, wl_array, wl_interface, wl_message};
     use super::{
         smallvec, types_null, AnonymousObject, Argument, ArgumentType, Interface, Main, Message, MessageDesc,
-        MessageGroup, Object, ObjectMetadata, Proxy, NULLPTR,
+        MessageGroup, Object, ObjectMetadata, Proxy, SendError, NULLPTR,
     };
     use std::os::raw::c_char;
     pub enum Request {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[];
         type Map = super::ProxyMap;
@@ -1278,6 +1467,19 @@ pub mod wl_callback {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+                Event::Done { ref callback_data } => {
+                    f.write_str("wl_callback.done(")?;
+                    f.write_str("callback_data: ")?;
+                    write!(f, "{}", callback_data)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "done",
@@ -68,6 +68,39 @@ pub mod wl_foo {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+                Request::FooIt {
+                    ref number,
+                    ref unumber,
+                    ref text,
+                    ref float,
+                    ref file,
+                } => {
+                    f.write_str("wl_foo.foo_it(")?;
+                    f.write_str("number: ")?;
+                    write!(f, "{}", number)?;
+                    f.write_str(", unumber: ")?;
+                    write!(f, "{}", unumber)?;
+                    f.write_str(", text: ")?;
+                    write!(f, "{:?}", text)?;
+                    f.write_str(", float: ")?;
+                    write!(f, "{}", float)?;
+                    f.write_str(", file: ")?;
+                    write!(f, "fd {}", file)?;
+                    f.write_str(")")
+                }
+                Request::CreateBar { ref id } => {
+                    f.write_str("wl_foo.create_bar(")?;
+                    f.write_str("id: ")?;
+                    write!(f, "new id {}@{}", "wl_bar", id.as_ref().id())?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[
             super::MessageDesc {
@@ -224,6 +257,21 @@ pub mod wl_foo {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+                Event::Cake { ref kind, ref amount } => {
+                    f.write_str("wl_foo.cake(")?;
+                    f.write_str("kind: ")?;
+                    write!(f, "{:?}", kind)?;
+                    f.write_str(", amount: ")?;
+                    write!(f, "{}", amount)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "cake",
@@ -383,7 +431,7 @@ pub mod wl_bar {
             metadata: Vec<u8>,
             metametadata: Option<Vec<u8>>,
         },
-        #[doc = "release this bar\n\nNotify the compositor that you have finished using this bar.\n\nThis is a destructor, once received this object cannot be used any longer."]
+        #[doc = "release this bar\n\nNotify the compositor that you have finished using this bar.\n\nThis is a destructor, once received this object cannot be used any longer.\nOnly available since version 2 of the interface"]
         Release,
         #[doc = "ask for erronous bindings from wayland-scanner\n\nThis request tests argument names which can break wayland-scanner.\n\nOnly available since version 2 of the interface"]
         _Self {
@@ -399,6 +447,68 @@ pub mod wl_bar {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+                Request::BarDelivery {
+                    ref kind,
+                    ref target,
+                    ref metadata,
+                    ref metametadata,
+                } => {
+                    f.write_str("wl_bar.bar_delivery(")?;
+                    f.write_str("kind: ")?;
+                    write!(f, "{:?}", kind)?;
+                    f.write_str(", target: ")?;
+                    write!(f, "{}@{}", "wl_foo", target.as_ref().id())?;
+                    f.write_str(", metadata: ")?;
+                    write!(f, "array[{}]", metadata.len())?;
+                    f.write_str(", metametadata: ")?;
+                    match *metametadata {
+                        Some(ref metametadata) => {
+                            write!(f, "array[{}]", metametadata.len())?;
+                        }
+                        None => f.write_str("nil")?,
+                    }
+                    f.write_str(")")
+                }
+                Request::Release => {
+                    f.write_str("wl_bar.release(")?;
+                    f.write_str(")")
+                }
+                Request::_Self {
+                    ref _self,
+                    ref _mut,
+                    ref object,
+                    ref ___object,
+                    ref handler,
+                    ref ___handler,
+                    ref request,
+                    ref event,
+                } => {
+                    f.write_str("wl_bar.self(")?;
+                    f.write_str("self: ")?;
+                    write!(f, "{}", _self)?;
+                    f.write_str(", mut: ")?;
+                    write!(f, "{}", _mut)?;
+                    f.write_str(", object: ")?;
+                    write!(f, "{}", object)?;
+                    f.write_str(", __object: ")?;
+                    write!(f, "{}", ___object)?;
+                    f.write_str(", handler: ")?;
+                    write!(f, "{}", handler)?;
+                    f.write_str(", __handler: ")?;
+                    write!(f, "{}", ___handler)?;
+                    f.write_str(", request: ")?;
+                    write!(f, "{}", request)?;
+                    f.write_str(", event: ")?;
+                    write!(f, "{}", event)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[
             super::MessageDesc {
@@ -414,7 +524,7 @@ pub mod wl_bar {
             },
             super::MessageDesc {
                 name: "release",
-                since: 1,
+                since: 2,
                 signature: &[],
                 destructor: true,
             },
@@ -454,7 +564,7 @@ pub mod wl_bar {
             match *self {
                 Request::__nonexhaustive => unreachable!(),
                 Request::BarDelivery { .. } => 2,
-                Request::Release => 1,
+                Request::Release => 2,
                 Request::_Self { .. } => 2,
             }
         }
@@ -634,6 +744,42 @@ pub mod wl_bar {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+                Event::_Self {
+                    ref _self,
+                    ref _mut,
+                    ref object,
+                    ref ___object,
+                    ref handler,
+                    ref ___handler,
+                    ref request,
+                    ref event,
+                } => {
+                    f.write_str("wl_bar.self(")?;
+                    f.write_str("self: ")?;
+                    write!(f, "{}", _self)?;
+                    f.write_str(", mut: ")?;
+                    write!(f, "{}", _mut)?;
+                    f.write_str(", object: ")?;
+                    write!(f, "{}", object)?;
+                    f.write_str(", __object: ")?;
+                    write!(f, "{}", ___object)?;
+                    f.write_str(", handler: ")?;
+                    write!(f, "{}", handler)?;
+                    f.write_str(", __handler: ")?;
+                    write!(f, "{}", ___handler)?;
+                    f.write_str(", request: ")?;
+                    write!(f, "{}", request)?;
+                    f.write_str(", event: ")?;
+                    write!(f, "{}", event)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "self",
@@ -766,7 +912,7 @@ pub mod wl_bar {
         type Request = Request;
         type Event = Event;
         const NAME: &'static str = "wl_bar";
-        const VERSION: u32 = 1;
+        const VERSION: u32 = 2;
         fn c_interface() -> *const wl_interface {
             unsafe { &wl_bar_interface }
         }
@@ -800,7 +946,7 @@ pub mod wl_bar {
     #[doc = r" The minimal object version supporting this request"]
     pub const REQ_BAR_DELIVERY_SINCE: u32 = 2u32;
     #[doc = r" The minimal object version supporting this request"]
-    pub const REQ_RELEASE_SINCE: u32 = 1u32;
+    pub const REQ_RELEASE_SINCE: u32 = 2u32;
     #[doc = r" The minimal object version supporting this request"]
     pub const REQ_SELF_SINCE: u32 = 2u32;
     #[doc = r" The minimal object version supporting this event"]
@@ -820,7 +966,7 @@ pub mod wl_bar {
         },
         wl_message {
             name: b"release\0" as *const u8 as *const c_char,
-            signature: b"\0" as *const u8 as *const c_char,
+            signature: b"2\0" as *const u8 as *const c_char,
             types: unsafe { &types_null as *const _ },
         },
         wl_message {
@@ -838,7 +984,7 @@ pub mod wl_bar {
     #[doc = r" C representation of this interface, for interop"]
     pub static mut wl_bar_interface: wl_interface = wl_interface {
         name: b"wl_bar\0" as *const u8 as *const c_char,
-        version: 1,
+        version: 2,
         request_count: 3,
         requests: unsafe { &wl_bar_requests as *const _ },
         event_count: 1,
@@ -858,6 +1004,13 @@ pub mod wl_callback {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Request {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Request::__nonexhaustive => unreachable!(),
+            }
+        }
+    }
     impl super::MessageGroup for Request {
         const MESSAGES: &'static [super::MessageDesc] = &[];
         type Map = super::ResourceMap;
@@ -911,6 +1064,19 @@ pub mod wl_callback {
         #[doc(hidden)]
         __nonexhaustive,
     }
+    impl ::std::fmt::Debug for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::__nonexhaustive => unreachable!(),
+                Event::Done { ref callback_data } => {
+                    f.write_str("wl_callback.done(")?;
+                    f.write_str("callback_data: ")?;
+                    write!(f, "{}", callback_data)?;
+                    f.write_str(")")
+                }
+            }
+        }
+    }
     impl super::MessageGroup for Event {
         const MESSAGES: &'static [super::MessageDesc] = &[super::MessageDesc {
             name: "done",
@@ -0,0 +1,118 @@
+extern crate bitflags;
+extern crate wayland_client;
+extern crate wayland_commons;
+extern crate wayland_scanner_macros;
+extern crate wayland_server;
+
+mod helpers;
+
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use helpers::{roundtrip, ways, TestClient, TestServer};
+
+mod client_xdg {
+    pub(crate) use wayland_client::protocol::{wl_output, wl_seat, wl_surface};
+
+    wayland_scanner_macros::wayland_protocol!(
+        "./wayland-protocols/protocols/stable/xdg-shell/xdg-shell.xml",
+        client
+    );
+}
+
+mod server_xdg {
+    pub(crate) use wayland_server::protocol::{wl_output, wl_seat, wl_surface};
+
+    wayland_scanner_macros::wayland_protocol!(
+        r"./wayland-protocols/protocols/stable/xdg-shell/xdg-shell.xml",
+        server,
+    );
+}
+
+mod client_xdg_rust_only {
+    pub(crate) use wayland_client::protocol::{wl_output, wl_seat, wl_surface};
+
+    wayland_scanner_macros::wayland_protocol!(
+        "./wayland-protocols/protocols/stable/xdg-shell/xdg-shell.xml",
+        client,
+        rust_only
+    );
+}
+
+use client_xdg::xdg_wm_base::{self as client_wm_base, XdgWmBase as ClientWmBase};
+use server_xdg::xdg_wm_base::{self as server_wm_base, XdgWmBase as ServerWmBase};
+
+#[test]
+fn macro_generated_protocol() {
+    assert_eq!(<ClientWmBase as wayland_commons::Interface>::NAME, "xdg_wm_base");
+    assert_eq!(<ServerWmBase as wayland_commons::Interface>::VERSION, 2);
+
+    let mut server = TestServer::new();
+    let pong = Rc::new(Cell::new(None));
+    let pong2 = pong.clone();
+    server
+        .display
+        .create_global::<ServerWmBase, _>(2, move |wm_base: ways::Main<ServerWmBase>, _| {
+            let pong = pong2.clone();
+            wm_base.assign_mono(move |_, request| {
+                if let server_wm_base::Request::Pong { serial } = request {
+                    pong.set(Some(serial));
+                }
+            });
+            wm_base.ping(42);
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = helpers::wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let wm_base = manager.instantiate_exact::<ClientWmBase>(2).unwrap();
+    wm_base.assign_mono(|wm_base, event| {
+        if let client_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial).unwrap();
+        }
+    });
+    roundtrip(&mut client, &mut server).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(pong.get(), Some(42));
+}
+
+#[test]
+fn macro_generated_rust_only_protocol() {
+    use client_xdg_rust_only::xdg_wm_base::{Event, XdgWmBase};
+
+    let mut server = TestServer::new();
+    let pong = Rc::new(Cell::new(None));
+    let pong2 = pong.clone();
+    server
+        .display
+        .create_global::<ServerWmBase, _>(2, move |wm_base: ways::Main<ServerWmBase>, _| {
+            let pong = pong2.clone();
+            wm_base.assign_mono(move |_, request| {
+                if let server_wm_base::Request::Pong { serial } = request {
+                    pong.set(Some(serial));
+                }
+            });
+            wm_base.ping(7);
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    // this code can't be used with the system library, which the features of another crate
+    // of the workspace may enable, and which alone gives access to the C pointers
+    if panic::catch_unwind(AssertUnwindSafe(|| client.display_proxy.as_ref().c_ptr())).is_ok() {
+        return;
+    }
+    let manager = helpers::wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let wm_base = manager.instantiate_exact::<XdgWmBase>(2).unwrap();
+    wm_base.assign_mono(|wm_base, event| {
+        if let Event::Ping { serial } = event {
+            wm_base.pong(serial).unwrap();
+        }
+    });
+    roundtrip(&mut client, &mut server).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(pong.get(), Some(7));
+}
@@ -0,0 +1,258 @@
+extern crate tempfile;
+extern crate wayland_scanner;
+
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use wayland_scanner::{
+    generate_code_with_interface_paths, validate, validate_stream, Error, InterfacePaths, Side,
+};
+
+// the (line, column, message) of the diagnostics of a protocol
+fn diagnostics(protocol: &str) -> Vec<(u64, u64, String)> {
+    validate_stream(Cursor::new(protocol.as_bytes()))
+        .unwrap_err()
+        .diagnostics
+        .into_iter()
+        .map(|d| (d.line, d.column, d.message))
+        .collect()
+}
+
+fn visit(dir: &Path, files: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            visit(&path, files);
+        } else if path.extension().map(|e| e == "xml").unwrap_or(false) {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+}
+
+#[test]
+fn bundled_protocols_are_valid() {
+    let mut files = vec![
+        "./wayland-client/wayland.xml".to_owned(),
+        "./tests/scanner_assets/protocol.xml".to_owned(),
+    ];
+    visit(Path::new("./wayland-protocols"), &mut files);
+    assert!(files.len() > 10);
+    for file in files {
+        if let Err(e) = validate(&file) {
+            panic!("{}", e);
+        }
+    }
+}
+
+#[test]
+fn semantic_errors() {
+    let protocol = r#"<?xml version="1.0" encoding="UTF-8"?>
+<protocol name="broken">
+  <interface name="foo" version="2">
+    <request name="create">
+      <arg name="id" type="new_id"/>
+      <arg name="mode" type="uint" enum="mode"/>
+      <arg name="kind" type="uint" enum="bar.kind"/>
+      <arg name="format" type="uint" enum="wl_shm.format"/>
+    </request>
+    <request name="late" since="3"/>
+    <request name="create"/>
+    <event name="configure" since="2"/>
+    <event name="object">
+      <arg name="id" type="new_id"/>
+      <arg name="flags" type="string" enum="flags"/>
+    </event>
+    <enum name="flags" bitfield="true">
+      <entry name="a" value="0x1"/>
+      <entry name="b" value="0x2" since="3"/>
+      <entry name="a" value="0x4"/>
+    </enum>
+  </interface>
+  <interface name="bar" version="1">
+    <request name="dup">
+      <arg name="x" type="int"/>
+      <arg name="x" type="int"/>
+    </request>
+  </interface>
+  <interface name="foo" version="1"/>
+</protocol>
+"#;
+    let expected: Vec<(u64, u64, String)> =
+        vec![
+        (6, 7, "argument `mode` of `foo.create` references enum `mode`, which does not exist"),
+        (7, 7, "argument `kind` of `foo.create` references enum `bar.kind`, which does not exist"),
+        (
+            10,
+            5,
+            "request `foo.late` is available since version 3, but `foo` only has version 2",
+        ),
+        (11, 5, "duplicate request of `foo` `create`, first defined at 4:5"),
+        (
+            13,
+            5,
+            "event `foo.object` is available since version 1, lower than the version 2 of a previous event",
+        ),
+        (
+            14,
+            7,
+            "argument `id` of event `foo.object` is a `new_id` without an interface, which is only \
+             supported in requests like `wl_registry.bind`",
+        ),
+        (
+            15,
+            7,
+            "argument `flags` of `foo.object` references enum `flags`, but is not an `int` or `uint`",
+        ),
+        (
+            19,
+            7,
+            "entry `foo.flags.b` is available since version 3, but `foo` only has version 2",
+        ),
+        (20, 7, "duplicate entry of `foo.flags` `a`, first defined at 18:7"),
+        (26, 7, "duplicate argument of `bar.dup` `x`, first defined at 25:7"),
+        (29, 3, "duplicate interface of protocol `broken` `foo`, first defined at 3:3"),
+    ]
+        .into_iter()
+        .map(|(line, column, msg)| (line, column, msg.to_owned()))
+        .collect();
+    assert_eq!(diagnostics(protocol), expected);
+}
+
+#[test]
+fn parse_errors() {
+    // invalid values are reported along with the other problems
+    let protocol = r#"<protocol name="broken">
+  <interface name="foo" version="one">
+    <request name="req">
+      <arg name="a" type="integer"/>
+      <arg type="int"/>
+      <frobnicate/>
+    </request>
+  </interface>
+</protocol>
+"#;
+    assert_eq!(
+        diagnostics(protocol),
+        vec![
+            (2, 3, "invalid value `one` for attribute `version`".to_owned()),
+            (4, 7, "invalid argument type `integer`".to_owned()),
+            (5, 7, "missing attribute `name` on `<arg>`".to_owned()),
+            (
+                6,
+                7,
+                "unexpected element `<frobnicate>` in `<request>`".to_owned()
+            ),
+        ]
+    );
+
+    // malformed XML stops the parsing
+    let protocol = "<protocol name=\"broken\">\n  <interface name=\"foo\">\n  </request>\n</protocol>\n";
+    let found = diagnostics(protocol);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, 3);
+    assert!(found[0].2.starts_with("malformed XML"));
+
+    let protocol = "<protocol name=\"broken\">\n  <interface name=\"foo\">\n";
+    let found = diagnostics(protocol);
+    assert_eq!(found.len(), 1);
+    assert!(found[0].2.contains("end of file") || found[0].2.starts_with("malformed XML"));
+}
+
+#[test]
+fn file_diagnostics() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("broken.xml");
+    fs::write(
+        &path,
+        "<protocol name=\"broken\">\n  <interface name=\"foo\" version=\"1\">\n    <event name=\"e\" since=\"2\"/>\n  </interface>\n</protocol>\n",
+    )
+    .unwrap();
+    match validate(&path) {
+        Err(Error::Protocol(e)) => {
+            assert_eq!(e.diagnostics.len(), 1);
+            assert_eq!(e.diagnostics[0].file.as_ref(), Some(&path));
+            assert_eq!(
+                e.to_string(),
+                format!(
+                    "{}:3:5: event `foo.e` is available since version 2, but `foo` only has version 1",
+                    path.display()
+                )
+            );
+        }
+        _ => panic!("The protocol should be invalid."),
+    }
+    match validate(dir.path().join("missing.xml")) {
+        Err(Error::Io(_)) => {}
+        _ => panic!("The file should not be found."),
+    }
+}
+
+#[test]
+fn interface_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("extension.xml");
+    let target = dir.path().join("extension_api.rs");
+    fs::write(
+        &path,
+        r#"<protocol name="extension">
+  <interface name="ext_manager" version="1">
+    <request name="get_ext">
+      <arg name="id" type="new_id" interface="ext_surface"/>
+      <arg name="surface" type="object" interface="wl_surface"/>
+      <arg name="toplevel" type="object" interface="xdg_toplevel"/>
+    </request>
+  </interface>
+  <interface name="ext_surface" version="1">
+    <event name="state">
+      <arg name="state" type="uint" enum="xdg_toplevel.state"/>
+    </event>
+  </interface>
+</protocol>
+"#,
+    )
+    .unwrap();
+
+    // xdg_toplevel is unknown, only its first reference is reported
+    let mut paths = InterfacePaths::core(Side::Client);
+    match generate_code_with_interface_paths(&path, &target, Side::Client, &[], &paths) {
+        Err(Error::Protocol(e)) => assert_eq!(
+            e.to_string(),
+            format!(
+                "{}:6:7: interface `xdg_toplevel` is not defined by this protocol, and its path is unknown",
+                path.display()
+            )
+        ),
+        _ => panic!("xdg_toplevel should not be resolved."),
+    }
+
+    paths
+        .insert_protocol(
+            "./wayland-protocols/protocols/stable/xdg-shell/xdg-shell.xml",
+            "crate::xdg_shell::client",
+        )
+        .unwrap();
+    assert_eq!(
+        paths.get("xdg_popup"),
+        Some("crate::xdg_shell::client::xdg_popup")
+    );
+    generate_code_with_interface_paths(&path, &target, Side::Client, &[], &paths).unwrap();
+    let code = fs::read_to_string(&target).unwrap();
+    assert!(code.contains("use wayland_client::protocol::wl_surface"));
+    assert!(code.contains("use crate::xdg_shell::client::xdg_toplevel"));
+    assert!(!code.contains("use wayland_client::protocol::wl_output"));
+
+    // invalid paths are reported as well
+    paths.insert("wl_surface", "not a path");
+    match generate_code_with_interface_paths(&path, &target, Side::Client, &[], &paths) {
+        Err(Error::Protocol(e)) => {
+            assert_eq!(e.diagnostics.len(), 1);
+            assert_eq!(e.diagnostics[0].line, 5);
+            assert_eq!(
+                e.diagnostics[0].message,
+                "invalid path `not a path` for interface `wl_surface`"
+            );
+        }
+        _ => panic!("The path of wl_surface should be invalid."),
+    }
+}
@@ -15,4 +15,5 @@ fn send_sync_server() {
     ensure_both::<ways::Resource<::ways::protocol::wl_callback::WlCallback>>();
     ensure_both::<::ways::protocol::wl_callback::WlCallback>();
     ensure_both::<::ways::Client>();
+    ensure_both::<::ways::DisplayHandle>();
 }
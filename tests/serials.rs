@@ -0,0 +1,88 @@
+mod helpers;
+
+use std::rc::Rc;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::{wl_keyboard, wl_pointer, wl_seat};
+use ways::serial::SerialCounter;
+
+use wayc::protocol::wl_seat::WlSeat;
+use wayc::serial::{SeatSerials, Serial, SerialKind};
+
+#[test]
+fn serial_counter() {
+    let counter = SerialCounter::new();
+    assert!(counter.last_serial().is_none());
+    let first = counter.next_serial();
+    let second = counter.next_serial();
+    assert!(second.is_newer_than(&first));
+    assert!(counter.last_serial() == Some(second));
+    assert!(counter.is_valid(second, first));
+    assert!(counter.is_valid(first, first));
+    assert!(!counter.is_valid(first, second));
+    assert!(!counter.is_valid(Serial::from(42), first));
+}
+
+#[test]
+fn seat_serials() {
+    let mut server = TestServer::new();
+    let counter = Rc::new(SerialCounter::new());
+
+    // the seat sends a button press and release on its pointers, and a key press on its keyboards
+    server
+        .display
+        .create_global::<wl_seat::WlSeat, _>(5, move |seat: ways::Main<wl_seat::WlSeat>, _| {
+            let counter = counter.clone();
+            seat.assign_mono(move |_, request| match request {
+                wl_seat::Request::GetPointer { id } => {
+                    for &state in &[
+                        wl_pointer::ButtonState::Pressed,
+                        wl_pointer::ButtonState::Released,
+                    ] {
+                        id.button(counter.next_serial().into(), 0, 0x110, state);
+                    }
+                }
+                wl_seat::Request::GetKeyboard { id } => {
+                    id.key(
+                        counter.next_serial().into(),
+                        0,
+                        30,
+                        wl_keyboard::KeyState::Pressed,
+                    );
+                }
+                _ => {}
+            });
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let serials = Rc::new(SeatSerials::new());
+    let seat = manager.instantiate_exact::<WlSeat>(5).unwrap();
+
+    let pointer = seat.get_pointer().unwrap();
+    pointer.assign_mono({
+        let serials = serials.clone();
+        move |_, event| serials.process_pointer_event(&event)
+    });
+    roundtrip(&mut client, &mut server).unwrap();
+
+    // only the press is recorded
+    assert!(serials.get(SerialKind::PointerButton) == Some(Serial::from(1)));
+    assert!(serials.latest_interaction() == Some(Serial::from(1)));
+    assert!(serials.get(SerialKind::Key).is_none());
+
+    let keyboard = seat.get_keyboard().unwrap();
+    keyboard.assign_mono({
+        let serials = serials.clone();
+        move |_, event| serials.process_keyboard_event(&event)
+    });
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert!(serials.get(SerialKind::Key) == Some(Serial::from(3)));
+    assert!(serials.latest_interaction() == Some(Serial::from(3)));
+    assert!(serials.is_latest(1, &[SerialKind::PointerButton, SerialKind::Key]));
+    assert!(!serials.is_latest(2, &[SerialKind::PointerButton, SerialKind::Key]));
+}
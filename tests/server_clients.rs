@@ -71,3 +71,124 @@ fn client_user_data() {
     assert!(clients[1].data_map().get::<HasCompositor>().is_some());
     assert!(clients[1].data_map().get::<HasOutput>().is_some());
 }
+
+#[test]
+fn list_clients() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .create_global::<wl_output::WlOutput, _>(1, |_, _| {});
+
+    let mut client1 = TestClient::new(&server.socket_name);
+    let mut client2 = TestClient::new(&server.socket_name);
+
+    roundtrip(&mut client1, &mut server).unwrap();
+    roundtrip(&mut client2, &mut server).unwrap();
+
+    let clients = server.display.clients().collect::<Vec<_>>();
+    assert_eq!(clients.len(), 2);
+    assert!(!clients[0].equals(&clients[1]));
+
+    clients[0].kill();
+    server.display.flush_clients();
+
+    let remaining = server.display.clients().collect::<Vec<_>>();
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].equals(&clients[1]));
+}
+
+#[test]
+fn client_resources() {
+    let mut server = TestServer::new();
+    let output_id = Arc::new(Mutex::new(None));
+    server.display.create_global::<wl_output::WlOutput, _>(2, {
+        let output_id = output_id.clone();
+        move |output, _| {
+            *output_id.lock().unwrap() = Some(output.as_ref().id());
+        }
+    });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    manager.instantiate_exact::<ClientOutput>(2).unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let output_id = output_id.lock().unwrap().unwrap();
+    let server_client = server.display.clients().next().unwrap();
+
+    let outputs = server_client
+        .resources()
+        .filter(|res| res.interface_name() == "wl_output")
+        .collect::<Vec<_>>();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].id(), output_id);
+    assert_eq!(outputs[0].version(), 2);
+    assert!(server_client
+        .resources()
+        .any(|res| res.interface_name() == "wl_registry"));
+
+    let output = server_client
+        .get_resource::<wl_output::WlOutput>(output_id)
+        .unwrap();
+    assert_eq!(output.as_ref().version(), 2);
+    assert!(server_client
+        .get_resource::<wl_compositor::WlCompositor>(output_id)
+        .is_none());
+    assert!(server_client.get_resource::<wl_output::WlOutput>(1000).is_none());
+}
+
+// the native lib creates some resources itself, which must be listed along with ours
+#[cfg(feature = "server_native")]
+#[test]
+fn client_resources_native() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .create_global::<wl_compositor::WlCompositor, _>(1, |compositor, _| {
+            compositor.assign_mono(|_, request| {
+                if let wl_compositor::Request::CreateSurface { id } = request {
+                    id.assign_mono(|_, _| {});
+                }
+            });
+        });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager.instantiate_exact::<ClientCompositor>(1).unwrap();
+    let surface = compositor.create_surface().unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_client = server.display.clients().next().unwrap();
+    let resources = server_client.resources().collect::<Vec<_>>();
+
+    let display = resources
+        .iter()
+        .find(|res| res.interface_name() == "wl_display")
+        .unwrap();
+    assert_eq!(display.id(), 1);
+    assert!(display.is_external());
+
+    let server_surface = resources
+        .iter()
+        .find(|res| res.interface_name() == "wl_surface")
+        .unwrap();
+    assert!(!server_surface.is_external());
+    assert!(server_surface.is_alive());
+
+    surface.destroy().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert!(!server_surface.is_alive());
+    assert_eq!(
+        server_client.snapshot_objects().objects.len(),
+        resources.len() - 1
+    );
+}
@@ -0,0 +1,204 @@
+mod helpers;
+
+use helpers::{find_surface, roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::compositor::{
+    get_parent, init_compositor, is_effectively_sync, subsurface_position, subsurface_stack, surface_states,
+    Cacheable, Damage, StackEntry, SurfaceAttributes,
+};
+use ways::region::Rectangle;
+
+use wayc::protocol::wl_compositor::WlCompositor;
+use wayc::protocol::wl_subcompositor::WlSubcompositor;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// an extension state, counting the commits of the surface
+#[derive(Default)]
+struct CommitCounter {
+    pending: u32,
+    commits: u32,
+}
+
+impl Cacheable for CommitCounter {
+    fn commit(&mut self) -> CommitCounter {
+        CommitCounter {
+            pending: ::std::mem::replace(&mut self.pending, 0),
+            commits: 1,
+        }
+    }
+
+    fn merge_into(self, into: &mut CommitCounter) {
+        into.pending += self.pending;
+        into.commits += self.commits;
+    }
+}
+
+fn insert_compositor(server: &mut TestServer) -> Rc<RefCell<Vec<u32>>> {
+    let committed = Rc::new(RefCell::new(Vec::new()));
+    let committed2 = committed.clone();
+    init_compositor(&mut server.display, move |surface| {
+        committed2.borrow_mut().push(surface.as_ref().id());
+    });
+    committed
+}
+
+#[test]
+fn surface_double_buffering() {
+    let mut server = TestServer::new();
+    let committed = insert_compositor(&mut server);
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager.instantiate_exact::<WlCompositor>(4).unwrap();
+    let surface = compositor.create_surface().unwrap();
+    surface.set_buffer_scale(2).unwrap();
+    surface.damage(0, 0, 10, 10).unwrap();
+    surface.frame().unwrap();
+    let region = compositor.create_region().unwrap();
+    region.add(0, 0, 10, 10).unwrap();
+    region.subtract(0, 0, 5, 5).unwrap();
+    surface.set_opaque_region(Some(&region)).unwrap();
+    region.destroy().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_surface = find_surface(&server, surface.as_ref().id());
+    {
+        let states = surface_states(&server_surface).unwrap();
+        states.pending::<CommitCounter>().pending += 3;
+        // nothing is applied before the commit
+        let current = states.current::<SurfaceAttributes>();
+        assert_eq!(current.buffer_scale, 1);
+        assert!(current.damage.is_empty());
+        assert!(committed.borrow().is_empty());
+    }
+
+    surface.commit().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert_eq!(*committed.borrow(), vec![surface.as_ref().id()]);
+    let states = surface_states(&server_surface).unwrap();
+    {
+        let current = states.current::<SurfaceAttributes>();
+        assert_eq!(current.buffer_scale, 2);
+        assert_eq!(
+            current.damage,
+            vec![Damage::Surface(Rectangle {
+                x: 0,
+                y: 0,
+                width: 10,
+                height: 10
+            })]
+        );
+        assert_eq!(current.frame_callbacks.len(), 1);
+        let opaque = current.opaque_region.as_ref().unwrap();
+        assert!(opaque.contains(7, 2));
+        assert!(!opaque.contains(2, 2));
+        assert!(current.input_region.is_none());
+        let pending = states.pending::<SurfaceAttributes>();
+        assert_eq!(pending.buffer_scale, 2);
+        assert!(pending.damage.is_empty());
+    }
+    assert_eq!(states.current::<CommitCounter>().pending, 3);
+    assert_eq!(states.current::<CommitCounter>().commits, 1);
+}
+
+#[test]
+fn subsurface_sync() {
+    let mut server = TestServer::new();
+    let committed = insert_compositor(&mut server);
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager.instantiate_exact::<WlCompositor>(4).unwrap();
+    let subcompositor = manager.instantiate_exact::<WlSubcompositor>(1).unwrap();
+    let parent = compositor.create_surface().unwrap();
+    let child = compositor.create_surface().unwrap();
+    let subsurface = subcompositor.get_subsurface(&child, &parent).unwrap();
+    subsurface.set_position(5, 7).unwrap();
+    child.set_buffer_scale(3).unwrap();
+    child.commit().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_parent = find_surface(&server, parent.as_ref().id());
+    let server_child = find_surface(&server, child.as_ref().id());
+    assert!(get_parent(&server_child) == Some(server_parent.clone()));
+    assert!(is_effectively_sync(&server_child));
+
+    // the state of a synchronized subsurface is cached until its parent is committed
+    assert!(committed.borrow().is_empty());
+    let child_states = surface_states(&server_child).unwrap();
+    assert_eq!(child_states.current::<SurfaceAttributes>().buffer_scale, 1);
+    assert_eq!(subsurface_position(&server_child), Some((0, 0)));
+
+    parent.commit().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert_eq!(
+        *committed.borrow(),
+        vec![child.as_ref().id(), parent.as_ref().id()]
+    );
+    assert_eq!(child_states.current::<SurfaceAttributes>().buffer_scale, 3);
+    assert_eq!(subsurface_position(&server_child), Some((5, 7)));
+
+    // the stacking order is applied with the state of the parent
+    subsurface.place_below(&parent).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert!(
+        subsurface_stack(&server_parent)
+            == vec![StackEntry::Parent, StackEntry::Subsurface(server_child.clone())]
+    );
+    parent.commit().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert!(
+        subsurface_stack(&server_parent)
+            == vec![StackEntry::Subsurface(server_child.clone()), StackEntry::Parent]
+    );
+
+    // a desynchronized subsurface applies its state on its own commits,
+    // and its cached state when switching mode
+    committed.borrow_mut().clear();
+    child.set_buffer_scale(4).unwrap();
+    child.commit().unwrap();
+    subsurface.set_desync().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(*committed.borrow(), vec![child.as_ref().id()]);
+    assert_eq!(child_states.current::<SurfaceAttributes>().buffer_scale, 4);
+
+    child.set_buffer_scale(1).unwrap();
+    child.commit().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(child_states.current::<SurfaceAttributes>().buffer_scale, 1);
+
+    // destroying the subsurface unmaps the surface
+    subsurface.destroy().unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert!(get_parent(&server_child).is_none());
+    assert!(subsurface_stack(&server_parent) == vec![StackEntry::Parent]);
+}
+
+#[test]
+fn subsurface_of_itself() {
+    let mut server = TestServer::new();
+    insert_compositor(&mut server);
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let compositor = manager.instantiate_exact::<WlCompositor>(4).unwrap();
+    let subcompositor = manager.instantiate_exact::<WlSubcompositor>(1).unwrap();
+    let parent = compositor.create_surface().unwrap();
+    let child = compositor.create_surface().unwrap();
+    subcompositor.get_subsurface(&child, &parent).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    // a cycle between the surfaces is a protocol error
+    subcompositor.get_subsurface(&parent, &child).unwrap();
+    assert!(roundtrip(&mut client, &mut server).is_err());
+}
@@ -50,7 +50,7 @@ fn data_offer() {
     let received = Arc::new(Mutex::new(false));
     let received2 = received.clone();
 
-    let ddevice = ddmgr.get_data_device(&seat);
+    let ddevice = ddmgr.get_data_device(&seat).unwrap();
     ddevice.assign_mono(move |_, evt| match evt {
         CDDEvt::DataOffer { id: doffer } => {
             let doffer = doffer.as_ref();
@@ -95,12 +95,12 @@ fn server_id_reuse() {
     let offer = Rc::new(RefCell::new(None));
     let offer2 = offer.clone();
 
-    let ddevice = ddmgr.get_data_device(&seat);
+    let ddevice = ddmgr.get_data_device(&seat).unwrap();
 
     ddevice.assign_mono(move |_, evt| match evt {
         CDDEvt::DataOffer { id: doffer } => {
             if let Some(old_offer) = ::std::mem::replace(&mut *offer2.borrow_mut(), Some(doffer)) {
-                old_offer.destroy();
+                old_offer.destroy().unwrap();
             }
         }
         _ => unimplemented!(),
@@ -192,7 +192,7 @@ fn server_created_race() {
     let received = Rc::new(Cell::new(0));
     let received_2 = received.clone();
 
-    let ddevice = ddmgr.get_data_device(&seat);
+    let ddevice = ddmgr.get_data_device(&seat).unwrap();
     ddevice.assign_mono(move |_, evt| match evt {
         CDDEvt::DataOffer { id: doffer } => {
             let received_3 = received_2.clone();
@@ -200,7 +200,7 @@ fn server_created_race() {
                 received_3.set(received_3.get() + 1);
             });
             if let Some(old_offer) = ::std::mem::replace(&mut *offer2.borrow_mut(), Some(doffer)) {
-                old_offer.destroy();
+                old_offer.destroy().unwrap();
             }
         }
         _ => unimplemented!(),
@@ -217,7 +217,7 @@ fn server_created_race() {
     // destroy the object, this should not crash and the events to the zombie object
     // should be silently dropped
     server_do.borrow().as_ref().unwrap().offer("utf8".into());
-    offer.borrow().as_ref().unwrap().destroy();
+    offer.borrow().as_ref().unwrap().destroy().unwrap();
     roundtrip(&mut client, &mut server).unwrap();
     assert_eq!(received.get(), 1);
 }
@@ -255,12 +255,12 @@ fn creation_destruction_race() {
 
     let client_dd: Vec<_> = (0..2)
         .map(|_| {
-            let ddevice = ddmgr.get_data_device(&seat);
+            let ddevice = ddmgr.get_data_device(&seat).unwrap();
             let mut offer = None;
             ddevice.assign_mono(move |_, evt| match evt {
                 CDDEvt::DataOffer { id: doffer } => {
                     if let Some(old_offer) = ::std::mem::replace(&mut offer, Some(doffer)) {
-                        old_offer.destroy();
+                        old_offer.destroy().unwrap();
                     }
                 }
                 _ => unimplemented!(),
@@ -272,7 +272,7 @@ fn creation_destruction_race() {
     roundtrip(&mut client, &mut server).unwrap();
 
     // server sends a newid event to dd1 while dd1 gets destroyed
-    client_dd[0].release();
+    client_dd[0].release().unwrap();
     let offer1 = server_dd.borrow()[0]
         .as_ref()
         .client()
@@ -334,12 +334,12 @@ fn creation_destruction_queue_dispatch_race() {
 
     let called_count = Rc::new(RefCell::new(0u32));
 
-    let ddevice = ddmgr.get_data_device(&seat);
+    let ddevice = ddmgr.get_data_device(&seat).unwrap();
     let called_count2 = called_count.clone();
     ddevice.assign_mono(move |dd, evt| match evt {
         CDDEvt::DataOffer { .. } => {
             // destroy the data device after receiving the first offer
-            dd.release();
+            dd.release().unwrap();
             *called_count2.borrow_mut() += 1;
         }
         _ => unimplemented!(),
@@ -0,0 +1,162 @@
+extern crate nix;
+
+mod helpers;
+
+use helpers::{ways, TestServer};
+
+use ways::{FdEvent, FdInterest, Filter, Signal, SignalEvent, TimerEvent};
+
+use std::cell::Cell;
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[test]
+fn fd_source() {
+    let mut server = TestServer::new();
+    let (mut tx, rx) = UnixStream::pair().unwrap();
+
+    let readable = Rc::new(Cell::new(false));
+    let readable2 = readable.clone();
+    let rx_fd = rx.as_raw_fd();
+    let source = server
+        .display
+        .add_fd_event_source(
+            rx_fd,
+            FdInterest::READ,
+            Filter::new(move |evt, _| match evt {
+                FdEvent::Ready { fd, mask } => {
+                    assert_eq!(fd, rx_fd);
+                    assert!(mask.contains(FdInterest::READ));
+                    readable2.set(true);
+                }
+                FdEvent::Error { error, .. } => panic!("Unexpected fd error: {}", error),
+            }),
+        )
+        .unwrap();
+
+    server.display.dispatch(Duration::from_millis(10)).unwrap();
+    assert!(!readable.get());
+
+    tx.write_all(b"ping").unwrap();
+    server.display.dispatch(Duration::from_millis(10)).unwrap();
+    assert!(readable.get());
+
+    // once removed, the filter is no longer called
+    source.remove();
+    readable.set(false);
+    server.display.dispatch(Duration::from_millis(10)).unwrap();
+    assert!(!readable.get());
+}
+
+#[test]
+fn fd_source_update_interest() {
+    let mut server = TestServer::new();
+    let (tx, _rx) = UnixStream::pair().unwrap();
+
+    let writable = Rc::new(Cell::new(false));
+    let writable2 = writable.clone();
+    let mut source = server
+        .display
+        .add_fd_event_source(
+            tx.as_raw_fd(),
+            FdInterest::READ,
+            Filter::new(move |evt, _| {
+                if let FdEvent::Ready { mask, .. } = evt {
+                    if mask.contains(FdInterest::WRITE) {
+                        writable2.set(true);
+                    }
+                }
+            }),
+        )
+        .unwrap();
+
+    server.display.dispatch(Duration::from_millis(10)).unwrap();
+    assert!(!writable.get());
+
+    source.update_interest(FdInterest::WRITE);
+    server.display.dispatch(Duration::from_millis(10)).unwrap();
+    assert!(writable.get());
+
+    source.remove();
+}
+
+#[test]
+fn timer_source() {
+    let mut server = TestServer::new();
+
+    let fired = Rc::new(Cell::new(0));
+    let fired2 = fired.clone();
+    let mut source = server
+        .display
+        .add_timer_event_source(Filter::new(move |TimerEvent, _| fired2.set(fired2.get() + 1)))
+        .unwrap();
+
+    // the timer is initially disarmed
+    server.display.dispatch(Duration::from_millis(20)).unwrap();
+    assert_eq!(fired.get(), 0);
+
+    source.set_delay_ms(5);
+    server.display.dispatch(Duration::from_millis(100)).unwrap();
+    assert_eq!(fired.get(), 1);
+
+    // the timer fires only once per arming
+    server.display.dispatch(Duration::from_millis(20)).unwrap();
+    assert_eq!(fired.get(), 1);
+
+    source.remove();
+}
+
+#[test]
+fn sources_outliving_display() {
+    let mut server = TestServer::new();
+
+    let token = Rc::new(());
+    let token2 = token.clone();
+    let mut kept = server
+        .display
+        .add_timer_event_source(Filter::new(move |TimerEvent, _| {
+            assert!(Rc::strong_count(&token2) > 1)
+        }))
+        .unwrap();
+    let token3 = token.clone();
+    let forgotten = server
+        .display
+        .add_timer_event_source(Filter::new(move |TimerEvent, _| {
+            assert!(Rc::strong_count(&token3) > 1)
+        }))
+        .unwrap();
+    drop(forgotten);
+    drop(server);
+
+    // the sources can still be used and removed once the display is gone
+    kept.set_delay_ms(5);
+    kept.remove();
+    assert_eq!(Rc::strong_count(&token), 1);
+}
+
+#[test]
+fn signal_source() {
+    let mut server = TestServer::new();
+
+    let received = Rc::new(Cell::new(false));
+    let received2 = received.clone();
+    let source = server
+        .display
+        .add_signal_event_source(
+            Signal::SIGUSR1,
+            Filter::new(move |SignalEvent(sig), _| {
+                assert_eq!(sig, Signal::SIGUSR1);
+                received2.set(true);
+            }),
+        )
+        .unwrap();
+
+    nix::sys::signal::raise(Signal::SIGUSR1).unwrap();
+    server.display.dispatch(Duration::from_millis(10)).unwrap();
+    assert!(received.get());
+
+    source.remove();
+}
@@ -99,19 +99,75 @@ fn global_filter_try_force() {
 
     // privileged client can bind it
 
-    let registry2 = client2.display_proxy.get_registry();
-    registry2.bind::<WlOutput>(1, 1);
+    let registry2 = client2.display_proxy.get_registry().unwrap();
+    registry2.bind::<WlOutput>(1, 1).unwrap();
 
     roundtrip(&mut client2, &mut server).unwrap();
 
     // unprivileged client cannot
 
-    let registry = client.display_proxy.get_registry();
-    registry.bind::<WlOutput>(1, 1);
+    let registry = client.display_proxy.get_registry().unwrap();
+    registry.bind::<WlOutput>(1, 1).unwrap();
 
     assert!(roundtrip(&mut client, &mut server).is_err());
 }
 
+struct Legacy;
+
+#[test]
+fn global_version_cap() {
+    use wayc::protocol::wl_output::WlOutput;
+
+    use std::os::unix::io::IntoRawFd;
+
+    let mut server = TestServer::new();
+
+    // legacy clients are only told about version 1 of the output
+    server
+        .display
+        .create_global_with_version_cap::<wl_output::WlOutput, _, _>(
+            3,
+            |_, _| {},
+            |client| {
+                if client.data_map().get::<Legacy>().is_some() {
+                    1
+                } else {
+                    // capped to the version of the global
+                    42
+                }
+            },
+        );
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    let (server_cx, client_cx) = ::std::os::unix::net::UnixStream::pair().unwrap();
+    let legacy_client = unsafe { server.display.create_client(server_cx.into_raw_fd()) };
+    legacy_client.data_map().insert_if_missing(|| Legacy);
+    let mut client2 = unsafe { TestClient::from_fd(client_cx.into_raw_fd()) };
+    let manager2 = wayc::GlobalManager::new(&client2.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+    roundtrip(&mut client2, &mut server).unwrap();
+
+    assert_eq!(manager.list(), vec![(1, "wl_output".into(), 3)]);
+    // libwayland advertises the same version to every client
+    #[cfg(not(feature = "server_native"))]
+    assert_eq!(manager2.list(), vec![(1, "wl_output".into(), 1)]);
+
+    // the regular client can bind the full version
+    manager.instantiate_exact::<WlOutput>(3).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    // the legacy client can bind the capped version, but not more
+    manager2.instantiate_exact::<WlOutput>(1).unwrap();
+    roundtrip(&mut client2, &mut server).unwrap();
+
+    let registry2 = client2.display_proxy.get_registry().unwrap();
+    registry2.bind::<WlOutput>(2, 1).unwrap();
+    assert!(roundtrip(&mut client2, &mut server).is_err());
+}
+
 #[cfg(feature = "server_native")]
 #[test]
 fn external_globals() {
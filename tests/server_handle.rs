@@ -0,0 +1,92 @@
+mod helpers;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::wl_output::{self as server_output, WlOutput as ServerOutput};
+
+use wayc::protocol::wl_output;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[test]
+fn handle_send_event() {
+    let mut server = TestServer::new();
+    let handle = server.display.handle().unwrap();
+    let outputs = Arc::new(Mutex::new(Vec::new()));
+    server.display.create_global::<ServerOutput, _>(3, {
+        let outputs = outputs.clone();
+        move |output, _| {
+            output.assign_mono(|_, _| {});
+            outputs.lock().unwrap().push(output.as_ref().clone());
+        }
+    });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let done = Arc::new(Mutex::new(0));
+    let output = manager.instantiate_exact::<wl_output::WlOutput>(3).unwrap();
+    output.assign_mono({
+        let done = done.clone();
+        move |_, event| {
+            if let wl_output::Event::Done = event {
+                *done.lock().unwrap() += 1;
+            }
+        }
+    });
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let resource = outputs.lock().unwrap()[0].clone();
+    thread::spawn(move || {
+        handle.send_event(&resource, server_output::Event::Done);
+        handle.send_event(&resource, server_output::Event::Done);
+    })
+    .join()
+    .unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(*done.lock().unwrap(), 2);
+}
+
+#[test]
+fn handle_create_destroy_global() {
+    let mut server = TestServer::new();
+    let handle = server.display.handle().unwrap();
+    // subsequent calls give the same handle
+    let handle2 = server.display.handle().unwrap();
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+
+    let bound = Arc::new(Mutex::new(false));
+    let global = thread::spawn({
+        let bound = bound.clone();
+        move || {
+            handle.create_global::<ServerOutput, _>(2, move |output, version| {
+                assert_eq!(version, 2);
+                output.assign_mono(|_, _| {});
+                *bound.lock().unwrap() = true;
+            })
+        }
+    })
+    .join()
+    .unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+    assert_eq!(manager.list(), vec![(1, "wl_output".into(), 2)]);
+
+    manager.instantiate_exact::<wl_output::WlOutput>(2).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+    assert!(*bound.lock().unwrap());
+
+    thread::spawn(move || handle2.destroy_global(global))
+        .join()
+        .unwrap();
+
+    roundtrip(&mut client, &mut server).unwrap();
+    assert!(manager.list().is_empty());
+}
@@ -106,7 +106,7 @@ fn dead_resources() {
         outputs_lock[0].clone()
     };
 
-    client_output1.release();
+    client_output1.release().unwrap();
 
     roundtrip(&mut client, &mut server).unwrap();
 
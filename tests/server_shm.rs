@@ -0,0 +1,149 @@
+extern crate nix;
+
+use std::ffi::CString;
+use std::fs::File;
+use std::io::Write;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::sync::{Arc, Mutex};
+
+use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+
+mod helpers;
+
+use helpers::{find_buffer, roundtrip, wayc, ways, TestClient, TestServer};
+
+use wayc::protocol::wl_shm::{Format, WlShm};
+
+use ways::protocol::wl_shm::Format as ServerFormat;
+use ways::shm::{init_shm_global, with_buffer_contents, BufferAccessError, BufferData};
+
+fn memfd(contents: &[u8]) -> File {
+    let fd = memfd_create(
+        &CString::new("wayland-rs-test").unwrap(),
+        MemFdCreateFlag::MFD_CLOEXEC,
+    )
+    .unwrap();
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(contents).unwrap();
+    file
+}
+
+#[test]
+fn shm_formats() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, vec![ServerFormat::Yuyv]);
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let formats = Arc::new(Mutex::new(Vec::new()));
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    shm.assign_mono({
+        let formats = formats.clone();
+        move |_, event| {
+            if let wayc::protocol::wl_shm::Event::Format { format } = event {
+                formats.lock().unwrap().push(format);
+            }
+        }
+    });
+    roundtrip(&mut client, &mut server).unwrap();
+
+    assert_eq!(
+        *formats.lock().unwrap(),
+        vec![Format::Yuyv, Format::Argb8888, Format::Xrgb8888]
+    );
+}
+
+#[test]
+fn shm_buffer_contents() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let contents = (0..64).collect::<Vec<u8>>();
+    let file = memfd(&contents);
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let pool = shm.create_pool(file.as_raw_fd(), 64).unwrap();
+    let buffer = pool.create_buffer(16, 2, 2, 8, Format::Argb8888).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_buffer = find_buffer(&server, buffer.as_ref().id());
+    let ret = with_buffer_contents(&server_buffer, |slice, data| {
+        assert_eq!(
+            data,
+            BufferData {
+                offset: 16,
+                width: 2,
+                height: 2,
+                stride: 8,
+                format: ServerFormat::Argb8888,
+            }
+        );
+        slice.to_vec()
+    });
+    assert_eq!(ret.unwrap(), (16..32).collect::<Vec<u8>>());
+
+    // grow the pool and create a buffer in the new area
+    file.set_len(128).unwrap();
+    pool.resize(128).unwrap();
+    let buffer2 = pool.create_buffer(64, 4, 4, 16, Format::Xrgb8888).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_buffer2 = find_buffer(&server, buffer2.as_ref().id());
+    let ret = with_buffer_contents(&server_buffer2, |slice, _| slice.iter().all(|&b| b == 0));
+    assert_eq!(ret, Ok(true));
+
+    // the first buffer still works after the resize
+    let ret = with_buffer_contents(&server_buffer, |slice, _| slice[0]);
+    assert_eq!(ret, Ok(16));
+}
+
+#[test]
+fn shm_invalid_buffer() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let file = memfd(&[0; 64]);
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let pool = shm.create_pool(file.as_raw_fd(), 64).unwrap();
+    // does not fit in the pool
+    pool.create_buffer(32, 4, 4, 16, Format::Argb8888).unwrap();
+
+    assert!(roundtrip(&mut client, &mut server).is_err());
+}
+
+#[test]
+fn shm_truncated_pool() {
+    let mut server = TestServer::new();
+    init_shm_global(&mut server.display, Vec::new());
+
+    let mut client = TestClient::new(&server.socket_name);
+    let manager = wayc::GlobalManager::new(&client.display_proxy);
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let file = memfd(&[0xFF; 8192]);
+    let shm = manager.instantiate_exact::<WlShm>(1).unwrap();
+    let pool = shm.create_pool(file.as_raw_fd(), 8192).unwrap();
+    let buffer = pool.create_buffer(0, 32, 64, 128, Format::Argb8888).unwrap();
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let server_buffer = find_buffer(&server, buffer.as_ref().id());
+    let ret = with_buffer_contents(&server_buffer, |slice, _| slice[8191]);
+    assert_eq!(ret, Ok(0xFF));
+
+    // the client shrinks its file behind our back, accessing it raises a SIGBUS
+    file.set_len(0).unwrap();
+    let ret = with_buffer_contents(&server_buffer, |slice, _| slice[8191]);
+    assert_eq!(ret, Err(BufferAccessError::BadMap));
+
+    // the client was killed
+    assert!(roundtrip(&mut client, &mut server).is_err());
+}
@@ -0,0 +1,85 @@
+mod helpers;
+
+use helpers::{roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::wl_output::WlOutput as ServerOutput;
+
+use wayc::protocol::wl_output;
+use wayc::GlobalEvent;
+
+use std::cell::RefCell;
+use std::env;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn graceful_shutdown() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let outputs = Rc::new(RefCell::new(Vec::new()));
+
+    let mut server = TestServer::new();
+    server.display.create_global::<ServerOutput, _>(3, {
+        let log = log.clone();
+        let outputs = outputs.clone();
+        move |output, _| {
+            output.assign_mono(|_, _| {});
+            let log2 = log.clone();
+            output.assign_destructor(ways::Filter::new(move |_: ways::Resource<_>, _| {
+                log2.lock().unwrap().push("resource");
+            }));
+            let log3 = log.clone();
+            output
+                .as_ref()
+                .client()
+                .unwrap()
+                .add_destructor(ways::Filter::new(move |_, _| {
+                    log3.lock().unwrap().push("client");
+                }));
+            outputs.borrow_mut().push(output);
+        }
+    });
+
+    let mut client = TestClient::new(&server.socket_name);
+    let removed = Arc::new(Mutex::new(false));
+    let removed2 = removed.clone();
+    let manager = wayc::GlobalManager::new_with_cb(&client.display_proxy, move |event, _| {
+        if let GlobalEvent::Removed { .. } = event {
+            *removed2.lock().unwrap() = true;
+        }
+    });
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let done = Arc::new(Mutex::new(false));
+    let done2 = done.clone();
+    let output = manager.instantiate_exact::<wl_output::WlOutput>(3).unwrap();
+    output.assign_mono(move |_, event| {
+        if let wl_output::Event::Done = event {
+            *done2.lock().unwrap() = true;
+        }
+    });
+
+    roundtrip(&mut client, &mut server).unwrap();
+
+    let mut socket_path: PathBuf = env::var_os("XDG_RUNTIME_DIR").unwrap().into();
+    socket_path.push(&server.socket_name);
+    assert!(socket_path.exists());
+
+    // this event is not flushed before the shutdown
+    outputs.borrow()[0].done();
+    server.display.shutdown(Duration::from_secs(1)).unwrap();
+
+    assert!(!socket_path.exists());
+    assert_eq!(*log.lock().unwrap(), vec!["resource", "client"]);
+
+    // the client still receives the last events before being disconnected
+    let ret = client.event_queue.prepare_read().unwrap().read_events();
+    client.event_queue.dispatch_pending(|_, _| {}).unwrap();
+    assert!(*done.lock().unwrap());
+    assert!(*removed.lock().unwrap());
+    if ret.is_ok() {
+        assert!(client.event_queue.prepare_read().unwrap().read_events().is_err());
+    }
+}
@@ -1,15 +1,10 @@
-extern crate byteorder;
-extern crate tempfile;
 #[macro_use(event_enum)]
 extern crate wayland_client;
 
 use std::cmp::min;
-use std::io::Write;
-use std::os::unix::io::AsRawFd;
-
-use byteorder::{NativeEndian, WriteBytesExt};
 
 use wayland_client::protocol::{wl_compositor, wl_keyboard, wl_pointer, wl_seat, wl_shell, wl_shm};
+use wayland_client::shm::ShmPool;
 use wayland_client::{Display, Filter, GlobalManager};
 
 // declare an event enum containing the events we want to receive in the iterator
@@ -31,6 +26,22 @@ fn main() {
     // roundtrip to retrieve the globals list
     event_queue.sync_roundtrip(|_, _| unreachable!()).unwrap();
 
+    /*
+     * Init wayland objects
+     */
+
+    // The compositor allows us to creates surfaces
+    let compositor = globals
+        .instantiate_exact::<wl_compositor::WlCompositor>(1)
+        .unwrap();
+    let surface = compositor.create_surface().unwrap();
+
+    // The SHM allows us to share memory with the server, and create buffers
+    // on this shared memory to paint our surfaces. The ShmPool manages this
+    // memory for us.
+    let shm = globals.instantiate_exact::<wl_shm::WlShm>(1).unwrap();
+    let mut pool = ShmPool::new(&shm).expect("Unable to create a shm pool.");
+
     /*
      * Create a buffer with window contents
      */
@@ -39,43 +50,23 @@ fn main() {
     let buf_x: u32 = 320;
     let buf_y: u32 = 240;
 
-    // create a tempfile to write the contents of the window on
-    let mut tmp = tempfile::tempfile().ok().expect("Unable to create a tempfile.");
+    let (canvas, buffer) = pool
+        .buffer(
+            buf_x as i32,             // width of the buffer in pixels
+            buf_y as i32,             // height of the buffer in pixels
+            (buf_x * 4) as i32,       // number of bytes between the beginning of two consecutive lines
+            wl_shm::Format::Argb8888, // chosen encoding for the data
+        )
+        .expect("Unable to create a buffer.");
     // write the contents to it, lets put a nice color gradient
-    for i in 0..(buf_x * buf_y) {
-        let x = (i % buf_x) as u32;
-        let y = (i / buf_x) as u32;
+    for (i, pixel) in canvas.chunks_mut(4).enumerate() {
+        let x = i as u32 % buf_x;
+        let y = i as u32 / buf_x;
         let r: u32 = min(((buf_x - x) * 0xFF) / buf_x, ((buf_y - y) * 0xFF) / buf_y);
         let g: u32 = min((x * 0xFF) / buf_x, ((buf_y - y) * 0xFF) / buf_y);
         let b: u32 = min(((buf_x - x) * 0xFF) / buf_x, (y * 0xFF) / buf_y);
-        let _ = tmp.write_u32::<NativeEndian>((0xFF << 24) + (r << 16) + (g << 8) + b);
+        pixel.copy_from_slice(&((0xFF << 24) + (r << 16) + (g << 8) + b).to_ne_bytes());
     }
-    let _ = tmp.flush();
-
-    /*
-     * Init wayland objects
-     */
-
-    // The compositor allows us to creates surfaces
-    let compositor = globals
-        .instantiate_exact::<wl_compositor::WlCompositor>(1)
-        .unwrap();
-    let surface = compositor.create_surface();
-
-    // The SHM allows us to share memory with the server, and create buffers
-    // on this shared memory to paint our surfaces
-    let shm = globals.instantiate_exact::<wl_shm::WlShm>(1).unwrap();
-    let pool = shm.create_pool(
-        tmp.as_raw_fd(),            // RawFd to the tempfile serving as shared memory
-        (buf_x * buf_y * 4) as i32, // size in bytes of the shared memory (4 bytes per pixel)
-    );
-    let buffer = pool.create_buffer(
-        0,                        // Start of the buffer in the pool
-        buf_x as i32,             // width of the buffer in pixels
-        buf_y as i32,             // height of the buffer in pixels
-        (buf_x * 4) as i32,       // number of bytes between the beginning of two consecutive lines
-        wl_shm::Format::Argb8888, // chosen encoding for the data
-    );
 
     // The shell allows us to define our surface as a "toplevel", meaning the
     // server will treat it as a window
@@ -85,20 +76,20 @@ fn main() {
     let shell = globals
         .instantiate_exact::<wl_shell::WlShell>(1)
         .expect("Compositor does not support wl_shell");
-    let shell_surface = shell.get_shell_surface(&surface);
+    let shell_surface = shell.get_shell_surface(&surface).unwrap();
     shell_surface.assign_mono(|shell_surface, event| {
         use wayland_client::protocol::wl_shell_surface::Event;
         // This ping/pong mechanism is used by the wayland server to detect
         // unresponsive applications
         if let Event::Ping { serial } = event {
-            shell_surface.pong(serial);
+            shell_surface.pong(serial).unwrap();
         }
     });
 
     // Set our surface as toplevel and define its contents
-    shell_surface.set_toplevel();
-    surface.attach(Some(&buffer), 0, 0);
-    surface.commit();
+    shell_surface.set_toplevel().unwrap();
+    surface.attach(Some(&buffer), 0, 0).unwrap();
+    surface.commit().unwrap();
 
     // initialize a seat to retrieve pointer & keyboard events
     //
@@ -155,12 +146,12 @@ fn main() {
                 if !pointer_created && capabilities.contains(Capability::Pointer) {
                     // create the pointer only once
                     pointer_created = true;
-                    seat.get_pointer().assign(common_filter.clone());
+                    seat.get_pointer().unwrap().assign(common_filter.clone());
                 }
                 if !keyboard_created && capabilities.contains(Capability::Keyboard) {
                     // create the keyboard only once
                     keyboard_created = true;
-                    seat.get_keyboard().assign(common_filter.clone());
+                    seat.get_keyboard().unwrap().assign(common_filter.clone());
                 }
             }
         });
@@ -9,7 +9,7 @@ use std::sync::Arc;
 
 use nix::fcntl;
 
-use crate::{EventQueue, Proxy};
+use crate::{EventQueue, ObjectTreeSnapshot, Proxy};
 
 use crate::imp::DisplayInner;
 
@@ -207,6 +207,19 @@ impl Display {
         self.inner.protocol_error()
     }
 
+    /// Take a snapshot of the protocol objects currently alive on this connection
+    ///
+    /// This is intended as a debugging tool, for example to track leaked objects by
+    /// comparing periodic snapshots with `ObjectTreeSnapshot::diff()`.
+    ///
+    /// When using the system libwayland, the message that created each object is not known,
+    /// and only the proxies managed by this crate are listed. As libwayland provides no way
+    /// to enumerate the objects of a connection, the proxies of all the connections of the
+    /// process are listed in this case.
+    pub fn snapshot_objects(&self) -> ObjectTreeSnapshot {
+        self.inner.snapshot_objects()
+    }
+
     #[cfg(feature = "use_system_lib")]
     /// Create a Display and Event Queue from an external display
     ///
@@ -0,0 +1,108 @@
+use std::io;
+
+use calloop::generic::{Fd, Generic};
+use calloop::{EventSource, Interest, Mode, Poll, Readiness, Token};
+
+use crate::{EventQueue, ReadEventsGuard};
+
+/// A calloop event source for a wayland `EventQueue`
+///
+/// Inserting it into a `calloop::EventLoop` will read the events from the wayland
+/// socket as they arrive. Your callback is then invoked with a `&mut EventQueue`,
+/// and must dispatch it using `EventQueue::dispatch_pending()`.
+///
+/// This source takes care of the read intention protocol: it holds a read intention
+/// (see `EventQueue::prepare_read()`) while the event loop sleeps, and releases it by
+/// reading the socket once it becomes readable. The pending requests are flushed to the
+/// server once your callback returns, before the event loop goes back to sleep.
+///
+/// Requests sent from the callbacks of other event sources are not automatically flushed,
+/// you need to call `Display::flush()` from these callbacks as well. Similarly, events already
+/// pending in the queue when this source is inserted in the event loop are only dispatched
+/// once new events arrive, so you should dispatch them beforehand.
+pub struct WaylandSource {
+    queue: EventQueue,
+    fd: Generic<Fd>,
+    read_guard: Option<ReadEventsGuard>,
+}
+
+impl WaylandSource {
+    /// Wrap an `EventQueue` into a calloop event source
+    pub fn new(queue: EventQueue) -> WaylandSource {
+        let fd = queue.get_connection_fd();
+        WaylandSource {
+            queue,
+            fd: Generic::from_fd(fd, Interest::Readable, Mode::Level),
+            read_guard: None,
+        }
+    }
+
+    /// Access the underlying `EventQueue`
+    pub fn queue(&mut self) -> &mut EventQueue {
+        &mut self.queue
+    }
+
+    /// Unwrap the source to retrieve the underlying `EventQueue`
+    ///
+    /// The read intention held by this source, if any, is cancelled.
+    pub fn into_queue(self) -> EventQueue {
+        self.queue
+    }
+}
+
+impl EventSource for WaylandSource {
+    type Event = ();
+    type Metadata = EventQueue;
+    type Ret = ();
+
+    fn process_events<F>(&mut self, readiness: Readiness, token: Token, mut callback: F) -> io::Result<()>
+    where
+        F: FnMut((), &mut EventQueue),
+    {
+        let queue = &mut self.queue;
+        let read_guard = &mut self.read_guard;
+        self.fd.process_events(readiness, token, |_, _| {
+            loop {
+                // read the events from the socket
+                if let Some(guard) = read_guard.take() {
+                    if let Err(e) = guard.read_events() {
+                        if e.kind() != io::ErrorKind::WouldBlock {
+                            return Err(e);
+                        }
+                    }
+                }
+                // let the user dispatch them
+                callback((), queue);
+                // flush the requests before the event loop goes back to sleep
+                if let Err(e) = queue.inner.flush() {
+                    if e.kind() != io::ErrorKind::WouldBlock {
+                        return Err(e);
+                    }
+                }
+                // declare the read intention for the next wakeup, if events are still
+                // pending in the queue, they need to be dispatched first
+                *read_guard = queue.prepare_read();
+                if read_guard.is_some() {
+                    return Ok(());
+                }
+            }
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token: Token) -> io::Result<()> {
+        if self.read_guard.is_none() {
+            self.read_guard = self.queue.prepare_read();
+        }
+        self.fd.register(poll, token)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token: Token) -> io::Result<()> {
+        self.fd.reregister(poll, token)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> io::Result<()> {
+        // the read intention must not be held while we are not polling the socket
+        self.read_guard = None;
+        self.fd.unregister(poll)
+    }
+}
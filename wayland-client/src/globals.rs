@@ -77,6 +77,7 @@ impl GlobalManager {
         let registry = display
             .as_ref()
             .send::<wl_registry::WlRegistry>(wl_display::Request::GetRegistry {}, None)
+            .unwrap()
             .unwrap();
         registry.assign_mono(move |_proxy, msg| {
             let mut inner = inner.lock().unwrap();
@@ -122,6 +123,7 @@ impl GlobalManager {
         let registry = display
             .as_ref()
             .send::<wl_registry::WlRegistry>(wl_display::Request::GetRegistry {}, None)
+            .unwrap()
             .unwrap();
         registry.assign_mono(move |proxy, msg| {
             let mut inner = inner.lock().unwrap();
@@ -184,7 +186,7 @@ impl GlobalManager {
                 if version > server_version {
                     return Err(GlobalError::VersionTooLow(server_version));
                 } else {
-                    return Ok(self.registry.bind::<I>(version, id));
+                    return Ok(self.bind::<I>(version, id));
                 }
             }
         }
@@ -213,7 +215,7 @@ impl GlobalManager {
             if interface == I::NAME {
                 if version >= min_version {
                     let version = ::std::cmp::min(version, max_version);
-                    return Ok(self.registry.bind::<I>(version, id));
+                    return Ok(self.bind::<I>(version, id));
                 } else {
                     return Err(GlobalError::VersionTooLow(version));
                 }
@@ -222,6 +224,16 @@ impl GlobalManager {
         Err(GlobalError::Missing)
     }
 
+    // the registry has no destructor and `bind` is available since its version 1
+    fn bind<I>(&self, version: u32, id: u32) -> Main<I>
+    where
+        I: Interface + AsRef<Proxy<I>> + From<Proxy<I>>,
+    {
+        self.registry
+            .bind::<I>(version, id)
+            .expect("Binding a global cannot fail.")
+    }
+
     /// Retrieve the list of currently known globals
     pub fn list(&self) -> Vec<(u32, String, u32)> {
         self.inner.lock().unwrap().list.clone()
@@ -327,7 +339,7 @@ macro_rules! global_filter {
                         if version < $version {
                             GlobalImplementor::<$interface>::error(&mut cb, version);
                         } else {
-                            let proxy = registry.bind::<$interface>(version, id);
+                            let proxy = registry.bind::<$interface>(version, id).unwrap();
                             GlobalImplementor::<$interface>::new_global(&mut cb, proxy);
                         }
                     }) as Box<_>
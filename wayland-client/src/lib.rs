@@ -45,8 +45,10 @@
 //! Rust objects still refer to it. In such case, these Rust objects will be disabled
 //! and the `alive()` method on the underlying `Proxy<I>` will start to return `false`.
 //!
-//! Sending requests on dead objects will be silently ignored. And if these requests
-//! would create new objects, these objects will be created dead.
+//! Requests sent on dead objects are not sent, and the methods sending them return a
+//! `SendError::DeadObject` instead. Similarly, a request introduced by a version of the
+//! interface higher than the version of the object returns a `SendError::VersionTooLow`.
+//! `panic_on_send_errors()` turns these errors into panics, to catch them in tests.
 //!
 //! ### Filters
 //!
@@ -58,6 +60,10 @@
 //! If an object is not assigned to any `Filter`, its events will instead be delivered to the
 //! fallback closure given to its event queue when dispatching it.
 //!
+//! Alternatively, each interface module has an `EventHandler` trait, with one method per event
+//! that does nothing by default. Any implementor can be assigned to an object with
+//! `Main::assign_handler`, and only needs to handle the events it is interested in.
+//!
 //! ## Event Queues
 //!
 //! The Wayland client machinery provides the possibility to have one or more event queues
@@ -78,6 +84,9 @@
 //! At the beginning you'll need to create an event queue and assign the initial `Proxy<WlDisplay>`
 //! to it.
 //!
+//! If you use `calloop` as your event loop, enabling the `calloop` cargo feature of this crate
+//! provides a `WaylandSource`, wrapping an `EventQueue` into a ready-to-insert calloop event source.
+//!
 //! ## Dynamic linking with `libwayland-client.so`
 //!
 //! If you need to gracefully handle the case of a system on which Wayland is not installed (by
@@ -100,15 +109,22 @@ extern crate wayland_sys;
 
 mod display;
 mod event_queue;
+#[cfg(feature = "calloop")]
+mod eventloop;
 mod globals;
 mod proxy;
+pub mod serial;
+pub mod shm;
 
 pub use anonymous_object::AnonymousObject;
 pub use display::{ConnectError, Display, ProtocolError};
 pub use event_queue::{EventQueue, QueueToken, ReadEventsGuard};
+#[cfg(feature = "calloop")]
+pub use eventloop::WaylandSource;
 pub use globals::{GlobalError, GlobalEvent, GlobalImplementor, GlobalManager};
 pub use imp::ProxyMap;
-pub use proxy::{Attached, Main, Proxy};
+pub use proxy::{panic_on_send_errors, Attached, HandledBy, Main, Proxy, SendError};
+pub use wayland_commons::introspection::{ObjectCreator, ObjectInfo, ObjectTreeSnapshot, SnapshotDiff};
 pub use wayland_commons::{filter::Filter, user_data::UserData, Interface, MessageGroup, NoMessage};
 
 // rust implementation
@@ -133,7 +149,7 @@ pub mod protocol {
     #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
     #![allow(missing_docs, clippy::all)]
 
-    pub(crate) use crate::{AnonymousObject, Attached, Main, Proxy, ProxyMap};
+    pub(crate) use crate::{AnonymousObject, Attached, HandledBy, Main, Proxy, ProxyMap, SendError};
     pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
     pub(crate) use wayland_commons::smallvec;
     pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
@@ -23,6 +23,8 @@ unsafe fn make_display(ptr: *mut wl_display) -> Result<Arc<DisplayInner>, Connec
         return Err(ConnectError::NoCompositorListening);
     }
 
+    super::proxy::register_connection(ptr);
+
     let display = Arc::new(DisplayInner {
         proxy: Proxy::from_c_ptr(ptr as *mut _),
         display: ptr,
@@ -67,6 +69,10 @@ impl DisplayInner {
         &self.proxy
     }
 
+    pub(crate) fn snapshot_objects(&self) -> crate::ObjectTreeSnapshot {
+        super::proxy::snapshot_proxies(self.display)
+    }
+
     pub(crate) fn protocol_error(&self) -> Option<crate::ProtocolError> {
         let ret = unsafe { ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_display_get_error, self.ptr()) };
         if ret == ::nix::errno::Errno::EPROTO as i32 {
@@ -94,6 +100,7 @@ impl DisplayInner {
     }
 
     pub(crate) unsafe fn from_external(display_ptr: *mut wl_display) -> Arc<DisplayInner> {
+        super::proxy::register_connection(display_ptr);
         Arc::new(DisplayInner {
             proxy: Proxy::wrap(ProxyInner::from_external_display(display_ptr as *mut _)),
             display: display_ptr,
@@ -104,6 +111,7 @@ impl DisplayInner {
 
 impl Drop for DisplayInner {
     fn drop(&mut self) {
+        super::proxy::unregister_connection(self.display);
         if !self.external {
             // disconnect only if we are owning this display
             unsafe {
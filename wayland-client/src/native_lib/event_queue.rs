@@ -36,6 +36,10 @@ impl EventQueueInner {
         unsafe { ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_display_get_fd, self.inner.ptr()) }
     }
 
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
     pub fn dispatch<F>(&self, fallback: F) -> io::Result<u32>
     where
         F: FnMut(RawEvent, Main<AnonymousObject>),
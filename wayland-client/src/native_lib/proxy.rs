@@ -1,10 +1,13 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::os::raw::{c_int, c_void};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 
 use crate::{Interface, Main, Proxy, RawEvent};
 use wayland_commons::filter::Filter;
+use wayland_commons::introspection::{timestamp, ObjectInfo, ObjectOrigin, ObjectTreeSnapshot};
 use wayland_commons::user_data::UserData;
 use wayland_commons::wire::ArgumentType;
 use wayland_commons::MessageGroup;
@@ -16,18 +19,141 @@ use wayland_sys::common::*;
 
 pub struct ProxyInternal {
     alive: AtomicBool,
+    created: Duration,
     user_data: UserData,
+    // the connection this proxy is tracked in, 0 if it is not tracked
+    connection: AtomicUsize,
 }
 
 impl ProxyInternal {
     pub fn new(user_data: UserData) -> ProxyInternal {
         ProxyInternal {
             alive: AtomicBool::new(true),
+            created: timestamp(),
             user_data,
+            connection: AtomicUsize::new(0),
         }
     }
 }
 
+// libwayland provides no way to enumerate the proxies of a connection, so we keep
+// track of the proxies we manage ourselves for each connection, for introspection
+// purposes. The lock is also held while destroying proxies, so that a snapshot never
+// accesses a proxy being destroyed on another thread.
+struct TrackedProxy {
+    internal: Weak<ProxyInternal>,
+    ptr: *mut wl_proxy,
+}
+
+unsafe impl Send for TrackedProxy {}
+
+lazy_static::lazy_static! {
+    static ref TRACKED_PROXIES: Mutex<HashMap<usize, Vec<TrackedProxy>>> = Mutex::new(HashMap::new());
+}
+
+impl TrackedProxy {
+    fn upgrade(&self) -> Option<Arc<ProxyInternal>> {
+        self.internal
+            .upgrade()
+            .filter(|internal| internal.alive.load(Ordering::Acquire))
+    }
+}
+
+pub(crate) fn register_connection(display: *mut wl_display) {
+    TRACKED_PROXIES
+        .lock()
+        .unwrap()
+        .entry(display as usize)
+        .or_default();
+}
+
+pub(crate) fn unregister_connection(display: *mut wl_display) {
+    TRACKED_PROXIES.lock().unwrap().remove(&(display as usize));
+}
+
+fn track_proxy(connection: usize, internal: &Arc<ProxyInternal>, ptr: *mut wl_proxy) {
+    let mut tracked = TRACKED_PROXIES.lock().unwrap();
+    let proxies = match tracked.get_mut(&connection) {
+        Some(proxies) => proxies,
+        None => return,
+    };
+    // cleanup the dead proxies before growing the storage
+    if proxies.len() == proxies.capacity() {
+        proxies.retain(|proxy| proxy.upgrade().is_some());
+    }
+    proxies.push(TrackedProxy {
+        internal: Arc::downgrade(internal),
+        ptr,
+    });
+    internal.connection.store(connection, Ordering::Release);
+}
+
+// track the objects created by the server in an event, once they are managed by us
+unsafe fn track_new_ids<I: Interface>(connection: usize, opcode: u32, args: *const wl_argument) {
+    let signature = I::Event::MESSAGES[opcode as usize].signature;
+    for (i, &arg) in signature.iter().enumerate() {
+        if arg != ArgumentType::NewId {
+            continue;
+        }
+        let ptr = (*args.add(i)).o as *mut wl_proxy;
+        if ptr.is_null()
+            || ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_get_listener, ptr)
+                != &::wayland_sys::RUST_MANAGED as *const u8 as *const _
+        {
+            continue;
+        }
+        let user_data = ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_get_user_data, ptr)
+            as *mut ProxyUserData<crate::AnonymousObject>;
+        track_proxy(connection, &(*user_data).internal, ptr);
+    }
+}
+
+// mark a proxy as dead and destroy it, without racing with the snapshots
+unsafe fn destroy_proxy(internal: Option<&Arc<ProxyInternal>>, ptr: *mut wl_proxy) {
+    let _tracked = TRACKED_PROXIES.lock().unwrap();
+    if let Some(internal) = internal {
+        internal.alive.store(false, Ordering::Release);
+    }
+    ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_destroy, ptr);
+}
+
+pub(crate) fn snapshot_proxies(display: *mut wl_display) -> ObjectTreeSnapshot {
+    let taken_at = timestamp();
+    let tracked = TRACKED_PROXIES.lock().unwrap();
+    let objects = tracked
+        .get(&(display as usize))
+        .into_iter()
+        .flat_map(|proxies| proxies.iter())
+        .filter_map(|proxy| {
+            let internal = proxy.upgrade()?;
+            // the creator of objects is not tracked by libwayland
+            let origin = ObjectOrigin {
+                time: internal.created,
+                creator: None,
+            };
+            // the proxy can't be destroyed while we hold the lock
+            unsafe {
+                let id = ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_get_id, proxy.ptr);
+                let version = ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_get_version, proxy.ptr);
+                let class = std::ffi::CStr::from_ptr(ffi_dispatch!(
+                    WAYLAND_CLIENT_HANDLE,
+                    wl_proxy_get_class,
+                    proxy.ptr
+                ));
+                Some(ObjectInfo::new(
+                    id,
+                    &class.to_string_lossy(),
+                    version,
+                    Some(&origin),
+                    internal.user_data.type_name(),
+                    taken_at,
+                ))
+            }
+        })
+        .collect();
+    ObjectTreeSnapshot::new(taken_at, objects)
+}
+
 pub(crate) struct ProxyInner {
     internal: Option<Arc<ProxyInternal>>,
     ptr: *mut wl_proxy,
@@ -49,6 +175,16 @@ impl ProxyInner {
         self.internal.is_none()
     }
 
+    // the connection in which the children of this proxy are tracked
+    fn connection(&self) -> usize {
+        match self.internal {
+            Some(ref internal) => internal.connection.load(Ordering::Acquire),
+            // the display itself is not managed by us, other external proxies are
+            // not tracked as they are not a registered connection
+            None => self.ptr as usize,
+        }
+    }
+
     pub(crate) fn version(&self) -> u32 {
         if !self.is_alive() {
             return 0;
@@ -140,7 +276,11 @@ impl ProxyInner {
                             version
                         )
                     });
-                    Some(ProxyInner::init_from_c_ptr::<J>(ptr))
+                    let child = ProxyInner::init_from_c_ptr::<J>(ptr);
+                    if let Some(ref internal) = child.internal {
+                        track_proxy(self.connection(), internal, ptr);
+                    }
+                    Some(child)
                 }
             } else {
                 // Create a dead proxy ex-nihilo
@@ -164,11 +304,8 @@ impl ProxyInner {
 
         if destructor {
             // we need to destroy the proxy now
-            if let Some(ref internal) = self.internal {
-                internal.alive.store(false, Ordering::Release);
-            }
             unsafe {
-                ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_destroy, self.ptr);
+                destroy_proxy(self.internal.as_ref(), self.ptr);
             }
         }
 
@@ -259,7 +396,9 @@ impl ProxyInner {
         ProxyInner {
             internal: Some(Arc::new(ProxyInternal {
                 alive: AtomicBool::new(false),
+                created: timestamp(),
                 user_data: UserData::new(),
+                connection: AtomicUsize::new(0),
             })),
             ptr: std::ptr::null_mut(),
             wrapping: None,
@@ -333,6 +472,8 @@ impl Drop for ProxyInner {
 
 type BoxedCallback<I> = Box<dyn Fn(<I as Interface>::Event, Main<I>)>;
 
+// the layout of ProxyUserData must not depend on its interface
+#[repr(C)]
 struct ProxyUserData<I: Interface + From<Proxy<I>> + AsRef<Proxy<I>>> {
     internal: Arc<ProxyInternal>,
     implem: RefCell<Option<BoxedCallback<I>>>,
@@ -369,15 +510,17 @@ where
             let user_data = &mut *(user_data as *mut ProxyUserData<I>);
             let implem = user_data.implem.borrow();
 
+            let connection = user_data.internal.connection.load(Ordering::Acquire);
+
             if must_destroy {
-                user_data.internal.alive.store(false, Ordering::Release);
-                ffi_dispatch!(WAYLAND_CLIENT_HANDLE, wl_proxy_destroy, proxy);
+                destroy_proxy(Some(&user_data.internal), proxy);
             }
             // if there is an implem, call it, otherwise call the fallback
             match implem.as_ref() {
                 Some(implem) => {
                     // parse the message:
                     let msg = I::Event::from_raw_c(proxy as *mut _, opcode, args)?;
+                    track_new_ids::<I>(connection, opcode, args);
                     // create the proxy object
                     let mut proxy_inner = ProxyInner::from_c_ptr::<I>(proxy);
                     // This proxy must be a Main, so it as attached wrapping itself
@@ -390,6 +533,7 @@ where
                         let mut fallback = fallback.borrow_mut();
                         // parse the message:
                         let msg = parse_raw_event::<I>(opcode, args);
+                        track_new_ids::<I>(connection, opcode, args);
                         // create the proxy object
                         let proxy_obj = crate::Main::wrap(ProxyInner::from_c_ptr::<I>(proxy));
                         (&mut *fallback)(msg, proxy_obj);
@@ -1,4 +1,5 @@
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::AnonymousObject;
 use wayland_commons::user_data::UserData;
@@ -12,6 +13,57 @@ use crate::imp::ProxyInner;
 
 use wayland_commons::{filter::Filter, MessageGroup};
 
+/// Delivery of the events of an interface to a handler
+///
+/// This trait is implemented by the code generated with handler traits: the interfaces
+/// implement it for all the implementors of their `EventHandler` trait. It is used by
+/// `Main::assign_handler`.
+pub trait HandledBy<H>: Interface + AsRef<Proxy<Self>> + From<Proxy<Self>> {
+    /// Deliver an event of an object to the handler
+    fn handle(handler: &mut H, object: Main<Self>, event: Self::Event);
+}
+
+/// The reasons why a request could not be sent
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SendError {
+    /// The object is dead, it was destroyed by a destructor request or event
+    DeadObject,
+    /// The request is not available at the version of the object
+    VersionTooLow {
+        /// The version of the interface introducing the request
+        since: u32,
+        /// The version of the object
+        version: u32,
+    },
+}
+
+impl ::std::error::Error for SendError {
+    fn description(&self) -> &str {
+        match *self {
+            SendError::DeadObject => "The object is dead.",
+            SendError::VersionTooLow { .. } => "The version of the object is too low for the request.",
+        }
+    }
+}
+
+impl ::std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        f.write_str(::std::error::Error::description(self))
+    }
+}
+
+static PANIC_ON_SEND_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Panic rather than returning a `SendError`
+///
+/// Once enabled, sending a request through a dead object or an object whose version is
+/// too low panics, like a failed debug assertion, so that tests catch the requests that
+/// were not sent even if their error is ignored. This is a process-wide setting, disabled
+/// by default.
+pub fn panic_on_send_errors(enabled: bool) {
+    PANIC_ON_SEND_ERRORS.store(enabled, Ordering::Relaxed);
+}
+
 /// An handle to a wayland proxy
 ///
 /// This represents a wayland object instantiated in your client
@@ -68,29 +120,52 @@ where
         }
     }
 
-    /// Send a request creating an object through this object
+    /// Send a request through this object
     ///
     /// **Warning:** This method is mostly intended to be used by code generated
     /// by `wayland-scanner`, and you should probably never need to use it directly,
     /// but rather use the appropriate methods on the Rust object.
     ///
-    /// This is the generic method to send requests
-    pub fn send<J>(&self, msg: I::Request, version: Option<u32>) -> Option<Main<J>>
+    /// This is the generic method to send requests. If the request creates an object,
+    /// the new object is returned.
+    ///
+    /// The request is not sent if this object is dead, or if its version is lower than
+    /// the version of the interface introducing the request. A `SendError` is returned
+    /// instead, or the method panics if `panic_on_send_errors` is enabled.
+    pub fn send<J>(&self, msg: I::Request, version: Option<u32>) -> Result<Option<Main<J>>, SendError>
     where
         J: Interface + AsRef<Proxy<J>> + From<Proxy<J>>,
     {
-        if msg.since() > self.version() && self.version() > 0 {
-            let opcode = msg.opcode() as usize;
-            panic!(
-                "Cannot send request {} which requires version >= {} on proxy {}@{} which is version {}.",
-                I::Request::MESSAGES[opcode].name,
-                msg.since(),
-                I::NAME,
-                self.id(),
-                self.version()
-            );
+        let error = if !self.is_alive() {
+            Some(SendError::DeadObject)
+        } else if msg.since() > self.version() && self.version() > 0 {
+            Some(SendError::VersionTooLow {
+                since: msg.since(),
+                version: self.version(),
+            })
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            if PANIC_ON_SEND_ERRORS.load(Ordering::Relaxed) {
+                let name = I::Request::MESSAGES[msg.opcode() as usize].name;
+                match error {
+                    SendError::DeadObject => {
+                        panic!("Cannot send request {} on a dead proxy of {}.", name, I::NAME)
+                    }
+                    SendError::VersionTooLow { since, version } => panic!(
+                        "Cannot send request {} which requires version >= {} on proxy {}@{} which is version {}.",
+                        name,
+                        since,
+                        I::NAME,
+                        self.id(),
+                        version
+                    ),
+                }
+            }
+            return Err(error);
         }
-        self.inner.send::<I, J>(msg, version).map(Main::wrap)
+        Ok(self.inner.send::<I, J>(msg, version).map(Main::wrap))
     }
 
     /// Check if the object associated with this proxy is still alive
@@ -280,6 +355,20 @@ where
     {
         self.assign(Filter::new(move |(proxy, event), _| f(proxy, event)))
     }
+
+    /// Assign a handler to this object
+    ///
+    /// Behaves similarly as `assign_mono(..)`, but the events are delivered to the
+    /// methods of the `EventHandler` trait generated for the interface, which
+    /// `handler` implements.
+    pub fn assign_handler<H>(&self, mut handler: H)
+    where
+        I: Interface + AsRef<Proxy<I>> + From<Proxy<I>> + Sync + HandledBy<H>,
+        H: 'static,
+        I::Event: MessageGroup<Map = crate::ProxyMap>,
+    {
+        self.assign_mono(move |proxy, event| I::handle(&mut handler, proxy, event))
+    }
 }
 
 impl Main<AnonymousObject> {
@@ -4,6 +4,7 @@ use std::sync::{Arc, Mutex};
 
 use nix::Result as NixResult;
 
+use wayland_commons::introspection::ObjectOrigin;
 use wayland_commons::map::{Object, ObjectMap, SERVER_ID_LIMIT};
 use wayland_commons::socket::{BufferedSocket, Socket};
 use wayland_commons::wire::{Argument, ArgumentType, Message, MessageParseError};
@@ -86,6 +87,9 @@ impl Connection {
                         .next()
                         .unwrap();
                     let child_interface = child.interface;
+                    if let Some(ref parent) = object {
+                        child.meta.origin = ObjectOrigin::created_by(parent.interface, msg.sender_id, parent.events[msg.opcode as usize].name);
+                    }
                     // if this ID belonged to a now destroyed server object, we can replace it
                     if new_id >= SERVER_ID_LIMIT && map.with(new_id, |obj| obj.meta.client_destroyed).unwrap_or(false) {
                         map.remove(new_id)
@@ -1,7 +1,9 @@
 use std::io;
 use std::os::unix::io::RawFd;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 
+use wayland_commons::introspection::{timestamp, ObjectInfo, ObjectTreeSnapshot};
 use wayland_commons::map::{Object, ObjectMap};
 use wayland_commons::wire::Message;
 use wayland_commons::MessageGroup;
@@ -67,6 +69,27 @@ impl DisplayInner {
         &self.proxy
     }
 
+    pub(crate) fn snapshot_objects(&self) -> ObjectTreeSnapshot {
+        let taken_at = timestamp();
+        let mut objects = Vec::new();
+        let map = self.connection.lock().unwrap().map.clone();
+        map.lock().unwrap().with_all(|id, obj| {
+            // skip the objects destroyed but not yet acknowledged by the server
+            if !obj.meta.alive.load(Ordering::Acquire) {
+                return;
+            }
+            objects.push(ObjectInfo::new(
+                id,
+                obj.interface,
+                obj.version,
+                Some(&obj.meta.origin),
+                obj.meta.user_data.type_name(),
+                taken_at,
+            ));
+        });
+        ObjectTreeSnapshot::new(taken_at, objects)
+    }
+
     pub(crate) fn protocol_error(&self) -> Option<ProtocolError> {
         let cx = self.connection.lock().unwrap();
         let last_error = cx.last_error.lock().unwrap();
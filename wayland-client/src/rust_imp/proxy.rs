@@ -2,6 +2,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use wayland_commons::filter::Filter;
+use wayland_commons::introspection::ObjectOrigin;
 use wayland_commons::map::{Object, ObjectMap, ObjectMetadata};
 use wayland_commons::user_data::UserData;
 use wayland_commons::wire::{Argument, ArgumentType};
@@ -16,7 +17,8 @@ use crate::{Interface, Main, Proxy};
 pub(crate) struct ObjectMeta {
     pub(crate) buffer: QueueBuffer,
     pub(crate) alive: Arc<AtomicBool>,
-    user_data: Arc<UserData>,
+    pub(crate) origin: ObjectOrigin,
+    pub(crate) user_data: Arc<UserData>,
     pub(crate) dispatcher: Arc<Mutex<dyn Dispatcher>>,
     pub(crate) server_destroyed: bool,
     pub(crate) client_destroyed: bool,
@@ -27,6 +29,7 @@ impl ObjectMetadata for ObjectMeta {
         ObjectMeta {
             buffer: self.buffer.clone(),
             alive: Arc::new(AtomicBool::new(true)),
+            origin: ObjectOrigin::now(),
             user_data: Arc::new(UserData::new()),
             dispatcher: super::default_dispatcher(),
             server_destroyed: false,
@@ -40,6 +43,7 @@ impl ObjectMeta {
         ObjectMeta {
             buffer,
             alive: Arc::new(AtomicBool::new(true)),
+            origin: ObjectOrigin::now(),
             user_data: Arc::new(UserData::new()),
             dispatcher: super::default_dispatcher(),
             server_destroyed: false,
@@ -51,6 +55,7 @@ impl ObjectMeta {
         ObjectMeta {
             buffer: super::queues::create_queue_buffer(),
             alive: Arc::new(AtomicBool::new(false)),
+            origin: ObjectOrigin::now(),
             user_data: Arc::new(UserData::new()),
             dispatcher: super::default_dispatcher(),
             server_destroyed: true,
@@ -152,7 +157,7 @@ impl ProxyInner {
                 nid_idx += 2;
             }
             // insert the newly created object in the message
-            let new_object = Object::from_interface::<J>(
+            let mut new_object = Object::from_interface::<J>(
                 version.unwrap_or(self.object.version),
                 if alive {
                     ObjectMeta::new(target_queue.clone())
@@ -160,6 +165,8 @@ impl ProxyInner {
                     ObjectMeta::dead()
                 },
             );
+            new_object.meta.origin =
+                ObjectOrigin::created_by(I::NAME, self.id, I::Request::MESSAGES[opcode as usize].name);
             let mut new_id = 0;
             if alive {
                 new_id = self.map.lock().unwrap().client_insert_new(new_object.clone());
@@ -0,0 +1,129 @@
+//! Tracking of the serials of input events
+//!
+//! Many requests, like `wl_pointer.set_cursor`, `wl_data_device.set_selection` or
+//! `xdg_toplevel.move`, need to provide the serial of the input event that triggered them,
+//! and compositors reject the requests whose serial is unknown or outdated. This module
+//! provides `SeatSerials`, which records the latest serial of each kind of input event of
+//! a seat, for you to fill in or validate the serials of these requests.
+
+use std::cell::Cell;
+
+use crate::protocol::{wl_keyboard, wl_pointer, wl_touch};
+
+pub use wayland_commons::serial::Serial;
+
+/// The kinds of input events whose serial is tracked by `SeatSerials`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SerialKind {
+    /// The pointer entered a surface
+    PointerEnter,
+    /// A pointer button was pressed
+    PointerButton,
+    /// The keyboard entered a surface
+    KeyboardEnter,
+    /// A key was pressed
+    Key,
+    /// A touch point went down
+    TouchDown,
+}
+
+/// The latest serials of the input events of a seat
+///
+/// You need to keep one of these for each seat, and feed it the events of the pointers,
+/// keyboards and touch devices of this seat from your callbacks. As it only needs a shared
+/// reference to do so, it can be shared between your callbacks with an `Rc`.
+///
+/// Only the presses are recorded for the buttons and the keys, as the requests needing
+/// a serial expect the one of the press that triggered them.
+#[derive(Debug, Default)]
+pub struct SeatSerials {
+    pointer_enter: Cell<Option<Serial>>,
+    pointer_button: Cell<Option<Serial>>,
+    keyboard_enter: Cell<Option<Serial>>,
+    key: Cell<Option<Serial>>,
+    touch_down: Cell<Option<Serial>>,
+}
+
+impl SeatSerials {
+    /// Create a new empty record
+    pub fn new() -> SeatSerials {
+        Default::default()
+    }
+
+    fn cell(&self, kind: SerialKind) -> &Cell<Option<Serial>> {
+        match kind {
+            SerialKind::PointerEnter => &self.pointer_enter,
+            SerialKind::PointerButton => &self.pointer_button,
+            SerialKind::KeyboardEnter => &self.keyboard_enter,
+            SerialKind::Key => &self.key,
+            SerialKind::TouchDown => &self.touch_down,
+        }
+    }
+
+    /// Record the serial of an event of given kind
+    pub fn record(&self, kind: SerialKind, serial: u32) {
+        self.cell(kind).set(Some(Serial::from(serial)));
+    }
+
+    /// Record the serial of a pointer event, if it carries a tracked one
+    pub fn process_pointer_event(&self, event: &wl_pointer::Event) {
+        match *event {
+            wl_pointer::Event::Enter { serial, .. } => self.record(SerialKind::PointerEnter, serial),
+            wl_pointer::Event::Button {
+                serial,
+                state: wl_pointer::ButtonState::Pressed,
+                ..
+            } => self.record(SerialKind::PointerButton, serial),
+            _ => {}
+        }
+    }
+
+    /// Record the serial of a keyboard event, if it carries a tracked one
+    pub fn process_keyboard_event(&self, event: &wl_keyboard::Event) {
+        match *event {
+            wl_keyboard::Event::Enter { serial, .. } => self.record(SerialKind::KeyboardEnter, serial),
+            wl_keyboard::Event::Key {
+                serial,
+                state: wl_keyboard::KeyState::Pressed,
+                ..
+            } => self.record(SerialKind::Key, serial),
+            _ => {}
+        }
+    }
+
+    /// Record the serial of a touch event, if it carries a tracked one
+    pub fn process_touch_event(&self, event: &wl_touch::Event) {
+        if let wl_touch::Event::Down { serial, .. } = *event {
+            self.record(SerialKind::TouchDown, serial);
+        }
+    }
+
+    /// The latest serial of given kind, if any
+    pub fn get(&self, kind: SerialKind) -> Option<Serial> {
+        self.cell(kind).get()
+    }
+
+    /// The latest serial of the user interactions of this seat
+    ///
+    /// This is the most recent of the pointer button, key and touch down serials, which is
+    /// the one to use for requests like `wl_data_device.set_selection` or `xdg_toplevel.move`.
+    pub fn latest_interaction(&self) -> Option<Serial> {
+        [SerialKind::PointerButton, SerialKind::Key, SerialKind::TouchDown]
+            .iter()
+            .filter_map(|&kind| self.get(kind))
+            .fold(None, |latest, serial| match latest {
+                Some(latest) if latest.is_no_older_than(&serial) => Some(latest),
+                _ => Some(serial),
+            })
+    }
+
+    /// Check if given serial is the latest one of any of given kinds
+    ///
+    /// This can be used to check that a serial is still relevant before sending a request
+    /// with it, as compositors generally reject outdated serials.
+    pub fn is_latest(&self, serial: u32, kinds: &[SerialKind]) -> bool {
+        kinds
+            .iter()
+            .any(|&kind| self.get(kind) == Some(Serial::from(serial)))
+    }
+}
@@ -0,0 +1,316 @@
+//! Shared memory buffers
+//!
+//! The software-rendered clients draw their contents in `wl_buffer`s created from a `wl_shm_pool`,
+//! a memory area shared with the server. This module provides `ShmPool`, which manages such a
+//! pool: it creates and maps its memory, grows it as needed, and recycles the buffers once the
+//! server has released them.
+//!
+//! Double (or triple) buffering comes for free: just ask the pool for a buffer each time you draw.
+//! You will get a buffer the server is not using anymore, or a new one if they are all in use.
+//!
+//! ```no_run
+//! # extern crate wayland_client;
+//! # use wayland_client::{Display, GlobalManager};
+//! # use wayland_client::protocol::{wl_shm::{Format, WlShm}, wl_surface::WlSurface};
+//! # use wayland_client::shm::ShmPool;
+//! # fn draw(surface: &WlSurface, shm: &wayland_client::Attached<WlShm>) {
+//! let mut pool = ShmPool::new(shm).expect("Failed to create the pool.");
+//! // then, for each frame
+//! let (canvas, buffer) = pool.buffer(320, 240, 320 * 4, Format::Argb8888).unwrap();
+//! for pixel in canvas.chunks_mut(4) {
+//!     pixel.copy_from_slice(&[0xFF, 0x00, 0x00, 0xFF]);
+//! }
+//! surface.attach(Some(&buffer), 0, 0).unwrap();
+//! surface.commit().unwrap();
+//! # }
+//! # fn main() {}
+//! ```
+
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::ptr;
+use std::rc::Rc;
+
+use nix::sys::mman;
+
+use crate::protocol::wl_buffer::{self, WlBuffer};
+use crate::protocol::wl_shm::{Format, WlShm};
+use crate::protocol::wl_shm_pool::WlShmPool;
+use crate::{Attached, Main, SendError};
+
+const INITIAL_SIZE: usize = 4096;
+
+struct Slot {
+    offset: usize,
+    capacity: usize,
+    // the parameters of the current buffer of this slot
+    params: (i32, i32, i32, Format),
+    buffer: Main<WlBuffer>,
+    busy: Rc<Cell<bool>>,
+}
+
+/// A pool of shared memory buffers
+///
+/// The buffers it hands out are considered in use by the server until it sends their `release`
+/// event, after which they are reused by the next calls to `buffer()`. This requires the event
+/// queue of the `wl_shm` the pool was created from to be dispatched.
+///
+/// The pool never shrinks: its memory can only grow to fit more or larger buffers.
+///
+/// Dropping the pool destroys it and all its buffers.
+pub struct ShmPool {
+    pool: Main<WlShmPool>,
+    file: File,
+    ptr: *mut u8,
+    size: usize,
+    // sorted by offset, each slot starting where the previous one ends
+    slots: Vec<Slot>,
+}
+
+impl ShmPool {
+    /// Create a new pool from a `wl_shm` global
+    pub fn new(shm: &Attached<WlShm>) -> io::Result<ShmPool> {
+        let file = create_shm_file()?;
+        file.set_len(INITIAL_SIZE as u64)?;
+        let ptr = map(file.as_raw_fd(), INITIAL_SIZE)?;
+        let pool = shm
+            .create_pool(file.as_raw_fd(), INITIAL_SIZE as i32)
+            .map_err(send_error)?;
+        Ok(ShmPool {
+            pool,
+            file,
+            ptr,
+            size: INITIAL_SIZE,
+            slots: Vec::new(),
+        })
+    }
+
+    /// The size of the memory of this pool
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of buffers currently in use by the server
+    pub fn busy_buffers(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.busy.get()).count()
+    }
+
+    /// Get a buffer with given parameters
+    ///
+    /// This returns a buffer not in use by the server, along with its contents for you to draw on.
+    /// The buffer is then considered in use until the server releases it, so you need to attach it
+    /// to a surface and commit it.
+    ///
+    /// The contents of a recycled buffer are those you drew previously on this buffer, or garbage
+    /// if it was resized.
+    ///
+    /// Fails if the parameters are invalid, or if the pool cannot grow to fit the buffer.
+    pub fn buffer(
+        &mut self,
+        width: i32,
+        height: i32,
+        stride: i32,
+        format: Format,
+    ) -> io::Result<(&mut [u8], WlBuffer)> {
+        if width <= 0 || height <= 0 || stride <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid buffer dimensions.",
+            ));
+        }
+        let len = (height as usize)
+            .checked_mul(stride as usize)
+            .filter(|&len| len <= ::std::i32::MAX as usize)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Buffer too large."))?;
+        let params = (width, height, stride, format);
+
+        // reuse a free buffer with the same parameters, or allocate a new one
+        let index = match self
+            .slots
+            .iter()
+            .position(|s| !s.busy.get() && s.params == params)
+        {
+            Some(index) => index,
+            None => self.allocate(len, params)?,
+        };
+
+        let slot = &self.slots[index];
+        slot.busy.set(true);
+        let contents = unsafe { ::std::slice::from_raw_parts_mut(self.ptr.add(slot.offset), len) };
+        Ok((contents, (**slot.buffer).clone()))
+    }
+
+    // find room for a buffer of given size in a free slot, merging the free slots or appending
+    // a new slot at the end of the pool if needed, and create the buffer there
+    fn allocate(&mut self, len: usize, params: (i32, i32, i32, Format)) -> io::Result<usize> {
+        let index = match self.free_slot(len) {
+            Some(index) => Some(index),
+            None => {
+                self.merge_free_slots();
+                self.free_slot(len)
+            }
+        };
+        if let Some(index) = index {
+            let slot = &mut self.slots[index];
+            let _ = slot.buffer.destroy();
+            slot.buffer = create_buffer(&self.pool, slot.offset, params, &slot.busy)?;
+            slot.params = params;
+            return Ok(index);
+        }
+
+        // the last slot is too small, extend it rather than appending after it
+        if self.slots.last().map(|s| !s.busy.get()).unwrap_or(false) {
+            if let Some(slot) = self.slots.pop() {
+                let _ = slot.buffer.destroy();
+            }
+        }
+        let offset = self.slots.last().map(|s| s.offset + s.capacity).unwrap_or(0);
+        self.grow(offset + len)?;
+        let busy = Rc::new(Cell::new(false));
+        self.slots.push(Slot {
+            offset,
+            capacity: len,
+            params,
+            buffer: create_buffer(&self.pool, offset, params, &busy)?,
+            busy,
+        });
+        Ok(self.slots.len() - 1)
+    }
+
+    fn free_slot(&self, len: usize) -> Option<usize> {
+        self.slots.iter().position(|s| !s.busy.get() && s.capacity >= len)
+    }
+
+    // merge the adjacent free slots, destroying their buffers but the first one
+    fn merge_free_slots(&mut self) {
+        let mut i = 0;
+        while i + 1 < self.slots.len() {
+            if !self.slots[i].busy.get() && !self.slots[i + 1].busy.get() {
+                let next = self.slots.remove(i + 1);
+                let _ = next.buffer.destroy();
+                self.slots[i].capacity += next.capacity;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    // grow the pool to at least given size
+    fn grow(&mut self, size: usize) -> io::Result<()> {
+        if size <= self.size {
+            return Ok(());
+        }
+        // the size of a pool is an i32 on the wire
+        if size > ::std::i32::MAX as usize {
+            return Err(nix::errno::Errno::ENOMEM.into());
+        }
+        // double the size of the pool, to avoid resizing it too often
+        let new_size = ::std::cmp::min(::std::cmp::max(size, self.size * 2), ::std::i32::MAX as usize);
+        self.file.set_len(new_size as u64)?;
+        let ptr = map(self.file.as_raw_fd(), new_size)?;
+        unmap(self.ptr, self.size);
+        self.ptr = ptr;
+        self.size = new_size;
+        self.pool.resize(new_size as i32).map_err(send_error)
+    }
+}
+
+impl Drop for ShmPool {
+    fn drop(&mut self) {
+        for slot in &self.slots {
+            let _ = slot.buffer.destroy();
+        }
+        let _ = self.pool.destroy();
+        unmap(self.ptr, self.size);
+    }
+}
+
+fn create_buffer(
+    pool: &WlShmPool,
+    offset: usize,
+    (width, height, stride, format): (i32, i32, i32, Format),
+    busy: &Rc<Cell<bool>>,
+) -> io::Result<Main<WlBuffer>> {
+    let buffer = pool
+        .create_buffer(offset as i32, width, height, stride, format)
+        .map_err(send_error)?;
+    let busy = busy.clone();
+    buffer.assign_mono(move |_, event| {
+        if let wl_buffer::Event::Release = event {
+            busy.set(false);
+        }
+    });
+    Ok(buffer)
+}
+
+fn send_error(error: SendError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+fn create_shm_file() -> io::Result<File> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+        let name = CStr::from_bytes_with_nul(b"wayland-rs-shm\0").unwrap();
+        // fallback to shm_open if memfd is not supported by the kernel
+        if let Ok(fd) = memfd_create(name, MemFdCreateFlag::MFD_CLOEXEC) {
+            return Ok(unsafe { File::from_raw_fd(fd) });
+        }
+    }
+    shm_open_anonymous()
+}
+
+fn shm_open_anonymous() -> io::Result<File> {
+    use nix::fcntl::OFlag;
+    use nix::sys::stat::Mode;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut attempt = 0u32;
+    loop {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let name = format!("/wayland-rs-{}-{}-{}\0", nix::unistd::getpid(), nanos, attempt);
+        let name = CStr::from_bytes_with_nul(name.as_bytes()).unwrap();
+        match mman::shm_open(
+            name,
+            OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_RDWR | OFlag::O_CLOEXEC,
+            Mode::S_IRUSR | Mode::S_IWUSR,
+        ) {
+            Ok(fd) => {
+                // the file only needs to live as long as its fds
+                let _ = mman::shm_unlink(name);
+                return Ok(unsafe { File::from_raw_fd(fd) });
+            }
+            Err(nix::Error::Sys(nix::errno::Errno::EEXIST)) if attempt < 16 => attempt += 1,
+            Err(nix::Error::Sys(errno)) => return Err(errno.into()),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+fn map(fd: RawFd, size: usize) -> io::Result<*mut u8> {
+    let ret = unsafe {
+        mman::mmap(
+            ptr::null_mut(),
+            size,
+            mman::ProtFlags::PROT_READ | mman::ProtFlags::PROT_WRITE,
+            mman::MapFlags::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+    match ret {
+        Ok(ptr) => Ok(ptr as *mut u8),
+        Err(nix::Error::Sys(errno)) => Err(errno.into()),
+        Err(_) => unreachable!(),
+    }
+}
+
+fn unmap(ptr: *mut u8, size: usize) {
+    let _ = unsafe { mman::munmap(ptr as *mut _, size) };
+}
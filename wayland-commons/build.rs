@@ -0,0 +1,24 @@
+use std::env::var;
+use std::process::Command;
+
+// `std::any::type_name` is only available since rust 1.38
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(has_type_name)");
+    let rustc = var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let minor = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|version| {
+            // the output looks like "rustc 1.38.0 (625451e37 2019-09-23)"
+            version
+                .split_whitespace()
+                .nth(1)
+                .and_then(|v| v.split('.').nth(1))
+                .and_then(|minor| minor.parse::<u32>().ok())
+        });
+    if minor.map(|minor| minor >= 38).unwrap_or(false) {
+        println!("cargo:rustc-cfg=has_type_name");
+    }
+}
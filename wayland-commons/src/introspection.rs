@@ -0,0 +1,296 @@
+//! Introspection of the protocol objects of a connection
+//!
+//! Both `wayland-client` and `wayland-server` can take a snapshot of the protocol
+//! objects currently alive on a connection, as an `ObjectTreeSnapshot`. This is mostly
+//! intended as a debugging tool: comparing snapshots taken at different points in time
+//! (see `ObjectTreeSnapshot::diff()`) allows you to find which objects are leaking.
+//!
+//! If the `serde` cargo feature is enabled, all the types of this module can be serialized,
+//! so that snapshots can be stored and compared later.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+static EPOCH: spin::Once<Instant> = spin::Once::new();
+
+/// Time elapsed since a process-wide reference point
+///
+/// All creation times of objects and snapshots are expressed relative to
+/// this same reference point, so that they can be compared.
+pub fn timestamp() -> Duration {
+    EPOCH.call_once(Instant::now).elapsed()
+}
+
+/// Creation information of a protocol object
+///
+/// This is tracked by the protocol implementations for each object they manage.
+#[derive(Copy, Clone, Debug)]
+pub struct ObjectOrigin {
+    /// Creation time of the object, see `timestamp()`
+    pub time: Duration,
+    /// The message that created this object, if known, as the interface
+    /// and id of its sender, and the name of the message
+    pub creator: Option<(&'static str, u32, &'static str)>,
+}
+
+impl ObjectOrigin {
+    /// Origin of an object created now, by an unknown message
+    pub fn now() -> ObjectOrigin {
+        ObjectOrigin {
+            time: timestamp(),
+            creator: None,
+        }
+    }
+
+    /// Origin of an object created now, by given message
+    pub fn created_by(interface: &'static str, id: u32, message: &'static str) -> ObjectOrigin {
+        ObjectOrigin {
+            time: timestamp(),
+            creator: Some((interface, id, message)),
+        }
+    }
+}
+
+/// The message that created a protocol object
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ObjectCreator {
+    /// Interface of the object that sent the message
+    pub interface: String,
+    /// Id of the object that sent the message
+    pub id: u32,
+    /// Name of the message
+    pub message: String,
+}
+
+impl fmt::Display for ObjectCreator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{}.{}", self.interface, self.id, self.message)
+    }
+}
+
+/// Description of a protocol object in a snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ObjectInfo {
+    /// Protocol id of the object
+    pub id: u32,
+    /// Interface of the object
+    pub interface: String,
+    /// Version of the object
+    pub version: u32,
+    /// The message that created this object, if known
+    pub creator: Option<ObjectCreator>,
+    /// Time elapsed between the creation of this object and the snapshot, if known
+    pub age: Option<Duration>,
+    /// Name of the type stored in the user data of this object, if any
+    pub user_data: Option<String>,
+}
+
+impl ObjectInfo {
+    /// Describe an object for a snapshot taken at time `taken_at`
+    pub fn new(
+        id: u32,
+        interface: &str,
+        version: u32,
+        origin: Option<&ObjectOrigin>,
+        user_data: Option<&str>,
+        taken_at: Duration,
+    ) -> ObjectInfo {
+        ObjectInfo {
+            id,
+            interface: interface.into(),
+            version,
+            creator: origin
+                .and_then(|o| o.creator)
+                .map(|(interface, id, message)| ObjectCreator {
+                    interface: interface.into(),
+                    id,
+                    message: message.into(),
+                }),
+            age: origin.map(|o| taken_at.checked_sub(o.time).unwrap_or_default()),
+            user_data: user_data.map(Into::into),
+        }
+    }
+
+    // ids are reused, so an object is identified by its id, interface and creation time
+    fn identity(&self, taken_at: Duration) -> (u32, &str, Option<Duration>) {
+        (
+            self.id,
+            &self.interface,
+            self.age.and_then(|age| taken_at.checked_sub(age)),
+        )
+    }
+}
+
+/// A snapshot of the protocol objects alive on a connection
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ObjectTreeSnapshot {
+    /// Time at which this snapshot was taken, see `timestamp()`
+    pub taken_at: Duration,
+    /// The objects alive at this time, sorted by id
+    pub objects: Vec<ObjectInfo>,
+}
+
+impl ObjectTreeSnapshot {
+    /// Create a snapshot from a list of objects
+    pub fn new(taken_at: Duration, mut objects: Vec<ObjectInfo>) -> ObjectTreeSnapshot {
+        objects.sort_by_key(|o| o.id);
+        ObjectTreeSnapshot { taken_at, objects }
+    }
+
+    /// Find the object with given id in this snapshot
+    pub fn find(&self, id: u32) -> Option<&ObjectInfo> {
+        self.objects
+            .binary_search_by_key(&id, |o| o.id)
+            .ok()
+            .map(|idx| &self.objects[idx])
+    }
+
+    /// Iterate over the objects that were created by a message of the object with given id
+    pub fn children<'a>(&'a self, id: u32) -> impl Iterator<Item = &'a ObjectInfo> + 'a {
+        self.objects
+            .iter()
+            .filter(move |o| o.creator.as_ref().map(|c| c.id == id).unwrap_or(false))
+    }
+
+    /// Count the objects of this snapshot for each interface
+    pub fn count_by_interface(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for object in &self.objects {
+            *counts.entry(object.interface.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Compute the changes between a previous snapshot and this one
+    pub fn diff(&self, previous: &ObjectTreeSnapshot) -> SnapshotDiff {
+        let old = previous
+            .objects
+            .iter()
+            .map(|o| o.identity(previous.taken_at))
+            .collect::<HashSet<_>>();
+        let new = self
+            .objects
+            .iter()
+            .map(|o| o.identity(self.taken_at))
+            .collect::<HashSet<_>>();
+
+        let created = self
+            .objects
+            .iter()
+            .filter(|o| !old.contains(&o.identity(self.taken_at)))
+            .cloned()
+            .collect();
+        let destroyed = previous
+            .objects
+            .iter()
+            .filter(|o| !new.contains(&o.identity(previous.taken_at)))
+            .cloned()
+            .collect();
+
+        let mut count_changes = BTreeMap::new();
+        for (interface, count) in self.count_by_interface() {
+            count_changes.insert(interface, count as i64);
+        }
+        for (interface, count) in previous.count_by_interface() {
+            *count_changes.entry(interface).or_insert(0) -= count as i64;
+        }
+        let count_changes = count_changes
+            .into_iter()
+            .filter(|&(_, change)| change != 0)
+            .collect();
+
+        SnapshotDiff {
+            created,
+            destroyed,
+            count_changes,
+        }
+    }
+}
+
+/// The changes between two snapshots of the same connection
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SnapshotDiff {
+    /// Objects present in the new snapshot but not in the previous one
+    pub created: Vec<ObjectInfo>,
+    /// Objects present in the previous snapshot but not in the new one
+    pub destroyed: Vec<ObjectInfo>,
+    /// The change of the number of objects of each interface, only listing
+    /// the interfaces for which it changed
+    pub count_changes: BTreeMap<String, i64>,
+}
+
+impl SnapshotDiff {
+    /// List the interfaces whose number of objects increased, by decreasing growth
+    pub fn growing_interfaces(&self) -> Vec<(&str, i64)> {
+        let mut growing = self
+            .count_changes
+            .iter()
+            .filter(|&(_, &change)| change > 0)
+            .map(|(interface, &change)| (&interface[..], change))
+            .collect::<Vec<_>>();
+        growing.sort_by_key(|&(_, change)| ::std::cmp::Reverse(change));
+        growing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(id: u32, interface: &'static str, created: u64, taken_at: Duration) -> ObjectInfo {
+        let origin = ObjectOrigin {
+            time: Duration::from_secs(created),
+            creator: Some(("wl_compositor", 3, "create_surface")),
+        };
+        ObjectInfo::new(id, interface, 1, Some(&origin), None, taken_at)
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let t1 = Duration::from_secs(10);
+        let t2 = Duration::from_secs(20);
+        let old = ObjectTreeSnapshot::new(
+            t1,
+            vec![object(4, "wl_surface", 1, t1), object(5, "wl_buffer", 2, t1)],
+        );
+        // id 5 was destroyed then reused, and two surfaces were created
+        let new = ObjectTreeSnapshot::new(
+            t2,
+            vec![
+                object(7, "wl_surface", 15, t2),
+                object(4, "wl_surface", 1, t2),
+                object(5, "wl_surface", 12, t2),
+            ],
+        );
+
+        let diff = new.diff(&old);
+        assert_eq!(diff.created.iter().map(|o| o.id).collect::<Vec<_>>(), vec![5, 7]);
+        assert_eq!(diff.destroyed.iter().map(|o| o.id).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(diff.growing_interfaces(), vec![("wl_surface", 2)]);
+        assert_eq!(diff.count_changes.get("wl_buffer"), Some(&-1));
+    }
+
+    #[test]
+    fn snapshot_tree() {
+        let t = Duration::from_secs(10);
+        let snapshot = ObjectTreeSnapshot::new(
+            t,
+            vec![object(5, "wl_surface", 4, t), object(3, "wl_compositor", 1, t)],
+        );
+        assert_eq!(snapshot.objects[0].id, 3);
+        assert_eq!(snapshot.find(5).unwrap().age, Some(Duration::from_secs(6)));
+        assert_eq!(snapshot.children(3).count(), 2);
+        assert_eq!(
+            snapshot.find(5).unwrap().creator.as_ref().unwrap().to_string(),
+            "wl_compositor@3.create_surface"
+        );
+    }
+}
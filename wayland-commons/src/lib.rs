@@ -22,7 +22,9 @@ use std::os::raw::c_void;
 use wayland_sys::common as syscom;
 
 pub mod filter;
+pub mod introspection;
 pub mod map;
+pub mod serial;
 pub mod socket;
 pub mod user_data;
 pub mod wire;
@@ -61,15 +63,29 @@ pub trait MessageGroup: Sized {
     /// Turn this message into its raw representation
     fn into_raw(self, send_id: u32) -> wire::Message;
     /// Construct a message of this group from its C representation
-    unsafe fn from_raw_c(obj: *mut c_void, opcode: u32, args: *const syscom::wl_argument)
-        -> Result<Self, ()>;
+    ///
+    /// Only required by the system library backend. The default implementation, used by
+    /// the code generated without the C interfaces, panics.
+    unsafe fn from_raw_c(
+        _obj: *mut c_void,
+        _opcode: u32,
+        _args: *const syscom::wl_argument,
+    ) -> Result<Self, ()> {
+        panic!("This message group was generated without its C representation, it can not be used with the system library.")
+    }
     /// Build a C representation of this message
     ///
     /// It can only be accessed from the provided closure, and this consumes
     /// the message.
-    fn as_raw_c_in<F, T>(self, f: F) -> T
+    ///
+    /// Only required by the system library backend. The default implementation, used by
+    /// the code generated without the C interfaces, panics.
+    fn as_raw_c_in<F, T>(self, _f: F) -> T
     where
-        F: FnOnce(u32, &mut [syscom::wl_argument]) -> T;
+        F: FnOnce(u32, &mut [syscom::wl_argument]) -> T,
+    {
+        panic!("This message group was generated without its C representation, it can not be used with the system library.")
+    }
 }
 
 /// The description of a wayland interface
@@ -98,7 +114,15 @@ pub trait Interface: 'static {
     /// ones the server supports.
     const VERSION: u32;
     /// Pointer to the C representation of this interface
-    fn c_interface() -> *const syscom::wl_interface;
+    ///
+    /// Only required by the system library backend. The default implementation, used by
+    /// the code generated without the C interfaces, panics.
+    fn c_interface() -> *const syscom::wl_interface {
+        panic!(
+            "Interface {} was generated without its C representation, it can not be used with the system library.",
+            Self::NAME
+        )
+    }
 }
 
 /// An empty enum representing a MessageGroup with no messages
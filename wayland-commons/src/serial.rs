@@ -0,0 +1,93 @@
+//! Wayland serials
+//!
+//! The serials are `u32` counters generated by the server and attached to some of its
+//! events. They wrap around, so they cannot be compared as plain integers. The `Serial`
+//! type provides comparison methods taking this wrapping into account: a serial is
+//! considered newer than another if it is less than 2^31 steps ahead of it.
+
+use std::fmt;
+
+/// A wayland serial
+///
+/// It purposely does not implement `Ord`: as serials wrap around, no total order exists
+/// between them. Use `is_newer_than()` and `is_no_older_than()` instead, which are only
+/// meaningful for serials generated reasonably close to each other, which is always the
+/// case in practice.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Serial(u32);
+
+impl Serial {
+    /// Check if this serial was generated after given serial
+    ///
+    /// This is the case if this serial is between 1 and 2^31 - 1 steps ahead of `other`,
+    /// wrapping around. Two serials exactly 2^31 steps apart are not newer than each other.
+    pub fn is_newer_than(&self, other: &Serial) -> bool {
+        let distance = self.0.wrapping_sub(other.0);
+        distance != 0 && distance < 1 << 31
+    }
+
+    /// Check if this serial is the same as, or was generated after, given serial
+    ///
+    /// This is the case if this serial is less than 2^31 steps ahead of `other`,
+    /// wrapping around.
+    pub fn is_no_older_than(&self, other: &Serial) -> bool {
+        self.0.wrapping_sub(other.0) < 1 << 31
+    }
+
+    /// The serial following this one
+    pub fn next(self) -> Serial {
+        Serial(self.0.wrapping_add(1))
+    }
+}
+
+impl From<u32> for Serial {
+    fn from(value: u32) -> Serial {
+        Serial(value)
+    }
+}
+
+impl From<Serial> for u32 {
+    fn from(serial: Serial) -> u32 {
+        serial.0
+    }
+}
+
+impl fmt::Display for Serial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::u32::MAX;
+
+    #[test]
+    fn wrapping_comparison() {
+        let a = Serial::from(10);
+        let b = Serial::from(42);
+        assert!(b.is_newer_than(&a));
+        assert!(!a.is_newer_than(&b));
+        assert!(b.is_no_older_than(&a));
+        assert!(a.is_no_older_than(&a));
+        assert!(!a.is_newer_than(&a));
+
+        let old = Serial::from(MAX - 3);
+        let new = Serial::from(2);
+        assert!(new.is_newer_than(&old));
+        assert!(!old.is_no_older_than(&new));
+        assert_eq!(Serial::from(MAX).next(), Serial::from(0));
+        assert_eq!(u32::from(new), 2);
+    }
+
+    #[test]
+    fn half_range_apart() {
+        let a = Serial::from(0);
+        let b = Serial::from(1 << 31);
+        assert!(!a.is_newer_than(&b));
+        assert!(!b.is_newer_than(&a));
+        assert!(!a.is_no_older_than(&b));
+        assert!(!b.is_no_older_than(&a));
+    }
+}
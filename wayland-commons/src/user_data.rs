@@ -14,8 +14,18 @@ pub struct UserData {
 }
 
 enum UserDataInner {
-    ThreadSafe(Box<dyn Any + Send + Sync + 'static>),
-    NonThreadSafe(Box<dyn Any + 'static>, ThreadId),
+    ThreadSafe(Box<dyn Any + Send + Sync + 'static>, Option<&'static str>),
+    NonThreadSafe(Box<dyn Any + 'static>, ThreadId, Option<&'static str>),
+}
+
+#[cfg(has_type_name)]
+fn type_name<T>() -> Option<&'static str> {
+    Some(::std::any::type_name::<T>())
+}
+
+#[cfg(not(has_type_name))]
+fn type_name<T>() -> Option<&'static str> {
+    None
 }
 
 // UserData itself is always threadsafe, as it only gives access to its
@@ -34,8 +44,9 @@ impl UserData {
     /// The provided closure is called to init the UserData,
     /// does nothing is the UserData had already been set.
     pub fn set<T: Any + 'static, F: FnOnce() -> T>(&self, f: F) {
-        self.inner
-            .call_once(|| UserDataInner::NonThreadSafe(Box::new(f()), thread::current().id()));
+        self.inner.call_once(|| {
+            UserDataInner::NonThreadSafe(Box::new(f()), thread::current().id(), type_name::<T>())
+        });
     }
 
     /// Sets the UserData to a given threadsafe value
@@ -43,7 +54,8 @@ impl UserData {
     /// The provided closure is called to init the UserData,
     /// does nothing is the UserData had already been set.
     pub fn set_threadsafe<T: Any + Send + Sync + 'static, F: FnOnce() -> T>(&self, f: F) {
-        self.inner.call_once(|| UserDataInner::ThreadSafe(Box::new(f())));
+        self.inner
+            .call_once(|| UserDataInner::ThreadSafe(Box::new(f()), type_name::<T>()));
     }
 
     /// Attempt to access the wrapped user data
@@ -55,8 +67,8 @@ impl UserData {
     ///   is attempted from an other thread than the one it was created on
     pub fn get<T: 'static>(&self) -> Option<&T> {
         match self.inner.wait() {
-            Some(&UserDataInner::ThreadSafe(ref val)) => Any::downcast_ref::<T>(&**val),
-            Some(&UserDataInner::NonThreadSafe(ref val, threadid)) => {
+            Some(&UserDataInner::ThreadSafe(ref val, _)) => Any::downcast_ref::<T>(&**val),
+            Some(&UserDataInner::NonThreadSafe(ref val, threadid, _)) => {
                 // only give access if we are on the right thread
                 if threadid == thread::current().id() {
                     Any::downcast_ref::<T>(&**val)
@@ -67,6 +79,20 @@ impl UserData {
             None => None,
         }
     }
+
+    /// Retrieve the name of the type stored in this `UserData`
+    ///
+    /// Returns `None` if the `UserData` has not been set yet, or if the crate was
+    /// built with a rust compiler older than 1.38. The name is available even from
+    /// threads that cannot access the value itself.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self.inner.wait() {
+            Some(&UserDataInner::ThreadSafe(_, name)) | Some(&UserDataInner::NonThreadSafe(_, _, name)) => {
+                name
+            }
+            None => None,
+        }
+    }
 }
 
 /// A storage able to store several values of `UserData`
@@ -289,7 +315,7 @@ mod list {
 
 #[cfg(test)]
 mod tests {
-    use super::UserDataMap;
+    use super::{UserData, UserDataMap};
 
     #[test]
     fn insert_twice() {
@@ -300,4 +326,13 @@ mod tests {
         assert!(!map.insert_if_missing(|| 43usize));
         assert_eq!(map.get::<usize>(), Some(&42));
     }
+
+    #[cfg(has_type_name)]
+    #[test]
+    fn type_name() {
+        let data = UserData::new();
+        assert_eq!(data.type_name(), None);
+        data.set_threadsafe(|| 42u32);
+        assert_eq!(data.type_name(), Some("u32"));
+    }
 }
@@ -0,0 +1,77 @@
+//! Aliases of the cursor names
+//!
+//! The themes name their cursors either after the CSS cursor names (as used by the
+//! `cursor-shape` protocol), or after the legacy X11 cursor font names, or both.
+//! This table groups the equivalent names, starting with the CSS one, the others
+//! being ordered by decreasing popularity in the themes. Each name belongs to a single
+//! group, so that a name always resolves to the same kind of cursor.
+
+const ALIASES: &[&[&str]] = &[
+    &["default", "left_ptr", "arrow", "top_left_arrow", "left_arrow"],
+    &["help", "question_arrow", "whats_this", "left_ptr_help"],
+    &["pointer", "hand2", "hand", "pointing_hand"],
+    &["progress", "left_ptr_watch", "half-busy"],
+    &["wait", "watch"],
+    &["cell", "plus"],
+    &["crosshair", "cross", "tcross", "cross_reverse", "diamond_cross"],
+    &["text", "xterm", "ibeam"],
+    &["alias", "dnd-link", "link"],
+    &["copy", "dnd-copy"],
+    &["move", "fleur", "dnd-move"],
+    &["no-drop", "dnd-no-drop", "dnd-none"],
+    &["not-allowed", "crossed_circle", "forbidden", "circle"],
+    &["grab", "openhand", "hand1"],
+    &["grabbing", "closedhand"],
+    &["e-resize", "right_side"],
+    &["n-resize", "top_side"],
+    &["ne-resize", "top_right_corner"],
+    &["nw-resize", "top_left_corner"],
+    &["s-resize", "bottom_side"],
+    &["se-resize", "bottom_right_corner"],
+    &["sw-resize", "bottom_left_corner"],
+    &["w-resize", "left_side"],
+    &["ew-resize", "sb_h_double_arrow", "h_double_arrow", "size_hor"],
+    &["ns-resize", "sb_v_double_arrow", "v_double_arrow", "size_ver"],
+    &["nesw-resize", "fd_double_arrow", "size_bdiag"],
+    &["nwse-resize", "bd_double_arrow", "size_fdiag"],
+    &["col-resize", "split_h"],
+    &["row-resize", "split_v"],
+    &["zoom-in", "zoom_in"],
+    &["zoom-out", "zoom_out"],
+];
+
+/// The names to try, in order, to find the cursor of given name
+///
+/// This is the name itself, followed by its aliases, in the order of the table.
+pub(crate) fn candidates(name: &str) -> Vec<&str> {
+    let mut candidates = vec![name];
+    if let Some(group) = ALIASES.iter().find(|group| group.contains(&name)) {
+        candidates.extend(group.iter().filter(|&&alias| alias != name));
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_group() {
+        for (i, group) in ALIASES.iter().enumerate() {
+            for name in group.iter() {
+                assert!(
+                    ALIASES[i + 1..].iter().all(|other| !other.contains(name)),
+                    "{} belongs to several groups",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn candidates_order() {
+        assert_eq!(candidates("fleur"), vec!["fleur", "move", "dnd-move"]);
+        assert_eq!(candidates("grabbing"), vec!["grabbing", "closedhand"]);
+        assert_eq!(candidates("unknown"), vec!["unknown"]);
+    }
+}
@@ -0,0 +1,244 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use wayland_client::protocol::{
+    wl_callback,
+    wl_compositor::WlCompositor,
+    wl_pointer::{self, WlPointer},
+    wl_surface::WlSurface,
+};
+use wayland_client::{Attached, Main, SendError};
+
+use crate::CursorTheme;
+
+/// The clock driving the animation of an `AnimatedCursor`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Clock {
+    /// The animation is driven by the frame callbacks of the cursor surface
+    ///
+    /// The cursor updates itself when the event queue of its surface is dispatched.
+    FrameCallbacks,
+    /// The animation is driven by calls to `AnimatedCursor::advance()`
+    Manual,
+}
+
+struct Inner {
+    theme: Arc<CursorTheme>,
+    surface: Main<WlSurface>,
+    clock: Clock,
+    scale: u32,
+    name: Option<String>,
+    pointer: Option<(WlPointer, u32)>,
+    // the clock time of the beginning of the animation, once known
+    start: Option<u32>,
+    // the time of the animation currently displayed, and its frame
+    time: u32,
+    frame: Option<usize>,
+    // the hotspot given to the pointer, if the cursor was set since it entered a surface
+    hotspot: Option<(u32, u32)>,
+    callback_pending: bool,
+}
+
+/// A cursor surface, animating a cursor of a theme
+///
+/// It owns a `wl_surface`, displays the frames of its current cursor on it, and sets it as
+/// the cursor image of a `wl_pointer` with `wl_pointer.set_cursor`.
+///
+/// The compositor forgets the cursor image of a pointer each time the pointer enters a
+/// surface: give the `enter` events of the pointer to `process_pointer_event()` to apply the
+/// cursor again.
+///
+/// Animated cursors are driven by a `Clock`: either the frame callbacks of the surface, in which
+/// case the compositor paces the animation, or calls to `advance()`, letting you integrate the
+/// animation in your own event loop.
+pub struct AnimatedCursor {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl AnimatedCursor {
+    /// Create a new cursor surface, displaying the cursors of given theme
+    ///
+    /// Fails if the surface cannot be created, because the compositor is dead.
+    pub fn new(
+        theme: Arc<CursorTheme>,
+        compositor: &Attached<WlCompositor>,
+        clock: Clock,
+    ) -> Result<AnimatedCursor, SendError> {
+        let surface = compositor.create_surface()?;
+        surface.assign_mono(|_, _| {});
+        Ok(AnimatedCursor {
+            inner: Rc::new(RefCell::new(Inner {
+                theme,
+                surface,
+                clock,
+                scale: 1,
+                name: None,
+                pointer: None,
+                start: None,
+                time: 0,
+                frame: None,
+                hotspot: None,
+                callback_pending: false,
+            })),
+        })
+    }
+
+    /// The surface of this cursor
+    pub fn surface(&self) -> WlSurface {
+        (**self.inner.borrow().surface).clone()
+    }
+
+    /// Set the cursor to display
+    ///
+    /// The animation restarts from its first frame.
+    ///
+    /// Returns `false` if the theme does not provide this cursor, in which case the current
+    /// cursor is kept.
+    pub fn set_cursor(&self, name: &str) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        if inner.theme.get_cursor(name).is_none() {
+            return false;
+        }
+        inner.name = Some(name.to_owned());
+        inner.restart();
+        Inner::apply(&self.inner, inner);
+        true
+    }
+
+    /// Set the scale of the output the pointer is on
+    ///
+    /// The images of the cursor are loaded for this scale.
+    pub fn set_scale(&self, scale: u32) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.scale != scale {
+            inner.scale = scale;
+            inner.frame = None;
+            Inner::apply(&self.inner, inner);
+        }
+    }
+
+    /// Set this cursor as the cursor image of a pointer
+    ///
+    /// The `serial` is the one of the last `enter` event of the pointer.
+    pub fn pointer_enter(&self, pointer: &WlPointer, serial: u32) {
+        let mut inner = self.inner.borrow_mut();
+        inner.pointer = Some((pointer.clone(), serial));
+        inner.hotspot = None;
+        inner.restart();
+        Inner::apply(&self.inner, inner);
+    }
+
+    /// Process an event of a pointer
+    ///
+    /// This applies the cursor again each time the pointer enters a surface.
+    pub fn process_pointer_event(&self, pointer: &WlPointer, event: &wl_pointer::Event) {
+        if let wl_pointer::Event::Enter { serial, .. } = *event {
+            self.pointer_enter(pointer, serial);
+        }
+    }
+
+    /// Advance the animation to given time
+    ///
+    /// The time is given in milliseconds, from a clock of your choosing: the animation starts at
+    /// the first time given after the cursor was set or the pointer entered a surface.
+    ///
+    /// Returns the amount of milliseconds after which the next frame should be displayed, or `None`
+    /// if the cursor is not animated. This has no effect if the animation is driven by the frame
+    /// callbacks of the surface.
+    pub fn advance(&self, time: u32) -> Option<u32> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.clock != Clock::Manual {
+            return None;
+        }
+        let start = *inner.start.get_or_insert(time);
+        inner.time = time.wrapping_sub(start);
+        Inner::apply(&self.inner, inner)
+    }
+}
+
+impl Drop for AnimatedCursor {
+    fn drop(&mut self) {
+        let _ = self.inner.borrow().surface.destroy();
+    }
+}
+
+impl Inner {
+    fn restart(&mut self) {
+        self.start = None;
+        self.time = 0;
+        self.frame = None;
+    }
+
+    // display the frame of the current time, returning the remaining duration of this frame
+    // if the cursor is animated
+    fn apply(this: &Rc<RefCell<Inner>>, mut inner: ::std::cell::RefMut<'_, Inner>) -> Option<u32> {
+        let name = inner.name.clone()?;
+        let theme = inner.theme.clone();
+        let cursor = theme.get_cursor(&name)?;
+        // the buffer scale requires version 3 of wl_surface
+        let scale = if inner.surface.as_ref().version() >= 3 {
+            inner.scale
+        } else {
+            1
+        };
+        let frame = cursor.scaled_frame(inner.time, scale);
+        let animated = frame.duration > 0;
+        let hotspot = Some((frame.hotspot_x, frame.hotspot_y));
+
+        let mut commit = false;
+        if inner.frame != Some(frame.frame) {
+            if inner.surface.as_ref().version() >= 3 {
+                let _ = inner.surface.set_buffer_scale(frame.buffer_scale as i32);
+            }
+            let _ = inner.surface.attach(Some(&frame.buffer), 0, 0);
+            let _ = inner.surface.damage(0, 0, ::std::i32::MAX, ::std::i32::MAX);
+            inner.frame = Some(frame.frame);
+            commit = true;
+        }
+        if animated && inner.clock == Clock::FrameCallbacks && !inner.callback_pending {
+            if let Ok(callback) = inner.surface.frame() {
+                let weak = Rc::downgrade(this);
+                callback.assign_mono(move |_, event| Inner::frame_done(&weak, event));
+                inner.callback_pending = true;
+                commit = true;
+            }
+        }
+        if commit {
+            let _ = inner.surface.commit();
+        }
+        // the cursor needs to be set again on each enter, and the hotspot may differ between the frames
+        if inner.hotspot != hotspot {
+            if let Some((ref pointer, serial)) = inner.pointer {
+                // the pointer may have been released
+                let _ = pointer.set_cursor(
+                    serial,
+                    Some(&inner.surface),
+                    frame.hotspot_x as i32,
+                    frame.hotspot_y as i32,
+                );
+                inner.hotspot = hotspot;
+            }
+        }
+
+        if animated {
+            Some(frame.duration)
+        } else {
+            None
+        }
+    }
+
+    fn frame_done(weak: &Weak<RefCell<Inner>>, event: wl_callback::Event) {
+        let this = match weak.upgrade() {
+            Some(this) => this,
+            None => return,
+        };
+        if let wl_callback::Event::Done { callback_data: time } = event {
+            let mut inner = this.borrow_mut();
+            inner.callback_pending = false;
+            let start = *inner.start.get_or_insert(time);
+            inner.time = time.wrapping_sub(start);
+            Inner::apply(&this, inner);
+        }
+    }
+}
@@ -1,9 +1,8 @@
 //! Wayland cursor utilities
 //!
-//! This crate contains bindings to the `libwayland-cursor.so` library.
-//!
-//! These utilities allow you to load cursor images in order to match
-//! your cursors to the ones of the system.
+//! This crate loads cursor images from the XCursor themes of the system, in order to
+//! match your cursors to the ones of the system. It is a pure Rust reimplementation of
+//! `libwayland-cursor`, and as such works with both backends of `wayland-client`.
 //!
 //! First of all, the function `load_theme` will allow you to load a
 //! `CursorTheme`, which represents the full cursor theme.
@@ -13,33 +12,66 @@
 //! means of querying which frame of the animation should be displayed at
 //! what time, as well as handles to the buffers containing these frames, to
 //! attach them to a wayland surface.
+//!
+//! The themes are looked up in the directories listed by the `XCURSOR_PATH`
+//! environment variable, or in the default directories of libXcursor
+//! (`~/.local/share/icons`, `~/.icons`, `/usr/share/icons`, ...) if it is not
+//! set. When a theme does not provide a cursor, it is looked up in the themes
+//! it inherits from (as listed by its `index.theme` file), and then in the
+//! `default` theme. The cursors are also looked up under their aliases, so that
+//! the CSS and X11 names of the cursors can be used regardless of the theme.
 
-use std::ffi::{CStr, CString};
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs::{self, File};
 use std::marker::PhantomData;
 use std::ops::Deref;
-use std::os::raw::c_int;
-use std::ptr;
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
-use wayland_client::{
-    protocol::{wl_buffer::WlBuffer, wl_shm::WlShm},
-    Proxy,
+use wayland_client::protocol::{
+    wl_buffer::WlBuffer,
+    wl_shm::{self, WlShm},
+    wl_shm_pool::WlShmPool,
 };
-use wayland_sys::{cursor::*, ffi_dispatch};
+use wayland_client::Main;
+
+mod aliases;
+mod animated;
+mod theme;
+mod xcursor;
 
-/// Checks if the wayland-cursor library is available and can be used
+pub use animated::{AnimatedCursor, Clock};
+
+use xcursor::Image;
+
+/// Checks if the cursor themes can be loaded
 ///
-/// Trying to call any function of this module if the library cannot
-/// be used will result in a panic.
+/// This crate does not rely on `libwayland-cursor` anymore, so this always
+/// returns `true`. It is kept for compatibility.
 pub fn is_available() -> bool {
-    is_lib_available()
+    true
 }
 
+type CursorCache = HashMap<(String, u32), Option<Arc<CursorData>>>;
+
 /// Represents a cursor theme loaded from the system.
 pub struct CursorTheme {
-    theme: *mut wl_cursor_theme,
+    name: String,
+    size: u32,
+    search_paths: Vec<PathBuf>,
+    pool: Mutex<ThemePool>,
+    // the cursors loaded so far for each scale, `None` for the ones not provided by the theme
+    cursors: Mutex<CursorCache>,
 }
 
+// The pool is only used to create buffers, whose events are ignored, so it does not matter from
+// which thread it is used.
 unsafe impl Send for CursorTheme {}
+unsafe impl Sync for CursorTheme {}
 
 /// Attempts to load a cursor theme.
 ///
@@ -47,107 +79,296 @@ unsafe impl Send for CursorTheme {}
 /// will be loaded.
 ///
 /// Other arguments are the requested size for the cursor images (ex: 16)
-/// and a handle to the global `WlShm` object.
+/// and a handle to the global `WlShm` object. This handle needs to be attached
+/// to an event queue, as the theme uses it to create its buffers.
+///
+/// The `size` is given in surface coordinates: for outputs with a scale factor,
+/// the images are loaded at the corresponding size, see `Cursor::scaled_frame()`.
+///
+/// The cursors are loaded lazily, when requested with `CursorTheme::get_cursor()`.
 ///
 /// # Panics
 ///
-/// - Panics if the `wayland-cursor` lib is not available
-///   (see `is_available()` function) in this module.
-/// - Panics in case of memory allocation failure.
-/// - Panics if `name` contains an interior null.
+/// - Panics in case of memory allocation failure, if the shared memory
+///   of the theme cannot be created, or if `shm` is dead.
 pub fn load_theme(name: Option<&str>, size: u32, shm: &WlShm) -> CursorTheme {
-    let ptr = if let Some(theme) = name {
-        let cstr = CString::new(theme).expect("Theme name contained an interior null.");
-        unsafe {
-            ffi_dispatch!(
-                WAYLAND_CURSOR_HANDLE,
-                wl_cursor_theme_load,
-                cstr.as_ptr(),
-                size as c_int,
-                shm.as_ref().c_ptr()
-            )
-        }
-    } else {
-        unsafe {
-            ffi_dispatch!(
-                WAYLAND_CURSOR_HANDLE,
-                wl_cursor_theme_load,
-                ptr::null(),
-                size as c_int,
-                shm.as_ref().c_ptr()
-            )
-        }
-    };
-
-    assert!(!ptr.is_null(), "Memory allocation failure while loading a theme.");
-
-    CursorTheme { theme: ptr }
+    CursorTheme {
+        name: name.unwrap_or("default").to_owned(),
+        size,
+        search_paths: theme::search_paths(),
+        pool: Mutex::new(ThemePool::new(shm, size)),
+        cursors: Mutex::new(HashMap::new()),
+    }
 }
 
 impl CursorTheme {
     /// Retrieves a cursor from the theme.
     ///
-    /// Returns `None` if this cursor is not provided by the theme.
-    ///
-    /// # Panics
+    /// If the theme does not provide a cursor with this name, its aliases are tried: the
+    /// CSS cursor names (like `pointer`) and their legacy X11 equivalents (like `hand2`)
+    /// can be used interchangeably. The name that was found is given by
+    /// `Cursor::resolved_name()`.
     ///
-    /// Panics if the name contains an interior null.
-    pub fn get_cursor(&self, name: &str) -> Option<Cursor> {
-        let cstr = CString::new(name).expect("Cursor name contained an interior null.");
-        let ptr = unsafe {
-            ffi_dispatch!(
-                WAYLAND_CURSOR_HANDLE,
-                wl_cursor_theme_get_cursor,
-                self.theme,
-                cstr.as_ptr()
-            )
-        };
-        if ptr.is_null() {
-            None
-        } else {
-            Some(Cursor {
-                _theme: PhantomData,
-                cursor: ptr,
+    /// Returns `None` if this cursor is not provided by the theme.
+    pub fn get_cursor(&self, name: &str) -> Option<Cursor<'_>> {
+        self.cursor_data(name, 1).map(|data| Cursor { theme: self, data })
+    }
+
+    // the images of a cursor for given scale, loaded if needed
+    fn cursor_data(&self, name: &str, scale: u32) -> Option<Arc<CursorData>> {
+        let mut cursors = self.cursors.lock().unwrap();
+        let key = (name.to_owned(), scale);
+        if let Some(cursor) = cursors.get(&key) {
+            return cursor.clone();
+        }
+        let cursor = self.load_cursor(name, scale).map(Arc::new);
+        cursors.insert(key, cursor.clone());
+        cursor
+    }
+
+    fn load_cursor(&self, name: &str, scale: u32) -> Option<CursorData> {
+        // prefer an alias provided by the theme to the cursor of the default theme
+        let mut themes = vec![self.name.as_str()];
+        if self.name != "default" {
+            themes.push("default");
+        }
+        let candidates = aliases::candidates(name);
+        let (resolved_name, images) = themes.iter().find_map(|theme| {
+            candidates.iter().find_map(|&candidate| {
+                self.find_images(theme, candidate, scale)
+                    .map(|images| (candidate, images))
+            })
+        })?;
+        let buffer_scale = self.buffer_scale(&images, scale);
+        let mut pool = self.pool.lock().unwrap();
+        let images = images
+            .into_iter()
+            .map(|image| CursorImage {
+                width: image.width,
+                height: image.height,
+                hotspot_x: image.xhot,
+                hotspot_y: image.yhot,
+                delay: image.delay,
+                buffer: pool.upload(&image),
             })
+            .collect();
+        Some(CursorData::new(
+            name.to_owned(),
+            resolved_name.to_owned(),
+            buffer_scale,
+            images,
+        ))
+    }
+
+    // the images of the cursor at the size closest to the requested one
+    fn find_images(&self, theme: &str, name: &str, scale: u32) -> Option<Vec<Image>> {
+        let path = theme::find_cursor(&self.search_paths, theme, name)?;
+        let images = xcursor::parse_xcursor(&fs::read(path).ok()?)?;
+        let size = i64::from(self.size) * i64::from(scale);
+        let best = images
+            .iter()
+            .map(|image| image.size)
+            .min_by_key(|&nominal| (i64::from(nominal) - size).abs())?;
+        Some(images.into_iter().filter(|image| image.size == best).collect())
+    }
+
+    // The buffer scale to display the images at, which may be lower than the requested scale
+    // if the theme does not provide large enough images. It must divide the size of the images.
+    fn buffer_scale(&self, images: &[Image], scale: u32) -> u32 {
+        let nominal = images.first().map(|image| image.size).unwrap_or(0);
+        let ideal = (f64::from(nominal) / f64::from(::std::cmp::max(self.size, 1))).round() as u32;
+        let mut buffer_scale = ::std::cmp::max(::std::cmp::min(ideal, scale), 1);
+        while images
+            .iter()
+            .any(|image| image.width % buffer_scale != 0 || image.height % buffer_scale != 0)
+        {
+            buffer_scale -= 1;
         }
+        buffer_scale
     }
 }
 
 impl Drop for CursorTheme {
     fn drop(&mut self) {
-        unsafe {
-            ffi_dispatch!(WAYLAND_CURSOR_HANDLE, wl_cursor_theme_destroy, self.theme);
+        for cursor in self.cursors.get_mut().unwrap().values().flatten() {
+            for image in &cursor.images {
+                let _ = image.buffer.destroy();
+            }
+        }
+        let _ = self.pool.get_mut().unwrap().pool.destroy();
+    }
+}
+
+// the shared memory holding the images of a theme
+struct ThemePool {
+    file: File,
+    pool: Main<WlShmPool>,
+    size: usize,
+    used: usize,
+}
+
+impl ThemePool {
+    fn new(shm: &WlShm, size: u32) -> ThemePool {
+        let file = create_shm_file().expect("Failed to create the shared memory of the cursor theme.");
+        // enough for one image of the requested size
+        let size = ::std::cmp::max(size as usize * size as usize * 4, 4096);
+        file.set_len(size as u64)
+            .expect("Failed to allocate the shared memory of the cursor theme.");
+        let pool = shm
+            .create_pool(file.as_raw_fd(), size as i32)
+            .expect("Failed to create the pool of the cursor theme.");
+        ThemePool {
+            file,
+            pool,
+            size,
+            used: 0,
+        }
+    }
+
+    fn upload(&mut self, image: &Image) -> WlBuffer {
+        let offset = self.used;
+        self.used += image.pixels.len();
+        if self.used > self.size {
+            self.size = ::std::cmp::max(self.used, self.size * 2);
+            self.file
+                .set_len(self.size as u64)
+                .expect("Failed to allocate the shared memory of the cursor theme.");
+            self.pool
+                .resize(self.size as i32)
+                .expect("Failed to resize the pool of the cursor theme.");
+        }
+        self.file
+            .write_all_at(&image.pixels, offset as u64)
+            .expect("Failed to write in the shared memory of the cursor theme.");
+        let buffer = self
+            .pool
+            .create_buffer(
+                offset as i32,
+                image.width as i32,
+                image.height as i32,
+                image.width as i32 * 4,
+                wl_shm::Format::Argb8888,
+            )
+            .expect("Failed to create the buffer of a cursor image.");
+        // the contents of the buffers never change, their release events are irrelevant
+        buffer.assign_mono(|_, _| {});
+        (**buffer).clone()
+    }
+}
+
+fn create_shm_file() -> ::std::io::Result<File> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+        let name = CStr::from_bytes_with_nul(b"wayland-cursor\0").unwrap();
+        if let Ok(fd) = memfd_create(name, MemFdCreateFlag::MFD_CLOEXEC) {
+            return Ok(unsafe { File::from_raw_fd(fd) });
+        }
+    }
+    // fallback to an unlinked shm file if memfd is not available
+    use nix::fcntl::OFlag;
+    use nix::sys::mman;
+    use nix::sys::stat::Mode;
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = format!(
+        "/wayland-cursor-{}-{}\0",
+        nix::unistd::getpid(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let name = CStr::from_bytes_with_nul(name.as_bytes()).unwrap();
+    match mman::shm_open(
+        name,
+        OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_RDWR | OFlag::O_CLOEXEC,
+        Mode::S_IRUSR | Mode::S_IWUSR,
+    ) {
+        Ok(fd) => {
+            let _ = mman::shm_unlink(name);
+            Ok(unsafe { File::from_raw_fd(fd) })
+        }
+        Err(nix::Error::Sys(errno)) => Err(errno.into()),
+        Err(_) => unreachable!(),
+    }
+}
+
+struct CursorImage {
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+    delay: u32,
+    buffer: WlBuffer,
+}
+
+struct CursorData {
+    name: String,
+    resolved_name: String,
+    buffer_scale: u32,
+    images: Vec<CursorImage>,
+    total_delay: u32,
+}
+
+impl CursorData {
+    fn new(name: String, resolved_name: String, buffer_scale: u32, images: Vec<CursorImage>) -> CursorData {
+        let total_delay = images.iter().map(|image| image.delay).sum();
+        CursorData {
+            name,
+            resolved_name,
+            buffer_scale,
+            images,
+            total_delay,
+        }
+    }
+
+    fn frame_and_duration(&self, duration: u32) -> (usize, u32) {
+        let images = &self.images;
+        if images.len() <= 1 || self.total_delay == 0 {
+            return (0, 0);
+        }
+        let mut time = duration % self.total_delay;
+        let mut frame = 0;
+        // a frame with a null delay is displayed until the animation loops
+        while images[frame].delay != 0 && time >= images[frame].delay {
+            time -= images[frame].delay;
+            frame += 1;
+        }
+        if time >= images[frame].delay {
+            // never report a null duration for an animated cursor
+            (frame, 1)
+        } else {
+            (frame, images[frame].delay - time)
         }
     }
 }
 
 /// A cursor from a theme. Can contain several images if animated.
 pub struct Cursor<'a> {
-    _theme: PhantomData<&'a CursorTheme>,
-    cursor: *mut wl_cursor,
+    theme: &'a CursorTheme,
+    data: Arc<CursorData>,
 }
 
-unsafe impl<'a> Send for Cursor<'a> {}
-
 impl<'a> Cursor<'a> {
     /// Returns the name of this cursor.
     pub fn name(&self) -> String {
-        let name = unsafe { CStr::from_ptr((*self.cursor).name) };
-        name.to_string_lossy().into_owned()
+        self.data.name.clone()
+    }
+
+    /// Returns the name under which the theme provides this cursor.
+    ///
+    /// This differs from `name()` if the cursor was found through one of its aliases.
+    pub fn resolved_name(&self) -> &str {
+        &self.data.resolved_name
     }
 
     /// Returns the number of images contained in this animated cursor
     pub fn image_count(&self) -> usize {
-        let count = unsafe { (*self.cursor).image_count };
-        count as usize
+        self.data.images.len()
     }
 
     /// Returns which frame of an animated cursor should be displayed at a given time.
     ///
     /// The time is given in milliseconds after the beginning of the animation.
     pub fn frame(&self, duration: u32) -> usize {
-        let frame = unsafe { ffi_dispatch!(WAYLAND_CURSOR_HANDLE, wl_cursor_frame, self.cursor, duration) };
-        frame as usize
+        self.frame_and_duration(duration).0
     }
 
     /// Returns the frame number and its remaining duration.
@@ -155,17 +376,44 @@ impl<'a> Cursor<'a> {
     /// Same as `frame()`, but also returns the amount of milliseconds this
     /// frame should continue to be displayed.
     pub fn frame_and_duration(&self, duration: u32) -> (usize, u32) {
-        let mut out_duration = 0u32;
-        let frame = unsafe {
-            ffi_dispatch!(
-                WAYLAND_CURSOR_HANDLE,
-                wl_cursor_frame_and_duration,
-                self.cursor,
-                duration,
-                &mut out_duration as *mut u32
-            )
-        } as usize;
-        (frame, out_duration)
+        self.data.frame_and_duration(duration)
+    }
+
+    /// Returns the frame to display at a given time on an output of given scale.
+    ///
+    /// The time is given in milliseconds after the beginning of the animation.
+    ///
+    /// The images are loaded at the size of the theme multiplied by the scale, or
+    /// the nearest one provided by the theme. The returned frame contains their
+    /// buffer, along with the buffer scale to set on the surface displaying it and
+    /// the hotspot in surface coordinates.
+    ///
+    /// Note that the animation may differ from one scale to another, as the theme
+    /// may provide a different number of images for each size.
+    pub fn scaled_frame(&self, time: u32, scale: u32) -> ScaledFrame<'_> {
+        let data = match scale {
+            0 | 1 => self.data.clone(),
+            scale => self
+                .theme
+                .cursor_data(&self.data.name, scale)
+                .unwrap_or_else(|| self.data.clone()),
+        };
+        let (frame, duration) = data.frame_and_duration(time);
+        let image = &data.images[frame];
+        let scale = data.buffer_scale;
+        ScaledFrame {
+            buffer: CursorImageBuffer {
+                _cursor: PhantomData,
+                buffer: image.buffer.clone(),
+            },
+            buffer_scale: scale,
+            frame,
+            width: image.width / scale,
+            height: image.height / scale,
+            hotspot_x: image.hotspot_x / scale,
+            hotspot_y: image.hotspot_y / scale,
+            duration,
+        }
     }
 
     /// Returns a `CursorImageBuffer` containing the given image of an animation.
@@ -176,21 +424,11 @@ impl<'a> Cursor<'a> {
     ///
     /// Note: destroying this buffer (using the `destroy` method) will corrupt
     /// your theme data, so you might not want to do it.
-    pub fn frame_buffer(&self, frame: usize) -> Option<CursorImageBuffer> {
-        if frame >= self.image_count() {
-            None
-        } else {
-            unsafe {
-                let image = *(*self.cursor).images.add(frame);
-                let ptr = ffi_dispatch!(WAYLAND_CURSOR_HANDLE, wl_cursor_image_get_buffer, image);
-                let buffer = Proxy::from_c_ptr(ptr).into();
-
-                Some(CursorImageBuffer {
-                    _cursor: PhantomData,
-                    buffer,
-                })
-            }
-        }
+    pub fn frame_buffer(&self, frame: usize) -> Option<CursorImageBuffer<'_>> {
+        self.data.images.get(frame).map(|image| CursorImageBuffer {
+            _cursor: PhantomData,
+            buffer: image.buffer.clone(),
+        })
     }
 
     /// Returns the metadata associated with a given frame of the animation.
@@ -199,34 +437,54 @@ impl<'a> Cursor<'a> {
     ///
     /// Returns `None` if the frame is out of bounds.
     pub fn frame_info(&self, frame: usize) -> Option<(u32, u32, u32, u32, u32)> {
-        if frame >= self.image_count() {
-            None
-        } else {
-            let image = unsafe { &**(*self.cursor).images.add(frame) };
-            Some((
+        self.data.images.get(frame).map(|image| {
+            (
                 image.width,
                 image.height,
                 image.hotspot_x,
                 image.hotspot_y,
                 image.delay,
-            ))
-        }
+            )
+        })
     }
 }
 
+/// A frame of a cursor, ready to be displayed on an output of a given scale.
+///
+/// The sizes and hotspot are given in surface coordinates, to be used with
+/// `wl_pointer.set_cursor` once the buffer is attached to a surface whose buffer
+/// scale is `buffer_scale`.
+pub struct ScaledFrame<'a> {
+    /// The buffer containing the image
+    pub buffer: CursorImageBuffer<'a>,
+    /// The scale to set with `wl_surface.set_buffer_scale`
+    pub buffer_scale: u32,
+    /// The index of this frame in the animation
+    pub frame: usize,
+    /// The width of the image
+    pub width: u32,
+    /// The height of the image
+    pub height: u32,
+    /// The horizontal coordinate of the hotspot
+    pub hotspot_x: u32,
+    /// The vertical coordinate of the hotspot
+    pub hotspot_y: u32,
+    /// The amount of milliseconds this frame should continue to be displayed,
+    /// 0 if the cursor is not animated
+    pub duration: u32,
+}
+
 /// A buffer containing a cursor image.
 ///
 /// You can access the `WlBuffer` via `Deref`.
 ///
-/// Note that this proxy will be considered as "unmanaged" by the crate, as such you should
-/// not try to act on it beyond assigning it to `wl_surface`s.
+/// This buffer is owned by its `CursorTheme`, as such you should not try to act on
+/// it beyond assigning it to `wl_surface`s.
 pub struct CursorImageBuffer<'a> {
     _cursor: PhantomData<&'a Cursor<'a>>,
     buffer: WlBuffer,
 }
 
-unsafe impl<'a> Send for CursorImageBuffer<'a> {}
-
 impl<'a> Deref for CursorImageBuffer<'a> {
     type Target = WlBuffer;
     fn deref(&self) -> &WlBuffer {
@@ -0,0 +1,87 @@
+//! Resolution of the cursor files of a theme
+//!
+//! The themes are looked up in the directories listed in `XCURSOR_PATH`, or in the default
+//! directories of libXcursor if it is not set. The cursors of a theme `name` are the files of
+//! the `name/cursors/` subdirectories of these directories. If a cursor is not found there, it
+//! is looked up in the themes listed by the `Inherits=` key of the `name/index.theme` files.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_SEARCH_PATH: &str = "~/.local/share/icons:~/.icons:/usr/share/icons:/usr/share/pixmaps:\
+                                   ~/.cursors:/usr/share/cursors/xorg-x11:/usr/X11R6/lib/X11/icons";
+
+/// The directories the themes are looked up in
+pub(crate) fn search_paths() -> Vec<PathBuf> {
+    let path = env::var("XCURSOR_PATH").unwrap_or_else(|_| DEFAULT_SEARCH_PATH.into());
+    let home = env::var_os("HOME").map(PathBuf::from);
+    path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .filter_map(|dir| {
+            if dir == "~" || dir.starts_with("~/") {
+                // skip the directories relative to the home if it is not known
+                home.as_ref()
+                    .map(|home| home.join(dir[1..].trim_start_matches('/')))
+            } else {
+                Some(PathBuf::from(dir))
+            }
+        })
+        .collect()
+}
+
+/// Find the file of a cursor in a theme or the themes it inherits from
+pub(crate) fn find_cursor(search_paths: &[PathBuf], theme: &str, name: &str) -> Option<PathBuf> {
+    let mut visited = HashSet::new();
+    find_cursor_inner(search_paths, theme, name, &mut visited)
+}
+
+fn find_cursor_inner(
+    search_paths: &[PathBuf],
+    theme: &str,
+    name: &str,
+    visited: &mut HashSet<String>,
+) -> Option<PathBuf> {
+    // guard against inheritance cycles
+    if !visited.insert(theme.to_owned()) {
+        return None;
+    }
+    for dir in search_paths {
+        let path = dir.join(theme).join("cursors").join(name);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    for dir in search_paths {
+        for parent in inherited_themes(&dir.join(theme).join("index.theme")) {
+            if let Some(path) = find_cursor_inner(search_paths, &parent, name, visited) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+/// The themes listed by the `Inherits=` key of an `index.theme` file
+fn inherited_themes(index: &Path) -> Vec<String> {
+    let contents = match fs::read_to_string(index) {
+        Ok(contents) => contents,
+        Err(_) => return Vec::new(),
+    };
+    // like libXcursor, don't bother with the sections of the file
+    for line in contents.lines() {
+        let mut parts = line.splitn(2, '=');
+        if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            if key.trim() == "Inherits" {
+                return value
+                    .split(&[',', ';', ':'][..])
+                    .map(str::trim)
+                    .filter(|theme| !theme.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+            }
+        }
+    }
+    Vec::new()
+}
@@ -0,0 +1,138 @@
+//! Parser for the XCursor file format
+//!
+//! An XCursor file starts with a header and a table of contents, listing the chunks of the
+//! file. Only the image chunks are of interest to us, each of them containing one frame of
+//! the cursor at a given nominal size.
+//!
+//! All integers are stored as little-endian `u32`, and the pixels are premultiplied ARGB, which
+//! is the layout of `wl_shm::Format::Argb8888` in memory.
+
+const XCURSOR_MAGIC: &[u8] = b"Xcur";
+const IMAGE_TYPE: u32 = 0xfffd_0002;
+const FILE_HEADER_LEN: u32 = 16;
+const IMAGE_HEADER_LEN: u32 = 36;
+const TOC_ENTRY_LEN: usize = 12;
+// the limit enforced by libXcursor
+const MAX_IMAGE_SIZE: u32 = 0x7fff;
+
+/// An image of an XCursor file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Image {
+    pub(crate) size: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) xhot: u32,
+    pub(crate) yhot: u32,
+    pub(crate) delay: u32,
+    pub(crate) pixels: Vec<u8>,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn parse_image(data: &[u8], position: usize) -> Option<Image> {
+    let header = |i: usize| read_u32(data, position + 4 * i);
+    if header(0)? != IMAGE_HEADER_LEN || header(1)? != IMAGE_TYPE || header(3)? != 1 {
+        return None;
+    }
+    let image = Image {
+        size: header(2)?,
+        width: header(4)?,
+        height: header(5)?,
+        xhot: header(6)?,
+        yhot: header(7)?,
+        delay: header(8)?,
+        pixels: Vec::new(),
+    };
+    if image.width == 0
+        || image.height == 0
+        || image.width > MAX_IMAGE_SIZE
+        || image.height > MAX_IMAGE_SIZE
+        || image.xhot > image.width
+        || image.yhot > image.height
+    {
+        return None;
+    }
+    let start = position + IMAGE_HEADER_LEN as usize;
+    let len = image.width as usize * image.height as usize * 4;
+    let pixels = data.get(start..start.checked_add(len)?)?.to_vec();
+    Some(Image { pixels, ..image })
+}
+
+/// Parse the images of an XCursor file
+///
+/// Returns `None` if the file is not a valid XCursor file. Its invalid images are ignored.
+pub(crate) fn parse_xcursor(data: &[u8]) -> Option<Vec<Image>> {
+    if !data.starts_with(XCURSOR_MAGIC) {
+        return None;
+    }
+    let header_len = read_u32(data, 4)?;
+    let ntoc = read_u32(data, 12)? as usize;
+    if header_len < FILE_HEADER_LEN {
+        return None;
+    }
+    let toc = data.get(header_len as usize..)?;
+    let mut images = Vec::new();
+    for i in 0..ntoc {
+        let entry = i * TOC_ENTRY_LEN;
+        let (chunk_type, position) = (read_u32(toc, entry)?, read_u32(toc, entry + 8)?);
+        if chunk_type != IMAGE_TYPE {
+            continue;
+        }
+        if let Some(image) = parse_image(data, position as usize) {
+            images.push(image);
+        }
+    }
+    Some(images)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(data: &mut Vec<u8>, values: &[u32]) {
+        for v in values {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn parse_file() {
+        let mut data = b"Xcur".to_vec();
+        push(&mut data, &[16, 0x1_0000, 3]);
+        // toc: a comment, and two images
+        push(
+            &mut data,
+            &[0xfffe_0001, 1, 0, IMAGE_TYPE, 24, 52, IMAGE_TYPE, 24, 92],
+        );
+        assert_eq!(data.len(), 52);
+        push(&mut data, &[36, IMAGE_TYPE, 24, 1, 1, 1, 0, 1, 50, 0xff00_00ff]);
+        push(&mut data, &[36, IMAGE_TYPE, 24, 1, 2, 1, 1, 0, 70]);
+        push(&mut data, &[0xffff_ffff, 0x8000_0000]);
+
+        let images = parse_xcursor(&data).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            images[0],
+            Image {
+                size: 24,
+                width: 1,
+                height: 1,
+                xhot: 0,
+                yhot: 1,
+                delay: 50,
+                pixels: vec![0xff, 0, 0, 0xff],
+            }
+        );
+        assert_eq!((images[1].width, images[1].xhot, images[1].delay), (2, 1, 70));
+        assert_eq!(images[1].pixels.len(), 8);
+
+        // a truncated image is ignored
+        data.truncate(data.len() - 1);
+        assert_eq!(parse_xcursor(&data).unwrap().len(), 1);
+        // not an xcursor file
+        assert!(parse_xcursor(b"Xcux").is_none());
+    }
+}
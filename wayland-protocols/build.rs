@@ -1,7 +1,7 @@
 extern crate wayland_scanner;
 
 use std::env::var;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use wayland_scanner::*;
 
 static STABLE_PROTOCOLS: &[(&str, &[(&str, &str)])] = &[
@@ -40,82 +40,59 @@ static UNSTABLE_PROTOCOLS: &[(&str, &[(&str, &[(&str, &str)])])] = &[
     ("xwayland-keyboard-grab", &[("v1", &[])]),
 ];
 
-static WLR_UNSTABLE_PROTOCOLS: &[(&str, &[(&str, &[(&str, &str)])])] = &[
-    ("wlr-data-control", &[("v1", &[])]),
-    ("wlr-export-dmabuf", &[("v1", &[])]),
-    ("wlr-foreign-toplevel-management", &[("v1", &[])]),
-    ("wlr-gamma-control", &[("v1", &[])]),
-    ("wlr-input-inhibitor", &[("v1", &[])]),
-    ("wlr-layer-shell", &[("v1", &[])]),
-    ("wlr-screencopy", &[("v1", &[])]),
+// the protocols are followed by the name of their module in `wlr::unstable`
+static WLR_UNSTABLE_PROTOCOLS: &[(&str, &str, &[(&str, &[(&str, &str)])])] = &[
+    ("wlr-data-control", "data_control", &[("v1", &[])]),
+    ("wlr-export-dmabuf", "export_dmabuf", &[("v1", &[])]),
+    (
+        "wlr-foreign-toplevel-management",
+        "foreign_toplevel",
+        &[("v1", &[])],
+    ),
+    ("wlr-gamma-control", "gamma_control", &[("v1", &[])]),
+    ("wlr-input-inhibitor", "input_inhibitor", &[("v1", &[])]),
+    ("wlr-layer-shell", "layer_shell", &[("v1", &[])]),
+    ("wlr-screencopy", "screencopy", &[("v1", &[])]),
 ];
 
 static MISC_PROTOCOLS: &[(&str, &[(&str, &str)])] = &[("gtk-primary-selection", &[])];
 
-fn generate_protocol(
-    name: &str,
-    protocol_file: &Path,
-    out_dir: &Path,
-    client: bool,
-    server: bool,
-    dest_events: &[(&str, &str)],
-) {
-    println!("cargo:rerun-if-changed={}", protocol_file.display());
-
-    if client {
-        generate_code_with_destructor_events(
-            &protocol_file,
-            out_dir.join(&format!("{}_client_api.rs", name)),
-            Side::Client,
-            dest_events,
-        );
-    }
-    if server {
-        generate_code_with_destructor_events(
-            &protocol_file,
-            out_dir.join(&format!("{}_server_api.rs", name)),
-            Side::Server,
-            dest_events,
-        );
-    }
+// A protocol file, along with the name of its generated files and its module
+struct ProtocolFile {
+    name: String,
+    file: PathBuf,
+    module: String,
+    dest_events: &'static [(&'static str, &'static str)],
 }
 
-fn main() {
-    println!("cargo:rerun-if-changed-env=CARGO_FEATURE_CLIENT");
-    println!("cargo:rerun-if-changed-env=CARGO_FEATURE_SERVER");
-    println!("cargo:rerun-if-changed-env=CARGO_FEATURE_UNSTABLE_PROTOCOLS");
-
-    let out_dir_str = var("OUT_DIR").unwrap();
-    let out_dir = Path::new(&out_dir_str);
+fn module_name(name: &str) -> String {
+    name.replace('-', "_")
+}
 
-    let client = var("CARGO_FEATURE_CLIENT").ok().is_some();
-    let server = var("CARGO_FEATURE_SERVER").ok().is_some();
+fn protocol_files(unstable: bool) -> Vec<ProtocolFile> {
+    // the stable protocols come first, so that their interfaces take precedence over the
+    // ones of the same name in older unstable versions
+    let mut files = Vec::new();
 
     for &(name, dest_events) in STABLE_PROTOCOLS {
-        let file = format!("{name}/{name}.xml", name = name);
-        generate_protocol(
-            name,
-            &Path::new("./protocols/stable").join(&file),
-            out_dir,
-            client,
-            server,
+        files.push(ProtocolFile {
+            name: name.into(),
+            file: Path::new("./protocols/stable").join(format!("{name}/{name}.xml", name = name)),
+            module: format!("crate::{}", module_name(name)),
             dest_events,
-        );
+        });
     }
 
     for &(name, dest_events) in MISC_PROTOCOLS {
-        let file = format!("{name}.xml", name = name);
-        generate_protocol(
-            name,
-            &Path::new("./misc").join(&file),
-            out_dir,
-            client,
-            server,
+        files.push(ProtocolFile {
+            name: name.into(),
+            file: Path::new("./misc").join(format!("{name}.xml", name = name)),
+            module: format!("crate::misc::{}", module_name(name)),
             dest_events,
-        );
+        });
     }
 
-    if var("CARGO_FEATURE_UNSTABLE_PROTOCOLS").ok().is_some() {
+    if unstable {
         for &(name, versions) in UNSTABLE_PROTOCOLS {
             for &(version, dest_events) in versions {
                 let file = format!(
@@ -123,28 +100,86 @@ fn main() {
                     name = name,
                     version = version
                 );
-                generate_protocol(
-                    &format!("{name}-{version}", name = name, version = version),
-                    &Path::new("./protocols/unstable").join(file),
-                    out_dir,
-                    client,
-                    server,
+                files.push(ProtocolFile {
+                    name: format!("{name}-{version}", name = name, version = version),
+                    file: Path::new("./protocols/unstable").join(file),
+                    module: format!("crate::unstable::{}::{}", module_name(name), version),
                     dest_events,
-                );
+                });
             }
         }
-        for &(name, versions) in WLR_UNSTABLE_PROTOCOLS {
+        for &(name, module, versions) in WLR_UNSTABLE_PROTOCOLS {
             for &(version, dest_events) in versions {
                 let file = format!("{name}-unstable-{version}.xml", name = name, version = version);
-                generate_protocol(
-                    &format!("{name}-{version}", name = name, version = version),
-                    &Path::new("./wlr-protocols/unstable").join(file),
-                    out_dir,
-                    client,
-                    server,
+                files.push(ProtocolFile {
+                    name: format!("{name}-{version}", name = name, version = version),
+                    file: Path::new("./wlr-protocols/unstable").join(file),
+                    module: format!("crate::wlr::unstable::{}::{}", module, version),
                     dest_events,
-                );
+                });
             }
         }
     }
+
+    files
+}
+
+fn interface_paths(files: &[ProtocolFile], side: Side) -> InterfacePaths {
+    let submodule = match side {
+        Side::Client => "client",
+        Side::Server => "server",
+    };
+    let mut paths = InterfacePaths::core(side);
+    for protocol in files {
+        let module = format!("{}::{}", protocol.module, submodule);
+        if let Err(e) = paths.insert_protocol(&protocol.file, &module) {
+            panic!("{}", e);
+        }
+    }
+    paths
+}
+
+fn generate_protocol(protocol: &ProtocolFile, out_dir: &Path, side: Side, paths: &InterfacePaths) {
+    let suffix = match side {
+        Side::Client => "client",
+        Side::Server => "server",
+    };
+    let target = out_dir.join(format!("{}_{}_api.rs", protocol.name, suffix));
+    let result = Scanner::new(&protocol.file, side)
+        .destructor_events(protocol.dest_events)
+        .interface_paths(paths.clone())
+        .handlers(true)
+        .output(target)
+        .generate();
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed-env=CARGO_FEATURE_CLIENT");
+    println!("cargo:rerun-if-changed-env=CARGO_FEATURE_SERVER");
+    println!("cargo:rerun-if-changed-env=CARGO_FEATURE_UNSTABLE_PROTOCOLS");
+
+    let out_dir_str = var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir_str);
+
+    let client = var("CARGO_FEATURE_CLIENT").ok().is_some();
+    let server = var("CARGO_FEATURE_SERVER").ok().is_some();
+    let unstable = var("CARGO_FEATURE_UNSTABLE_PROTOCOLS").ok().is_some();
+
+    let files = protocol_files(unstable);
+    for protocol in &files {
+        println!("cargo:rerun-if-changed={}", protocol.file.display());
+    }
+
+    for &(enabled, side) in &[(client, Side::Client), (server, Side::Server)] {
+        if !enabled {
+            continue;
+        }
+        let paths = interface_paths(&files, side);
+        for protocol in &files {
+            generate_protocol(protocol, out_dir, side, &paths);
+        }
+    }
 }
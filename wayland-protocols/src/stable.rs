@@ -5,14 +5,7 @@ pub mod presentation_time {
     //!
     //! Allows precise feedback on presentation timing, for example for smooth video playback.
 
-    wayland_protocol!(
-        "presentation-time",
-        [
-            (wl_surface, wl_surface_interface),
-            (wl_output, wl_output_interface)
-        ],
-        []
-    );
+    wayland_protocol!("presentation-time");
 }
 
 pub mod xdg_shell {
@@ -20,15 +13,7 @@ pub mod xdg_shell {
     //!
     //! Exposes the `xdg_wm_base` global, which deprecates and replaces `wl_shell`.
 
-    wayland_protocol!(
-        "xdg-shell",
-        [
-            (wl_seat, wl_seat_interface),
-            (wl_surface, wl_surface_interface),
-            (wl_output, wl_output_interface)
-        ],
-        []
-    );
+    wayland_protocol!("xdg-shell");
 }
 
 pub mod viewporter {
@@ -37,5 +22,5 @@ pub mod viewporter {
     //! Provides the capability of scaling and cropping surfaces, decorrelating the surface
     //! dimensions from the size of the buffer.
 
-    wayland_protocol!("viewporter", [(wl_surface, wl_surface_interface)], []);
+    wayland_protocol!("viewporter");
 }
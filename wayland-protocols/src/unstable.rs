@@ -19,57 +19,26 @@
 pub mod fullscreen_shell {
     //! Fullscreen shell protocol
 
-    wayland_protocol_versioned!(
-        "fullscreen-shell",
-        [v1],
-        [
-            (wl_surface, wl_surface_interface),
-            (wl_output, wl_output_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("fullscreen-shell", [v1]);
 }
 
 pub mod idle_inhibit {
     //! Screensaver inhibition protocol
 
-    wayland_protocol_versioned!(
-        "idle-inhibit",
-        [v1],
-        [(wl_surface, wl_surface_interface)],
-        []
-    );
+    wayland_protocol_versioned!("idle-inhibit", [v1]);
 }
 
 
 pub mod input_method {
     //! Input method protocol
 
-    wayland_protocol_versioned!(
-        "input-method",
-        [v1],
-        [
-            (wl_surface, wl_surface_interface),
-            (wl_output, wl_output_interface),
-            (wl_keyboard, wl_keyboard_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("input-method", [v1]);
 }
 
 pub mod input_timestamps {
     //! Input timestamps protocol
 
-    wayland_protocol_versioned!(
-        "input-timestamps",
-        [v1],
-        [
-            (wl_keyboard, wl_keyboard_interface),
-            (wl_pointer, wl_pointer_interface),
-            (wl_touch, wl_touch_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("input-timestamps", [v1]);
 }
 
 pub mod keyboard_shortcuts_inhibit {
@@ -79,39 +48,19 @@ pub mod keyboard_shortcuts_inhibit {
     //! to ignore its own keyboard shortcuts for a given seat, so that all
     //! key events from that seat get forwarded to a surface.
 
-    wayland_protocol_versioned!(
-        "keyboard-shortcuts-inhibit",
-        [v1],
-        [
-            (wl_seat, wl_seat_interface),
-            (wl_surface, wl_surface_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("keyboard-shortcuts-inhibit", [v1]);
 }
 
 pub mod linux_dmabuf {
     //! Linux DMA-BUF protocol
 
-    wayland_protocol_versioned!(
-        "linux-dmabuf",
-        [v1],
-        [(wl_buffer, wl_buffer_interface)],
-        []
-    );
+    wayland_protocol_versioned!("linux-dmabuf", [v1]);
 }
 
 pub mod linux_explicit_synchronization {
     //! Linux explicit synchronization protocol
 
-    wayland_protocol_versioned!(
-        "linux-explicit-synchronization",
-        [v1],
-        [
-            (wl_surface, wl_surface_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("linux-explicit-synchronization", [v1]);
 }
 
 pub mod pointer_constraints {
@@ -127,43 +76,19 @@ pub mod pointer_constraints {
     //! client uses the request that corresponds to the type of constraint it wants
     //! to make. See wp_pointer_constraints for more details.
 
-    wayland_protocol_versioned!(
-        "pointer-constraints",
-        [v1],
-        [
-            (wl_surface, wl_surface_interface),
-            (wl_pointer, wl_pointer_interface),
-            (wl_region, wl_region_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("pointer-constraints", [v1]);
 }
 
 pub mod pointer_gestures {
     //! Pointer gestures protocol
 
-    wayland_protocol_versioned!(
-        "pointer-gestures",
-        [v1],
-        [
-            (wl_surface, wl_surface_interface),
-            (wl_pointer, wl_pointer_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("pointer-gestures", [v1]);
 }
 
 pub mod primary_selection {
     //! Primary selection protocol
 
-    wayland_protocol_versioned!(
-        "primary-selection",
-        [v1],
-        [
-            (wl_seat, wl_seat_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("primary-selection", [v1]);
 }
 
 pub mod relative_pointer {
@@ -183,12 +108,7 @@ pub mod relative_pointer {
     //! the newly created relative pointer object. See the documentation of the
     //! relative pointer interface for more details.
 
-    wayland_protocol_versioned!(
-        "relative-pointer",
-        [v1],
-        [(wl_pointer, wl_pointer_interface)],
-        []
-    );
+    wayland_protocol_versioned!("relative-pointer", [v1]);
 }
 
 pub mod tablet {
@@ -271,29 +191,13 @@ pub mod tablet {
     //! will likely include some form of removing a tool when all tablets the
     //! tool was used on are removed.
 
-    wayland_protocol_versioned!(
-        "tablet",
-        [v1, v2],
-        [
-            (wl_seat, wl_seat_interface),
-            (wl_surface, wl_surface_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("tablet", [v1, v2]);
 }
 
 pub mod text_input {
     //! Text input protocol
 
-    wayland_protocol_versioned!(
-        "text-input",
-        [v1, v3],
-        [
-            (wl_seat, wl_seat_interface),
-            (wl_surface, wl_surface_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("text-input", [v1, v3]);
 }
 
 pub mod xdg_decoration {
@@ -311,14 +215,7 @@ pub mod xdg_decoration {
     //! decoration using this protocol, clients continue to self-decorate as they
     //! see fit.
 
-    wayland_protocol_versioned!(
-        "xdg-decoration",
-        [v1],
-        [],
-        [
-            (xdg_shell, xdg_toplevel, xdg_toplevel_interface)
-        ]
-    );
+    wayland_protocol_versioned!("xdg-decoration", [v1]);
 }
 
 pub mod xdg_foreign {
@@ -345,12 +242,7 @@ pub mod xdg_foreign {
     //! can show a file browser dialog and stack it above the sandboxed client's
     //! surface.
 
-    wayland_protocol_versioned!(
-        "xdg-foreign",
-        [v1, v2],
-        [(wl_surface, wl_surface_interface)],
-        []
-    );
+    wayland_protocol_versioned!("xdg-foreign", [v1, v2]);
 }
 
 pub mod xdg_output {
@@ -373,12 +265,7 @@ pub mod xdg_output {
     //! concepts (such as output location within the global compositor space,
     //! the connector name and types, etc.) out of the core wl_output protocol.
 
-    wayland_protocol_versioned!(
-        "xdg-output",
-        [v1],
-        [(wl_output, wl_output_interface)],
-        []
-    );
+    wayland_protocol_versioned!("xdg-output", [v1]);
 }
 
 pub mod xdg_shell {
@@ -389,16 +276,7 @@ pub mod xdg_shell {
     //! They remain here for compatibility reasons, allowing you to support older
     //! clients/server not yet implementing the new protocol.
 
-    wayland_protocol_versioned!(
-        "xdg-shell",
-        [v5, v6],
-        [
-            (wl_surface, wl_surface_interface),
-            (wl_output, wl_output_interface),
-            (wl_seat, wl_seat_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("xdg-shell", [v5, v6]);
 }
 
 pub mod xwayland_keyboard_grab {
@@ -431,13 +309,5 @@ pub mod xwayland_keyboard_grab {
     //! Compositors are required to restrict access to this application
     //! specific protocol to Xwayland alone.
 
-    wayland_protocol_versioned!(
-        "xwayland-keyboard-grab",
-        [v1],
-        [
-            (wl_seat, wl_seat_interface),
-            (wl_surface, wl_surface_interface)
-        ],
-        []
-    );
+    wayland_protocol_versioned!("xwayland-keyboard-grab", [v1]);
 }
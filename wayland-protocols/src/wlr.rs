@@ -27,14 +27,7 @@ pub mod unstable {
         //! An interface to control data devices, particularly to manage the current selection and
         //! take the role of a clipboard manager.
 
-        wayland_protocol_versioned!(
-            "wlr-data-control",
-            [v1],
-            [
-                (wl_seat, wl_seat_interface)
-            ],
-            []
-        );
+        wayland_protocol_versioned!("wlr-data-control", [v1]);
     }
 
     pub mod export_dmabuf {
@@ -42,14 +35,7 @@ pub mod unstable {
         //!
         //! An interface to capture surfaces in an efficient way by exporting DMA-BUFs.
 
-        wayland_protocol_versioned!(
-            "wlr-export-dmabuf",
-            [v1],
-            [
-                (wl_output, wl_output_interface)
-            ],
-            []
-        );
+        wayland_protocol_versioned!("wlr-export-dmabuf", [v1]);
     }
 
     pub mod foreign_toplevel {
@@ -57,16 +43,7 @@ pub mod unstable {
         //!
         //! Use for creating taskbars and docks.
 
-        wayland_protocol_versioned!(
-            "wlr-foreign-toplevel-management",
-            [v1],
-            [
-                (wl_seat, wl_seat_interface),
-                (wl_surface, wl_surface_interface),
-                (wl_output, wl_output_interface)
-            ],
-            []
-        );
+        wayland_protocol_versioned!("wlr-foreign-toplevel-management", [v1]);
     }
 
     pub mod gamma_control {
@@ -74,41 +51,19 @@ pub mod unstable {
         //!
         //! This protocol allows a privileged client to set the gamma tables for outputs.
 
-        wayland_protocol_versioned!(
-            "wlr-gamma-control",
-            [v1],
-            [
-                (wl_output, wl_output_interface)
-            ],
-            []
-        );
+        wayland_protocol_versioned!("wlr-gamma-control", [v1]);
     }
 
     pub mod input_inhibitor {
         //! Inhibits input events to other clients
 
-        wayland_protocol_versioned!(
-            "wlr-input-inhibitor",
-            [v1],
-            [],
-            []
-        );
+        wayland_protocol_versioned!("wlr-input-inhibitor", [v1]);
     }
 
     pub mod layer_shell {
         //! Layered shell protocol
 
-        wayland_protocol_versioned!(
-            "wlr-layer-shell",
-            [v1],
-            [
-                (wl_output, wl_output_interface),
-                (wl_surface, wl_surface_interface)
-            ],
-            [
-                (xdg_shell, xdg_popup, xdg_popup_interface)
-            ]
-        );
+        wayland_protocol_versioned!("wlr-layer-shell", [v1]);
     }
 
     pub mod screencopy {
@@ -117,14 +72,6 @@ pub mod unstable {
         //! This protocol allows clients to ask the compositor to copy part of the
         //! screen content to a client buffer.
 
-        wayland_protocol_versioned!(
-            "wlr-screencopy",
-            [v1],
-            [
-                (wl_buffer, wl_buffer_interface),
-                (wl_output, wl_output_interface)
-            ],
-            []
-        );
+        wayland_protocol_versioned!("wlr-screencopy", [v1]);
     }
 }
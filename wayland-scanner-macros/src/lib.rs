@@ -0,0 +1,236 @@
+//! Procedural macro front-end of `wayland-scanner`
+//!
+//! This crate provides the `wayland_protocol!` macro, generating the rust API of a
+//! wayland protocol XML file inline, as an alternative to running `wayland-scanner`
+//! in a build script and including its output.
+//!
+//! ```ignore
+//! extern crate wayland_client;
+//! extern crate wayland_scanner_macros;
+//!
+//! pub mod my_protocol {
+//!     // If your protocol interacts with objects from other protocols, import their
+//!     // modules next to the macro invocation
+//!     pub(crate) use wayland_client::protocol::{wl_region, wl_surface};
+//!
+//!     wayland_scanner_macros::wayland_protocol!("./my_protocol.xml", client);
+//! }
+//!
+//! // The interfaces of the protocol are then available like those of `wayland_client::protocol`
+//! use my_protocol::my_interface::MyInterface;
+//! ```
+//!
+//! The path of the XML file is relative to the directory containing the `Cargo.toml` of the
+//! crate invoking the macro, and the crate is rebuilt whenever this file changes. The second
+//! argument is the side to generate the code for, either `client` or `server`, the generated
+//! code depending on the `wayland-client` or `wayland-server` crate respectively.
+//!
+//! Like the code included from a build script, the generated code depends on the
+//! `wayland-commons` and `bitflags` crates, in addition to `wayland-client` or `wayland-server`.
+//!
+//! The generated code is the same as the one `wayland_scanner::generate_code` writes: the
+//! module of each interface of the protocol is added to the module invoking the macro.
+//!
+//! An optional third argument, `rust_only`, skips the generation of the C interfaces, for
+//! code that is only used with the rust implementation of the protocol (see
+//! `wayland_scanner::Mode::RustOnly`):
+//!
+//! ```ignore
+//! wayland_scanner_macros::wayland_protocol!("./my_protocol.xml", client, rust_only);
+//! ```
+
+#![warn(missing_docs)]
+
+extern crate proc_macro;
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use proc_macro2::{Ident, Literal, Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned};
+
+use wayland_scanner::{generate_code_tokens, Mode, Side};
+
+/// Generate the API of a wayland protocol
+///
+/// Takes the path of the XML file of the protocol, relative to the `Cargo.toml` of the crate,
+/// the side to generate the code for: `client` or `server`, and optionally `rust_only`.
+///
+/// See the crate documentation for details.
+#[proc_macro]
+pub fn wayland_protocol(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match expand(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err((span, msg)) => quote_spanned!(span=> compile_error!(#msg);).into(),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, (Span, String)> {
+    let (literal, side, mode) = parse_args(input)?;
+    let span = literal.span();
+    let relative = unquote(&literal.to_string()).ok_or_else(|| {
+        (
+            span,
+            "Expected the path of the protocol file as a string literal.".into(),
+        )
+    })?;
+
+    let mut path = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    path.push(&relative);
+    let file = File::open(&path).map_err(|e| {
+        (
+            span,
+            format!("Unable to open protocol file `{}`: {}", path.display(), e),
+        )
+    })?;
+    // report all the problems of the protocol, with their position in the file
+    let code = generate_code_tokens(file, side, mode).map_err(|e| {
+        let messages = e
+            .diagnostics
+            .iter()
+            .map(|d| format!("{}:{}:{}: {}", relative, d.line, d.column, d.message))
+            .collect::<Vec<_>>();
+        (span, format!("Invalid protocol file:\n{}", messages.join("\n")))
+    })?;
+
+    // the generated code is isolated in a permissive module, whose public items are re-exported
+    let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let stem: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let module = Ident::new(&format!("__wayland_protocol_{}", stem), Span::call_site());
+    let imports = match side {
+        Side::Client => quote! {
+            use wayland_client::{Main, Attached, Proxy, ProxyMap, SendError, AnonymousObject};
+        },
+        Side::Server => quote! {
+            use wayland_server::{Main, AnonymousObject, Resource, ResourceMap};
+        },
+    };
+    let sys = match (side, mode) {
+        (_, Mode::RustOnly) => None,
+        (Side::Client, Mode::Full) => Some(quote!(
+            use wayland_client::sys;
+        )),
+        (Side::Server, Mode::Full) => Some(quote!(
+            use wayland_server::sys;
+        )),
+    };
+    // depending on the contents of the file makes cargo rebuild the crate when it changes
+    let path = Literal::string(&path.to_string_lossy());
+
+    Ok(quote! {
+        pub use self::#module::*;
+
+        #[allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+        #[allow(non_upper_case_globals, non_snake_case, unused_imports)]
+        #[allow(missing_docs, clippy::all)]
+        mod #module {
+            use super::*;
+            #imports
+            #sys
+            use wayland_commons::map::{Object, ObjectMetadata};
+            use wayland_commons::{Interface, MessageGroup};
+            use wayland_commons::wire::{Argument, MessageDesc, ArgumentType, Message};
+            use wayland_commons::smallvec;
+
+            // the modules of the interfaces invoke `bitflags!` unqualified
+            macro_rules! bitflags {
+                ($($tokens:tt)*) => { ::bitflags::bitflags! { $($tokens)* } };
+            }
+
+            const PROTOCOL_FILE: &[u8] = include_bytes!(#path);
+
+            #code
+        }
+    })
+}
+
+// the arguments of the macro: a string literal, a side and an optional mode, separated by commas
+fn parse_args(input: TokenStream) -> Result<(Literal, Side, Mode), (Span, String)> {
+    let mut tokens = input.into_iter();
+    let literal = match tokens.next() {
+        Some(TokenTree::Literal(literal)) => literal,
+        Some(other) => return Err((other.span(), "Expected the path of the protocol file.".into())),
+        None => {
+            return Err((
+                Span::call_site(),
+                "Expected the path of the protocol file.".into(),
+            ))
+        }
+    };
+    match tokens.next() {
+        Some(TokenTree::Punct(ref punct)) if punct.as_char() == ',' => {}
+        Some(other) => return Err((other.span(), "Expected `,`.".into())),
+        None => {
+            return Err((
+                Span::call_site(),
+                "Expected the side to generate the code for.".into(),
+            ))
+        }
+    }
+    let side = match tokens.next() {
+        Some(TokenTree::Ident(ref ident)) if ident == "client" => Side::Client,
+        Some(TokenTree::Ident(ref ident)) if ident == "server" => Side::Server,
+        Some(other) => return Err((other.span(), "Expected `client` or `server`.".into())),
+        None => {
+            return Err((
+                Span::call_site(),
+                "Expected the side to generate the code for.".into(),
+            ))
+        }
+    };
+    let mut mode = Mode::Full;
+    match tokens.next() {
+        None => {}
+        Some(TokenTree::Punct(ref punct)) if punct.as_char() == ',' => match tokens.next() {
+            None => {}
+            Some(TokenTree::Ident(ref ident)) if ident == "rust_only" => mode = Mode::RustOnly,
+            Some(other) => return Err((other.span(), "Expected `rust_only`.".into())),
+        },
+        Some(other) => return Err((other.span(), "Unexpected token.".into())),
+    }
+    if mode == Mode::RustOnly {
+        match tokens.next() {
+            None => {}
+            Some(TokenTree::Punct(ref punct)) if punct.as_char() == ',' => {}
+            Some(other) => return Err((other.span(), "Unexpected token.".into())),
+        }
+    }
+    if let Some(other) = tokens.next() {
+        return Err((other.span(), "Unexpected token.".into()));
+    }
+    Ok((literal, side, mode))
+}
+
+// the value of a string literal, as written in the source
+fn unquote(literal: &str) -> Option<String> {
+    let mut chars = literal.chars();
+    if chars.next() == Some('r') {
+        let hashes = chars.as_str().trim_start_matches('#');
+        let count = literal.len() - 1 - hashes.len();
+        let inner = hashes.get(1..hashes.len().checked_sub(count + 1)?)?;
+        return Some(inner.to_owned());
+    }
+    if literal.len() < 2 || !literal.starts_with('"') || !literal.ends_with('"') {
+        return None;
+    }
+    let mut value = String::new();
+    let mut chars = literal[1..literal.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => value.push('\\'),
+            '"' => value.push('"'),
+            '\'' => value.push('\''),
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            _ => return None,
+        }
+    }
+    Some(value)
+}
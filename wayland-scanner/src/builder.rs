@@ -0,0 +1,347 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use proc_macro2::TokenStream;
+
+use crate::protocol::*;
+use crate::{resolve, Error, InterfacePaths, Side};
+
+/// The backends supported by the generated code
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Mode {
+    /// Support both the rust implementation of the protocol and the C system library
+    ///
+    /// This is what the `use_system_lib` feature of `wayland-client` and `wayland-server`
+    /// requires.
+    Full,
+    /// Only support the rust implementation of the protocol
+    ///
+    /// The C interfaces of the protocol and the conversions of its messages to and from
+    /// their C representation are not generated, so the code does not depend on `wayland-sys`.
+    /// Using it with the `use_system_lib` feature panics.
+    RustOnly,
+}
+
+impl Default for Mode {
+    fn default() -> Mode {
+        Mode::Full
+    }
+}
+
+/// The documentation included in the generated code
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Docs {
+    /// The summaries and descriptions of the protocol file
+    Full,
+    /// Only the one-line summaries of the protocol file
+    Summary,
+    /// No documentation from the protocol file
+    None,
+}
+
+impl Default for Docs {
+    fn default() -> Docs {
+        Docs::Full
+    }
+}
+
+enum Source<'a> {
+    File(PathBuf),
+    Stream(Box<dyn Read + 'a>),
+}
+
+enum Target<'a> {
+    File(PathBuf),
+    Stream(Box<dyn Write + 'a>),
+}
+
+/// A configurable code generator
+///
+/// Generates the code for a protocol, like the `generate_code*` functions, with all the
+/// options of the scanner:
+///
+/// ```no_run
+/// # extern crate wayland_scanner;
+/// # use wayland_scanner::{Docs, Mode, Scanner, Side};
+/// # fn main() {
+/// Scanner::new("./my_protocol.xml", Side::Client)
+///     .destructor_events(&[("my_callback", "done")])
+///     .mode(Mode::RustOnly)
+///     .docs(Docs::Summary)
+///     .output("./my_protocol_api.rs")
+///     .generate()
+///     .unwrap();
+/// # }
+/// ```
+pub struct Scanner<'a> {
+    source: Source<'a>,
+    side: Side,
+    events: Vec<(String, String)>,
+    target: Option<Target<'a>>,
+    docs: Docs,
+    paths: Option<InterfacePaths>,
+    mode: Mode,
+    handlers: bool,
+}
+
+impl<'a> Scanner<'a> {
+    /// Generate the code for a protocol file
+    ///
+    /// The path is absolute, or relative to the build script using the scanner.
+    pub fn new<P: AsRef<Path>>(protocol: P, side: Side) -> Scanner<'a> {
+        Scanner::with_source(Source::File(protocol.as_ref().to_owned()), side)
+    }
+
+    /// Generate the code for a protocol read from an IO stream
+    pub fn from_stream<R: Read + 'a>(protocol: R, side: Side) -> Scanner<'a> {
+        Scanner::with_source(Source::Stream(Box::new(protocol)), side)
+    }
+
+    fn with_source(source: Source<'a>, side: Side) -> Scanner<'a> {
+        Scanner {
+            source,
+            side,
+            events: Vec::new(),
+            target: None,
+            docs: Docs::default(),
+            paths: None,
+            mode: Mode::default(),
+            handlers: false,
+        }
+    }
+
+    /// Mark some events as destructors
+    ///
+    /// The events are given in the format `("interface_name", "event_name")`, as this
+    /// information is not encoded in the protocol files.
+    pub fn destructor_events(mut self, events: &[(&str, &str)]) -> Self {
+        self.events
+            .extend(events.iter().map(|&(i, e)| (i.to_owned(), e.to_owned())));
+        self
+    }
+
+    /// Write the generated code to a file
+    ///
+    /// The file is formatted with `rustfmt` if it is available.
+    pub fn output<P: AsRef<Path>>(mut self, target: P) -> Self {
+        self.target = Some(Target::File(target.as_ref().to_owned()));
+        self
+    }
+
+    /// Write the generated code to an IO stream
+    pub fn output_stream<W: Write + 'a>(mut self, target: W) -> Self {
+        self.target = Some(Target::Stream(Box::new(target)));
+        self
+    }
+
+    /// Set the documentation included in the generated code
+    ///
+    /// Defaults to `Docs::Full`.
+    pub fn docs(mut self, docs: Docs) -> Self {
+        self.docs = docs;
+        self
+    }
+
+    /// Import the interfaces of other protocols from the given paths
+    ///
+    /// Without them, the modules of these interfaces are expected in the module including
+    /// the generated code. With them, the generated code imports these modules itself, and
+    /// the interfaces whose path is unknown are reported as errors.
+    pub fn interface_paths(mut self, paths: InterfacePaths) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    /// Set the backends supported by the generated code
+    ///
+    /// Defaults to `Mode::Full`.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Generate a handler trait for each interface
+    ///
+    /// The trait has one method per message received by the objects of the interface, and
+    /// each method has a default implementation: the events are ignored, the destructor
+    /// requests do nothing and the other requests post a protocol error. An implementor
+    /// can then be assigned to an object with `Main::assign_handler`, and the messages added
+    /// by new versions of the protocol do not break it.
+    ///
+    /// The trait is named `EventHandler` client-side and `RequestHandler` server-side, and
+    /// the generated code expects the `HandledBy` trait of `wayland-client` or
+    /// `wayland-server` in the module including it. Defaults to `false`.
+    pub fn handlers(mut self, handlers: bool) -> Self {
+        self.handlers = handlers;
+        self
+    }
+
+    /// Generate the code and write it to the output
+    pub fn generate(mut self) -> Result<(), Error> {
+        let target = match self.target.take() {
+            Some(target) => target,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "no output was set for the generated code",
+                )
+                .into())
+            }
+        };
+        let code = self.generate_tokens()?;
+        match target {
+            Target::File(path) => write_file(&path, &code)?,
+            Target::Stream(mut out) => write!(out, "{}", code)?,
+        }
+        Ok(())
+    }
+
+    /// Check that the output file contains the generated code
+    ///
+    /// Generates the code as `generate` would, and compares it to the current contents of
+    /// the output file rather than writing it. Returns `false` if the file is missing or
+    /// outdated. This is intended for generated code checked into a repository.
+    ///
+    /// The output must be a file, and rustfmt must be available to format the generated
+    /// code, otherwise an error is returned. Nothing is written to the disk.
+    pub fn check(mut self) -> Result<bool, Error> {
+        let path = match self.target.take() {
+            Some(Target::File(path)) => path,
+            _ => {
+                return Err(
+                    io::Error::new(io::ErrorKind::InvalidInput, "only an output file can be checked").into(),
+                )
+            }
+        };
+        let code = self.generate_tokens()?;
+        let current = match fs::read(&path) {
+            Ok(current) => current,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        // format the code from the directory of the output, so that rustfmt uses the same
+        // configuration as when writing it
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        Ok(current == format(&code, dir)?)
+    }
+
+    /// Generate the code and return it as a token stream
+    ///
+    /// The output, if any, is ignored. This is intended for procedural macros.
+    pub fn generate_tokens(self) -> Result<TokenStream, Error> {
+        let (protocol, file) = match self.source {
+            Source::File(path) => {
+                let file = File::open(&path)?;
+                (crate::load_protocol(file), Some(path))
+            }
+            Source::Stream(stream) => (crate::load_protocol(stream), None),
+        };
+        let with_file = |e: crate::ProtocolError| match file {
+            Some(ref path) => e.with_file(path),
+            None => e,
+        };
+        let mut protocol = protocol.map_err(&with_file)?;
+
+        let events = self
+            .events
+            .iter()
+            .map(|(i, e)| (&i[..], &e[..]))
+            .collect::<Vec<_>>();
+        crate::set_destructor_events(&mut protocol, &events);
+        set_docs(&mut protocol, self.docs);
+
+        let imports = match self.paths {
+            Some(ref paths) => Some(resolve::imports(&protocol, paths).map_err(&with_file)?),
+            None => None,
+        };
+        let code = crate::generate(protocol, self.side, self.mode, self.handlers);
+        Ok(quote::quote!(#imports #code))
+    }
+}
+
+// write the code to a file, formatted with rustfmt if it is available
+fn write_file(path: &Path, code: &TokenStream) -> io::Result<()> {
+    {
+        let mut out = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(path)?;
+        write!(&mut out, "{}", code)?;
+    }
+    let _ = Command::new("rustfmt").arg(path).status();
+    Ok(())
+}
+
+// format the code with rustfmt, using the configuration applying to given directory
+fn format(code: &TokenStream, dir: &Path) -> io::Result<Vec<u8>> {
+    let mut rustfmt = Command::new("rustfmt")
+        .arg("--emit")
+        .arg("stdout")
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                io::ErrorKind::NotFound,
+                "rustfmt is unavailable, it is required to check the generated code",
+            ),
+            _ => e,
+        })?;
+    if let Some(mut stdin) = rustfmt.stdin.take() {
+        write!(stdin, "{}", code)?;
+    }
+    let output = rustfmt.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "rustfmt failed to format the generated code",
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn set_docs(protocol: &mut Protocol, docs: Docs) {
+    fn strip(description: &mut Option<(String, String)>, docs: Docs) {
+        match docs {
+            Docs::Full => {}
+            Docs::Summary => {
+                if let Some((_, ref mut long)) = *description {
+                    long.clear();
+                }
+            }
+            Docs::None => *description = None,
+        }
+    }
+
+    if docs == Docs::Full {
+        return;
+    }
+    for interface in &mut protocol.interfaces {
+        strip(&mut interface.description, docs);
+        for msg in interface.requests.iter_mut().chain(&mut interface.events) {
+            strip(&mut msg.description, docs);
+            for arg in &mut msg.args {
+                strip(&mut arg.description, docs);
+                if docs == Docs::None {
+                    arg.summary = None;
+                }
+            }
+        }
+        for enu in &mut interface.enums {
+            strip(&mut enu.description, docs);
+            for entry in &mut enu.entries {
+                strip(&mut entry.description, docs);
+                if docs == Docs::None {
+                    entry.summary = None;
+                }
+            }
+        }
+    }
+}
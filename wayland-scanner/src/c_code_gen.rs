@@ -6,9 +6,10 @@ use quote::quote;
 use crate::common_gen::*;
 use crate::protocol::*;
 use crate::util::*;
-use crate::Side;
+use crate::{Mode, Side};
 
-pub(crate) fn generate_protocol_client(protocol: Protocol) -> TokenStream {
+pub(crate) fn generate_protocol_client(protocol: Protocol, mode: Mode, handlers: bool) -> TokenStream {
+    let c_interfaces = mode == Mode::Full;
     let modules = protocol.interfaces.iter().map(|iface| {
         let doc_attr = iface.description.as_ref().map(description_to_doc_attr);
         let mod_name = Ident::new(&iface.name, Span::call_site());
@@ -19,68 +20,67 @@ pub(crate) fn generate_protocol_client(protocol: Protocol) -> TokenStream {
         let ident = Ident::new("Request", Span::call_site());
         let requests = gen_messagegroup(
             &ident,
+            &iface.name,
             Side::Client,
             false,
             &iface.requests,
-            Some(messagegroup_c_addon(
-                &ident,
-                &iface_name,
-                Side::Client,
-                false,
-                &iface.requests,
-            )),
+            if_c(c_interfaces, || {
+                messagegroup_c_addon(&ident, &iface_name, Side::Client, false, &iface.requests)
+            }),
         );
 
         let ident = Ident::new("Event", Span::call_site());
         let events = gen_messagegroup(
             &ident,
+            &iface.name,
             Side::Client,
             true,
             &iface.events,
-            Some(messagegroup_c_addon(
-                &ident,
-                &iface_name,
-                Side::Client,
-                true,
-                &iface.events,
-            )),
+            if_c(c_interfaces, || {
+                messagegroup_c_addon(&ident, &iface_name, Side::Client, true, &iface.events)
+            }),
         );
 
         let interface = gen_interface(
             &iface_name,
             &iface.name,
             iface.version,
-            Some(interface_c_addon(&iface.name)),
+            if_c(c_interfaces, || interface_c_addon(&iface.name)),
             Side::Client,
         );
 
         let object_methods = gen_object_methods(&iface_name, &iface.requests, Side::Client);
+        let handler = if handlers {
+            Some(gen_handler(&iface_name, &iface.name, Side::Client, &iface.events))
+        } else {
+            None
+        };
         let sinces = gen_since_constants(&iface.requests, &iface.events);
-        let c_interface = super::c_interface_gen::generate_interface(&iface);
+        let c_interface = if_c(c_interfaces, || {
+            super::c_interface_gen::generate_interface(&iface)
+        });
+        let imports = module_imports(Side::Client, c_interfaces);
 
         quote! {
             #doc_attr
             pub mod #mod_name {
-                use std::os::raw::c_char;
-                use super::{
-                    Proxy, AnonymousObject, Interface, MessageGroup, MessageDesc, ArgumentType,
-                    Object, Message, Argument, ObjectMetadata, types_null, NULLPTR, Main, smallvec,
-                };
-                use super::sys::common::{wl_interface, wl_array, wl_argument, wl_message};
-                use super::sys::client::*;
+                #imports
 
                 #(#enums)*
                 #requests
                 #events
                 #interface
                 #object_methods
+                #handler
                 #sinces
                 #c_interface
             }
         }
     });
 
-    let c_prefix = super::c_interface_gen::generate_interfaces_prefix(&protocol);
+    let c_prefix = if_c(c_interfaces, || {
+        super::c_interface_gen::generate_interfaces_prefix(&protocol)
+    });
 
     quote! {
         #c_prefix
@@ -89,7 +89,8 @@ pub(crate) fn generate_protocol_client(protocol: Protocol) -> TokenStream {
     }
 }
 
-pub(crate) fn generate_protocol_server(protocol: Protocol) -> TokenStream {
+pub(crate) fn generate_protocol_server(protocol: Protocol, mode: Mode, handlers: bool) -> TokenStream {
+    let c_interfaces = mode == Mode::Full;
     let modules = protocol
         .interfaces
         .iter()
@@ -105,67 +106,71 @@ pub(crate) fn generate_protocol_server(protocol: Protocol) -> TokenStream {
             let ident = Ident::new("Request", Span::call_site());
             let requests = gen_messagegroup(
                 &ident,
+                &iface.name,
                 Side::Server,
                 true,
                 &iface.requests,
-                Some(messagegroup_c_addon(
-                    &ident,
-                    &iface_name,
-                    Side::Server,
-                    true,
-                    &iface.requests,
-                )),
+                if_c(c_interfaces, || {
+                    messagegroup_c_addon(&ident, &iface_name, Side::Server, true, &iface.requests)
+                }),
             );
 
             let ident = Ident::new("Event", Span::call_site());
             let events = gen_messagegroup(
                 &ident,
+                &iface.name,
                 Side::Server,
                 false,
                 &iface.events,
-                Some(messagegroup_c_addon(
-                    &ident,
-                    &iface_name,
-                    Side::Server,
-                    false,
-                    &iface.events,
-                )),
+                if_c(c_interfaces, || {
+                    messagegroup_c_addon(&ident, &iface_name, Side::Server, false, &iface.events)
+                }),
             );
 
             let interface = gen_interface(
                 &Ident::new(&snake_to_camel(&iface.name), Span::call_site()),
                 &iface.name,
                 iface.version,
-                Some(interface_c_addon(&iface.name)),
+                if_c(c_interfaces, || interface_c_addon(&iface.name)),
                 Side::Server,
             );
             let object_methods = gen_object_methods(&iface_name, &iface.events, Side::Server);
+            let handler = if handlers {
+                Some(gen_handler(
+                    &iface_name,
+                    &iface.name,
+                    Side::Server,
+                    &iface.requests,
+                ))
+            } else {
+                None
+            };
             let sinces = gen_since_constants(&iface.requests, &iface.events);
-            let c_interface = super::c_interface_gen::generate_interface(&iface);
+            let c_interface = if_c(c_interfaces, || {
+                super::c_interface_gen::generate_interface(&iface)
+            });
+            let imports = module_imports(Side::Server, c_interfaces);
 
             quote! {
                 #doc_attr
                 pub mod #mod_name {
-                    use std::os::raw::c_char;
-                    use super::{
-                        Resource, AnonymousObject, Interface, MessageGroup, MessageDesc, Main, smallvec,
-                        ArgumentType, Object, Message, Argument, ObjectMetadata, types_null, NULLPTR
-                    };
-                    use super::sys::common::{wl_argument, wl_interface, wl_array, wl_message};
-                    use super::sys::server::*;
+                    #imports
 
                     #(#enums)*
                     #requests
                     #events
                     #interface
                     #object_methods
+                    #handler
                     #sinces
                     #c_interface
                 }
             }
         });
 
-    let c_prefix = super::c_interface_gen::generate_interfaces_prefix(&protocol);
+    let c_prefix = if_c(c_interfaces, || {
+        super::c_interface_gen::generate_interfaces_prefix(&protocol)
+    });
 
     quote! {
         #c_prefix
@@ -173,6 +178,51 @@ pub(crate) fn generate_protocol_server(protocol: Protocol) -> TokenStream {
     }
 }
 
+// the code only generated along with the C interfaces
+fn if_c<F: FnOnce() -> TokenStream>(c_interfaces: bool, f: F) -> Option<TokenStream> {
+    if c_interfaces {
+        Some(f())
+    } else {
+        None
+    }
+}
+
+fn module_imports(side: Side, c_interfaces: bool) -> TokenStream {
+    match (side, c_interfaces) {
+        (Side::Client, true) => quote! {
+            use std::os::raw::c_char;
+            use super::{
+                Proxy, AnonymousObject, Interface, MessageGroup, MessageDesc, ArgumentType,
+                Object, Message, Argument, ObjectMetadata, types_null, NULLPTR, Main, smallvec,
+                SendError,
+            };
+            use super::sys::common::{wl_interface, wl_array, wl_argument, wl_message};
+            use super::sys::client::*;
+        },
+        (Side::Client, false) => quote! {
+            use super::{
+                Proxy, AnonymousObject, Interface, MessageGroup, MessageDesc, ArgumentType,
+                Object, Message, Argument, ObjectMetadata, Main, smallvec, SendError,
+            };
+        },
+        (Side::Server, true) => quote! {
+            use std::os::raw::c_char;
+            use super::{
+                Resource, AnonymousObject, Interface, MessageGroup, MessageDesc, Main, smallvec,
+                ArgumentType, Object, Message, Argument, ObjectMetadata, types_null, NULLPTR
+            };
+            use super::sys::common::{wl_argument, wl_interface, wl_array, wl_message};
+            use super::sys::server::*;
+        },
+        (Side::Server, false) => quote! {
+            use super::{
+                Resource, AnonymousObject, Interface, MessageGroup, MessageDesc, Main, smallvec,
+                ArgumentType, Object, Message, Argument, ObjectMetadata
+            };
+        },
+    }
+}
+
 fn messagegroup_c_addon(
     name: &Ident,
     parent_iface: &Ident,
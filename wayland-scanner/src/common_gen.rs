@@ -183,6 +183,7 @@ pub(crate) fn gen_since_constants(requests: &[Message], events: &[Message]) -> T
 
 pub(crate) fn gen_messagegroup(
     name: &Ident,
+    iface_name: &str,
     side: Side,
     receiver: bool,
     messages: &[Message],
@@ -213,58 +214,7 @@ pub(crate) fn gen_messagegroup(
                     &format!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name),
                     Span::call_site(),
                 );
-                let field_type_inner = if let Some(ref enu) = arg.enum_ {
-                    dotted_to_relname(enu)
-                } else {
-                    match arg.typ {
-                        Type::Uint => quote!(u32),
-                        Type::Int => quote!(i32),
-                        Type::Fixed => quote!(f64),
-                        Type::String => quote!(String),
-                        Type::Array => quote!(Vec<u8>),
-                        Type::Fd => quote!(::std::os::unix::io::RawFd),
-                        Type::Object => {
-                            if let Some(ref iface) = arg.interface {
-                                let iface_mod = Ident::new(&iface, Span::call_site());
-                                let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
-                                quote!(super::#iface_mod::#iface_type)
-                            } else {
-                                quote!(AnonymousObject)
-                            }
-                        }
-                        Type::NewId if !receiver && side == Side::Client => {
-                            // Client-side sending does not have a pre-existing object
-                            // so skip serializing it
-                            if arg.interface.is_some() {
-                                return None;
-                            } else {
-                                quote!((String, u32))
-                            }
-                        }
-                        Type::NewId => {
-                            let object_name = if side == Side::Server && !receiver {
-                                Ident::new("Resource", Span::call_site())
-                            } else {
-                                Ident::new("Main", Span::call_site())
-                            };
-                            if let Some(ref iface) = arg.interface {
-                                let iface_mod = Ident::new(&iface, Span::call_site());
-                                let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
-                                quote!(#object_name<super::#iface_mod::#iface_type>)
-                            } else {
-                                // bind-like function
-                                quote!((String, u32, AnonymousObject))
-                            }
-                        }
-                        Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
-                    }
-                };
-
-                let field_type = if arg.allow_null {
-                    quote!(Option<#field_type_inner>)
-                } else {
-                    field_type_inner.into_token_stream()
-                };
+                let field_type = field_type(arg, side, receiver)?;
                 Some(quote! {
                     #field_name: #field_type
                 })
@@ -623,12 +573,76 @@ pub(crate) fn gen_messagegroup(
         }
     };
 
+    let debug_match_arms = messages.iter().map(|msg| {
+        let msg_type = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+        let prefix = format!("{}.{}(", iface_name, msg.name);
+        let mut fields = Vec::new();
+        let mut writes = Vec::new();
+        for (i, arg) in msg.args.iter().enumerate() {
+            let separator = if i > 0 { ", " } else { "" };
+            let label = format!("{}{}: ", separator, arg.name);
+            let created_iface = match arg.interface {
+                // the object is created when sending the request
+                Some(ref iface) if side == Side::Client && !receiver && arg.typ == Type::NewId => Some(iface),
+                _ => None,
+            };
+            let value = if let Some(iface) = created_iface {
+                let new_id = format!("new id {}", iface);
+                quote!(f.write_str(#new_id)?;)
+            } else {
+                let field = Ident::new(
+                    &format!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name),
+                    Span::call_site(),
+                );
+                fields.push(field.clone());
+                let value = debug_arg(arg, &quote!(#field), side, receiver);
+                if arg.allow_null {
+                    quote! {
+                        match *#field {
+                            Some(ref #field) => { #value }
+                            None => f.write_str("nil")?,
+                        }
+                    }
+                } else {
+                    value
+                }
+            };
+            writes.push(quote! {
+                f.write_str(#label)?;
+                #value
+            });
+        }
+        let pattern = if msg.args.is_empty() {
+            quote!(#name::#msg_type)
+        } else if fields.len() < msg.args.len() {
+            quote!(#name::#msg_type { #(ref #fields,)* .. })
+        } else {
+            quote!(#name::#msg_type { #(ref #fields,)* })
+        };
+        quote! {
+            #pattern => {
+                f.write_str(#prefix)?;
+                #(#writes)*
+                f.write_str(")")
+            }
+        }
+    });
+
     quote! {
         pub enum #name {
             #(#variants,)*
             #[doc(hidden)] __nonexhaustive,
         }
 
+        impl ::std::fmt::Debug for #name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    #name::__nonexhaustive => unreachable!(),
+                    #(#debug_match_arms,)*
+                }
+            }
+        }
+
         impl super::MessageGroup for #name {
             const MESSAGES: &'static [super::MessageDesc] = &[
                 #(#message_array_values,)*
@@ -676,6 +690,98 @@ pub(crate) fn gen_messagegroup(
     }
 }
 
+// the type of the field of an argument in a message enum, `None` if it is skipped
+fn field_type(arg: &Arg, side: Side, receiver: bool) -> Option<TokenStream> {
+    let field_type_inner = if let Some(ref enu) = arg.enum_ {
+        dotted_to_relname(enu)
+    } else {
+        match arg.typ {
+            Type::Uint => quote!(u32),
+            Type::Int => quote!(i32),
+            Type::Fixed => quote!(f64),
+            Type::String => quote!(String),
+            Type::Array => quote!(Vec<u8>),
+            Type::Fd => quote!(::std::os::unix::io::RawFd),
+            Type::Object => {
+                if let Some(ref iface) = arg.interface {
+                    let iface_mod = Ident::new(&iface, Span::call_site());
+                    let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
+                    quote!(super::#iface_mod::#iface_type)
+                } else {
+                    quote!(AnonymousObject)
+                }
+            }
+            Type::NewId if !receiver && side == Side::Client => {
+                // Client-side sending does not have a pre-existing object
+                // so skip serializing it
+                if arg.interface.is_some() {
+                    return None;
+                } else {
+                    quote!((String, u32))
+                }
+            }
+            Type::NewId => {
+                let object_name = if side == Side::Server && !receiver {
+                    Ident::new("Resource", Span::call_site())
+                } else {
+                    Ident::new("Main", Span::call_site())
+                };
+                if let Some(ref iface) = arg.interface {
+                    let iface_mod = Ident::new(&iface, Span::call_site());
+                    let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
+                    quote!(#object_name<super::#iface_mod::#iface_type>)
+                } else {
+                    // bind-like function
+                    quote!((String, u32, AnonymousObject))
+                }
+            }
+            Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
+        }
+    };
+
+    Some(if arg.allow_null {
+        quote!(Option<#field_type_inner>)
+    } else {
+        field_type_inner.into_token_stream()
+    })
+}
+
+// write an argument of a message to the formatter `f`, in the style of `WAYLAND_DEBUG`
+fn debug_arg(arg: &Arg, value: &TokenStream, side: Side, receiver: bool) -> TokenStream {
+    if arg.enum_.is_some() {
+        // the name of the variant, or the list of the flags of a bitfield
+        return quote!(write!(f, "{:?}", #value)?;);
+    }
+    match arg.typ {
+        Type::Int | Type::Uint | Type::Fixed => quote!(write!(f, "{}", #value)?;),
+        Type::String => quote!(write!(f, "{:?}", #value)?;),
+        Type::Array => quote!(write!(f, "array[{}]", #value.len())?;),
+        Type::Fd => quote!(write!(f, "fd {}", #value)?;),
+        Type::Object => match (arg.interface.as_ref(), side) {
+            (Some(iface), _) => quote!(write!(f, "{}@{}", #iface, #value.as_ref().id())?;),
+            (None, Side::Client) => quote!(write!(f, "[unknown]@{}", #value.as_ref().id())?;),
+            (None, Side::Server) => quote! {
+                write!(f, "{}@{}", #value.as_ref().interface_name(), #value.as_ref().id())?;
+            },
+        },
+        Type::NewId => match (arg.interface.as_ref(), side, receiver) {
+            // a resource created by the server
+            (Some(iface), Side::Server, false) => {
+                quote!(write!(f, "new id {}@{}", #iface, #value.id())?;)
+            }
+            (Some(iface), _, _) => quote!(write!(f, "new id {}@{}", #iface, #value.as_ref().id())?;),
+            // bind-like requests, the object is created when sending the request
+            (None, Side::Client, _) => quote! {
+                write!(f, "new id {} (version {})", #value.0, #value.1)?;
+            },
+            (None, Side::Server, _) => quote! {
+                write!(f, "new id {}@{} (version {})", #value.0, #value.2.as_ref().id(), #value.1)?;
+            },
+        },
+        Type::Destructor => panic!("An argument cannot have type Destructor"),
+    }
+}
+
 pub(crate) fn gen_interface(
     name: &Ident,
     low_name: &str,
@@ -809,6 +915,12 @@ pub fn method_prototype<'a>(iname: &Ident, msg: &'a Message, side: Side) -> (Tok
     } else {
         quote!(())
     };
+    // client-side, the requests report why they could not be sent
+    let return_type = if side == Side::Client {
+        quote!(Result<#return_type, SendError>)
+    } else {
+        return_type
+    };
 
     let prototype = quote! {
         pub fn #fn_name#(<#generics>)*(&self, #(#args),*) -> #return_type
@@ -884,11 +996,11 @@ pub(crate) fn gen_object_methods(name: &Ident, messages: &[Message], side: Side)
 
         let send_stmt = match return_type {
             Some(ret_type) if ret_type.interface.is_none() => {
-                quote!(self.0.send(msg, Some(version)).unwrap())
+                quote!(self.0.send(msg, Some(version)).map(|o| o.unwrap()))
             }
-            Some(_) => quote!(self.0.send(msg, None).unwrap()),
+            Some(_) => quote!(self.0.send(msg, None).map(|o| o.unwrap())),
             None => if side == Side::Client {
-                quote! { self.0.send::<AnonymousObject>(msg, None); }
+                quote! { self.0.send::<AnonymousObject>(msg, None).map(|_| ()) }
             } else {
                 quote! { self.0.send(msg); }
             }
@@ -909,3 +1021,121 @@ pub(crate) fn gen_object_methods(name: &Ident, messages: &[Message], side: Side)
         }
     }
 }
+
+pub(crate) fn gen_handler(name: &Ident, iface_name: &str, side: Side, messages: &[Message]) -> TokenStream {
+    let (trait_name, message_type, kind) = match side {
+        Side::Client => ("EventHandler", "Event", "event"),
+        Side::Server => ("RequestHandler", "Request", "request"),
+    };
+    let trait_name = Ident::new(trait_name, Span::call_site());
+    let message_type = Ident::new(message_type, Span::call_site());
+
+    let trait_doc = to_doc_attr(&format!(
+        "A handler for the {kind}s of this interface\n\n\
+         Each method handles one {kind}. {default} Assign a handler to an object with \
+         `Main::assign_handler`.",
+        kind = kind,
+        default = match side {
+            Side::Client => "They do nothing by default, so only the events of interest need to be handled.",
+            Side::Server => {
+                "By default, the destructors do nothing and the other requests post the \
+                 `invalid_method` protocol error of `wl_display`, so only the supported requests need to be handled."
+            }
+        }
+    ));
+
+    let methods = messages.iter().map(|msg| {
+        let mut docs = String::new();
+        if let Some((ref short, ref long)) = msg.description {
+            docs += &format!("{}\n\n{}\n", short, long);
+        }
+        if msg.since > 1 {
+            docs += &format!("\nOnly available since version {} of the interface.", msg.since);
+        }
+        let doc_attr = to_doc_attr(&docs);
+
+        let fn_name = handler_fn_name(msg);
+        let object = handler_object_name(msg);
+        let args = msg.args.iter().map(|arg| {
+            let arg_name = Ident::new(
+                &format!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name),
+                Span::call_site(),
+            );
+            // received messages never skip their arguments
+            let arg_type = field_type(arg, side, true).unwrap();
+            quote!(#arg_name: #arg_type)
+        });
+
+        let body = if side == Side::Server && msg.typ != Some(Type::Destructor) {
+            let error = format!("{}@{{}}.{} is not implemented", iface_name, msg.name);
+            quote!(#object.as_ref().post_invalid_method(format!(#error, #object.as_ref().id()));)
+        } else {
+            TokenStream::new()
+        };
+
+        quote! {
+            #doc_attr
+            fn #fn_name(&mut self, #object: Main<#name>, #(#args),*) {
+                #body
+            }
+        }
+    });
+
+    let match_arms = messages.iter().map(|msg| {
+        let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+        let fn_name = handler_fn_name(msg);
+        // bind the fields to names that cannot collide with the arguments of `handle`
+        let bindings = (0..msg.args.len())
+            .map(|i| Ident::new(&format!("arg{}", i), Span::call_site()))
+            .collect::<Vec<_>>();
+        let fields = msg.args.iter().zip(&bindings).map(|(arg, binding)| {
+            let field_name = Ident::new(
+                &format!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name),
+                Span::call_site(),
+            );
+            quote!(#field_name: #binding)
+        });
+        let pattern = if msg.args.is_empty() {
+            quote!(#message_type::#msg_name)
+        } else {
+            quote!(#message_type::#msg_name { #(#fields),* })
+        };
+        quote! {
+            #pattern => {
+                <H as #trait_name>::#fn_name(handler, object, #(#bindings),*)
+            }
+        }
+    });
+
+    quote! {
+        #trait_doc
+        pub trait #trait_name {
+            #(#methods)*
+        }
+
+        impl<H: #trait_name> super::HandledBy<H> for #name {
+            fn handle(handler: &mut H, object: Main<#name>, msg: #message_type) {
+                match msg {
+                    #(#match_arms,)*
+                    #message_type::__nonexhaustive => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+fn handler_fn_name(msg: &Message) -> Ident {
+    Ident::new(
+        &format!("{}{}", if is_keyword(&msg.name) { "_" } else { "" }, msg.name),
+        Span::call_site(),
+    )
+}
+
+// the name of the object argument of a handler method, distinct from the message arguments
+fn handler_object_name(msg: &Message) -> Ident {
+    let mut name = String::from("object");
+    while msg.args.iter().any(|arg| arg.name == name) {
+        name.insert(0, '_');
+    }
+    Ident::new(&name, Span::call_site())
+}
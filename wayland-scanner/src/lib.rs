@@ -77,29 +77,192 @@
 //!     }
 //! }
 //! ```
+//!
+//! The protocol files are validated before generating any code: the functions above panic
+//! with the list of the problems found, along with their line and column in the file. Use
+//! `validate` to get these problems as a `Result` instead.
+//!
+//! If your crate only uses the rust implementation of the protocol, and never enables the
+//! `use_system_lib` feature of `wayland-client` or `wayland-server`, `generate_code_with_mode`
+//! with `Mode::RustOnly` skips the generation of the C interfaces of the protocol, which
+//! are otherwise included in the generated code.
+//!
+//! Rather than importing the modules of the interfaces of other protocols by hand, you
+//! can give their paths to `generate_code_with_interface_paths`, which imports them in
+//! the generated code and reports the interfaces it cannot resolve:
+//!
+//! ```no_run
+//! # extern crate wayland_scanner;
+//! # use wayland_scanner::{Side, InterfacePaths, generate_code_with_interface_paths};
+//! # fn main() {
+//! let mut paths = InterfacePaths::core(Side::Client);
+//! // the interfaces of this protocol are in the `crate::xdg_shell::client` module
+//! paths.insert_protocol("./xdg-shell.xml", "crate::xdg_shell::client").unwrap();
+//! generate_code_with_interface_paths(
+//!     "./my_protocol.xml",
+//!     "./my_protocol_api.rs",
+//!     Side::Client,
+//!     &[],
+//!     &paths,
+//! ).unwrap();
+//! # }
+//! ```
+//!
+//! ## The `Scanner` builder
+//!
+//! The functions above each cover a combination of options. The `Scanner` builder exposes
+//! all of them, including the documentation to include in the generated code, and reports
+//! errors as a `Result` rather than panicking:
+//!
+//! ```no_run
+//! # extern crate wayland_scanner;
+//! # use wayland_scanner::{Docs, InterfacePaths, Scanner, Side};
+//! # fn main() {
+//! Scanner::new("./my_protocol.xml", Side::Client)
+//!     .interface_paths(InterfacePaths::core(Side::Client))
+//!     .docs(Docs::Summary)
+//!     .output("./my_protocol_api.rs")
+//!     .generate()
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! `Scanner::handlers` additionally generates a handler trait for each interface, with one
+//! method per received message, for the `assign_handler` method of `wayland-client` and
+//! `wayland-server`.
+//!
+//! `Scanner::check` compares the generated code to the contents of the output file rather
+//! than writing it, for crates checking their generated code into their repository.
+//!
+//! ## The command-line scanner
+//!
+//! The `wayland-scanner` binary of this crate runs the same generator outside of a build
+//! script, so that crates checking in their generated code don't depend on this crate at
+//! build time. For example, `wayland-scanner client my_protocol.xml src/my_protocol_api.rs
+//! --core-paths` generates the code, and the same command with `--check` fails if the
+//! checked-in code is outdated. See `wayland-scanner --help` for all the options.
+//!
+//! Alternatively, the `wayland_protocol!` procedural macro of the `wayland-scanner-macros`
+//! crate generates the same code inline, without a build script nor this boilerplate.
 
 #![recursion_limit = "128"]
 #![warn(missing_docs)]
 
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
 use std::path::Path;
-use std::process::Command;
 
+mod builder;
 mod c_code_gen;
 mod c_interface_gen;
 mod common_gen;
 mod parse;
 mod protocol;
+mod resolve;
 mod side;
 mod util;
+mod validate;
 
+pub use builder::{Docs, Mode, Scanner};
+pub use resolve::InterfacePaths;
 pub use side::Side;
+pub use validate::{Diagnostic, ProtocolError};
+
+/// An error of the scanner
+#[derive(Debug)]
+pub enum Error {
+    /// The protocol file could not be read
+    Io(io::Error),
+    /// The protocol file is invalid
+    Protocol(ProtocolError),
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<ProtocolError> for Error {
+    fn from(e: ProtocolError) -> Error {
+        Error::Protocol(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+            Error::Protocol(ref e) => write!(f, "invalid protocol file:\n{}", e),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            Error::Protocol(ref e) => Some(e),
+        }
+    }
+}
+
+// parse and validate a protocol
+fn load_protocol<R: Read>(stream: R) -> Result<protocol::Protocol, ProtocolError> {
+    let protocol = parse::parse_stream(stream)?;
+    let diagnostics = validate::validate_protocol(&protocol);
+    if diagnostics.is_empty() {
+        Ok(protocol)
+    } else {
+        Err(ProtocolError { diagnostics })
+    }
+}
+
+fn generate(
+    protocol: protocol::Protocol,
+    side: Side,
+    mode: Mode,
+    handlers: bool,
+) -> proc_macro2::TokenStream {
+    match side {
+        Side::Client => c_code_gen::generate_protocol_client(protocol, mode, handlers),
+        Side::Server => c_code_gen::generate_protocol_server(protocol, mode, handlers),
+    }
+}
+
+fn set_destructor_events(protocol: &mut protocol::Protocol, events: &[(&str, &str)]) {
+    for interface in &mut protocol.interfaces {
+        for event in &mut interface.events {
+            if events.contains(&(&interface.name, &event.name)) {
+                event.typ = Some(crate::protocol::Type::Destructor);
+            }
+        }
+    }
+}
+
+// the free functions panic on errors
+fn expect_generated(result: Result<(), Error>) {
+    if let Err(e) = result {
+        panic!("Unable to generate the code of the protocol: {}", e);
+    }
+}
 
-fn load_xml<P: AsRef<Path>>(prot: P) -> protocol::Protocol {
-    let pfile = File::open(prot.as_ref())
-        .unwrap_or_else(|_| panic!("Unable to open protocol file `{}`.", prot.as_ref().display()));
-    parse::parse_stream(pfile)
+/// Check a protocol file
+///
+/// Reports the problems that would prevent generating code for this protocol, with
+/// their position in the file.
+pub fn validate<P: AsRef<Path>>(prot: P) -> Result<(), Error> {
+    let pfile = File::open(prot.as_ref())?;
+    load_protocol(pfile).map_err(|e| e.with_file(prot.as_ref()))?;
+    Ok(())
+}
+
+/// Check a protocol from an IO stream
+///
+/// Like `validate`, but takes an IO stream rather than a filename.
+pub fn validate_stream<P: Read>(protocol: P) -> Result<(), ProtocolError> {
+    load_protocol(protocol).map(|_| ())
 }
 
 /// Generate the code for a protocol using the C system libs
@@ -127,33 +290,54 @@ pub fn generate_code_with_destructor_events<P1: AsRef<Path>, P2: AsRef<Path>>(
     side: Side,
     events: &[(&str, &str)],
 ) {
-    let mut protocol = load_xml(prot);
-
-    for interface in &mut protocol.interfaces {
-        for event in &mut interface.events {
-            if events.contains(&(&interface.name, &event.name)) {
-                event.typ = Some(crate::protocol::Type::Destructor);
-            }
-        }
-    }
-
-    {
-        let mut out = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&target)
-            .unwrap();
-
-        let output = match side {
-            Side::Client => c_code_gen::generate_protocol_client(protocol),
-            Side::Server => c_code_gen::generate_protocol_server(protocol),
-        };
+    generate_code_with_mode(prot, target, side, events, Mode::Full);
+}
 
-        write!(&mut out, "{}", output).unwrap();
-    }
+/// Generate the code for a protocol, for the given backends
+///
+/// Same as `generate_code_with_destructor_events`, but allows you to skip the generation
+/// of the C interfaces if the generated code is only used with the rust implementation
+/// of the protocol, see `Mode`.
+///
+/// The code generated in `Mode::RustOnly` can refer to interfaces of other protocols that
+/// were generated in any mode, while the code generated in `Mode::Full` requires the C
+/// interfaces of the interfaces it refers to.
+pub fn generate_code_with_mode<P1: AsRef<Path>, P2: AsRef<Path>>(
+    prot: P1,
+    target: P2,
+    side: Side,
+    events: &[(&str, &str)],
+    mode: Mode,
+) {
+    expect_generated(
+        Scanner::new(prot, side)
+            .destructor_events(events)
+            .mode(mode)
+            .output(target)
+            .generate(),
+    );
+}
 
-    let _ = Command::new("rustfmt").arg(target.as_ref()).status();
+/// Generate the code for a protocol, importing the interfaces of other protocols
+///
+/// Same as `generate_code_with_destructor_events`, but the generated code imports the
+/// modules of the interfaces of other protocols it refers to, from the paths given in
+/// `paths`, rather than expecting them in the module including it.
+///
+/// Rather than panicking, returns an error if the protocol file cannot be read, is invalid,
+/// or refers to an interface whose path is unknown.
+pub fn generate_code_with_interface_paths<P1: AsRef<Path>, P2: AsRef<Path>>(
+    prot: P1,
+    target: P2,
+    side: Side,
+    events: &[(&str, &str)],
+    paths: &InterfacePaths,
+) -> Result<(), Error> {
+    Scanner::new(prot, side)
+        .destructor_events(events)
+        .interface_paths(paths.clone())
+        .output(target)
+        .generate()
 }
 
 /// Generate the code for a protocol from/to IO streams
@@ -180,20 +364,29 @@ pub fn generate_code_streams_with_destructor_events<P1: Read, P2: Write>(
     side: Side,
     events: &[(&str, &str)],
 ) {
-    let mut protocol = parse::parse_stream(protocol);
-
-    for interface in &mut protocol.interfaces {
-        for event in &mut interface.events {
-            if events.contains(&(&interface.name, &event.name)) {
-                event.typ = Some(crate::protocol::Type::Destructor);
-            }
-        }
-    }
-
-    let output = match side {
-        Side::Client => c_code_gen::generate_protocol_client(protocol),
-        Side::Server => c_code_gen::generate_protocol_server(protocol),
-    };
+    expect_generated(
+        Scanner::from_stream(protocol, side)
+            .destructor_events(events)
+            .output_stream(target)
+            .generate(),
+    );
+}
 
-    write!(target, "{}", output.clone()).unwrap();
+/// Generate the code for a protocol as a token stream
+///
+/// Like `generate_code_streams`, but returns the generated code rather than writing it,
+/// for use in procedural macros. Returns the problems found in the protocol if it is invalid.
+///
+/// Args:
+///
+/// - `protocol`: an object `Read`-able containing the XML protocol file
+/// - `side`: the side (client or server) to generate code for.
+/// - `mode`: the backends the generated code supports.
+pub fn generate_code_tokens<P: Read>(
+    protocol: P,
+    side: Side,
+    mode: Mode,
+) -> Result<proc_macro2::TokenStream, ProtocolError> {
+    let protocol = load_protocol(protocol)?;
+    Ok(generate(protocol, side, mode, false))
 }
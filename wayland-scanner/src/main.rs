@@ -0,0 +1,181 @@
+//! Command-line front-end of the scanner
+//!
+//! Generates the code of a protocol outside of a build script, for crates checking in
+//! their generated code. See `wayland-scanner --help` for its usage.
+
+use std::env;
+use std::process::exit;
+
+use wayland_scanner::{Docs, InterfacePaths, Mode, Scanner, Side};
+
+const USAGE: &str = "\
+Usage: wayland-scanner <client|server> <PROTOCOL> <OUTPUT> [OPTIONS]
+
+Generate the rust code of the PROTOCOL XML file in the OUTPUT file, formatted
+with rustfmt if it is available.
+
+Options:
+    --check                         Check that OUTPUT is up to date rather than
+                                    writing it, and fail if it is not
+    --rust-only                     Do not generate the C interfaces, for code
+                                    only used with the rust implementation
+    --handlers                      Generate a handler trait for each interface
+    --docs <full|summary|none>      The documentation to generate (default: full)
+    --destructor-event <IFACE.EVENT>
+                                    Mark an event as a destructor
+    --core-paths                    Import the interfaces of the core protocol from
+                                    wayland-client or wayland-server
+    --interface-path <IFACE=PATH>   Import an interface from the module at PATH
+    --protocol-path <FILE=MODULE>   Import the interfaces of the protocol FILE from
+                                    the submodules of MODULE
+    -h, --help                      Print this message";
+
+struct Options {
+    side: Side,
+    protocol: String,
+    output: String,
+    check: bool,
+    mode: Mode,
+    handlers: bool,
+    docs: Docs,
+    events: Vec<(String, String)>,
+    paths: Option<InterfacePaths>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut check = false;
+    let mut mode = Mode::Full;
+    let mut handlers = false;
+    let mut docs = Docs::Full;
+    let mut events = Vec::new();
+    let mut core_paths = false;
+    let mut interface_paths = Vec::new();
+    let mut protocol_paths = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("Missing value for `{}`.", name))
+        };
+        match &arg[..] {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            "--check" => check = true,
+            "--rust-only" => mode = Mode::RustOnly,
+            "--handlers" => handlers = true,
+            "--docs" => {
+                docs = match &value(&arg)?[..] {
+                    "full" => Docs::Full,
+                    "summary" => Docs::Summary,
+                    "none" => Docs::None,
+                    other => return Err(format!("Invalid documentation level `{}`.", other)),
+                }
+            }
+            "--destructor-event" => events.push(split(&value(&arg)?, '.')?),
+            "--core-paths" => core_paths = true,
+            "--interface-path" => interface_paths.push(split(&value(&arg)?, '=')?),
+            "--protocol-path" => protocol_paths.push(split(&value(&arg)?, '=')?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option `{}`.", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    if positional.len() != 3 {
+        return Err("Expected a side, a protocol file and an output file.".into());
+    }
+    let output = positional.pop().unwrap();
+    let protocol = positional.pop().unwrap();
+    let side = match &positional[0][..] {
+        "client" => Side::Client,
+        "server" => Side::Server,
+        other => {
+            return Err(format!(
+                "Invalid side `{}`, expected `client` or `server`.",
+                other
+            ))
+        }
+    };
+
+    let paths = if core_paths || !interface_paths.is_empty() || !protocol_paths.is_empty() {
+        let mut paths = if core_paths {
+            InterfacePaths::core(side)
+        } else {
+            InterfacePaths::new()
+        };
+        for (interface, path) in interface_paths {
+            paths.insert(&interface, &path);
+        }
+        for (file, module) in protocol_paths {
+            paths.insert_protocol(&file, &module).map_err(|e| e.to_string())?;
+        }
+        Some(paths)
+    } else {
+        None
+    };
+
+    Ok(Options {
+        side,
+        protocol,
+        output,
+        check,
+        mode,
+        handlers,
+        docs,
+        events,
+        paths,
+    })
+}
+
+// split `a<sep>b` into its two non-empty parts
+fn split(value: &str, sep: char) -> Result<(String, String), String> {
+    match value.find(sep) {
+        Some(i) if i > 0 && i + 1 < value.len() => Ok((value[..i].into(), value[i + 1..].into())),
+        _ => Err(format!("Invalid value `{}`, expected `A{}B`.", value, sep)),
+    }
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            exit(2);
+        }
+    };
+
+    let events = options
+        .events
+        .iter()
+        .map(|(i, e)| (&i[..], &e[..]))
+        .collect::<Vec<_>>();
+    let mut scanner = Scanner::new(&options.protocol, options.side)
+        .destructor_events(&events)
+        .mode(options.mode)
+        .handlers(options.handlers)
+        .docs(options.docs)
+        .output(&options.output);
+    if let Some(paths) = options.paths.clone() {
+        scanner = scanner.interface_paths(paths);
+    }
+
+    let result = if options.check {
+        scanner.check().map(|up_to_date| {
+            if !up_to_date {
+                eprintln!(
+                    "`{}` is not up to date with `{}`, run the scanner without `--check` to update it.",
+                    options.output, options.protocol
+                );
+                exit(1);
+            }
+        })
+    } else {
+        scanner.generate()
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
@@ -1,286 +1,402 @@
 use crate::protocol::*;
+use crate::validate::{Diagnostic, ProtocolError};
 use std::io::Read;
+use std::str::FromStr;
 use xml::attribute::OwnedAttribute;
+use xml::common::Position as XmlPosition;
 use xml::reader::ParserConfig;
 use xml::reader::XmlEvent;
 use xml::EventReader;
 
-macro_rules! extract_from(
-    ($it: expr => $pattern: pat => $result: expr) => (
-        match $it.next() {
-            Ok($pattern) => { $result },
-            e => panic!("Ill-formed protocol file: {:?}", e)
+/// Parse a protocol file
+///
+/// Malformed XML stops the parsing, while invalid attribute values and unexpected
+/// elements are reported and skipped.
+pub fn parse_stream<S: Read>(stream: S) -> Result<Protocol, ProtocolError> {
+    let mut parser = Parser {
+        reader: EventReader::new_with_config(stream, ParserConfig::new().trim_whitespace(true)),
+        diagnostics: Vec::new(),
+    };
+    match parser.parse_protocol() {
+        Ok(protocol) if parser.diagnostics.is_empty() => Ok(protocol),
+        Ok(_) => Err(ProtocolError {
+            diagnostics: parser.diagnostics,
+        }),
+        Err(diagnostic) => {
+            parser.diagnostics.push(diagnostic);
+            Err(ProtocolError {
+                diagnostics: parser.diagnostics,
+            })
         }
-    )
-);
-
-macro_rules! extract_end_tag(
-    ($it: expr => $tag: expr) => (
-        extract_from!($it => XmlEvent::EndElement { name } => {
-            assert!(name.local_name == $tag, "Ill-formed protocol file");
-        });
-    )
-);
-
-pub fn parse_stream<S: Read>(stream: S) -> Protocol {
-    let mut reader = EventReader::new_with_config(stream, ParserConfig::new().trim_whitespace(true));
-    reader.next().expect("Could not read from event reader");
-    parse_protocol(reader)
+    }
+}
+
+struct Parser<R: Read> {
+    reader: EventReader<R>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+fn describe(event: &XmlEvent) -> String {
+    match *event {
+        XmlEvent::StartElement { ref name, .. } => format!("element `<{}>`", name.local_name),
+        XmlEvent::EndElement { ref name } => format!("closing tag `</{}>`", name.local_name),
+        XmlEvent::Characters(_) | XmlEvent::CData(_) => "text".into(),
+        XmlEvent::EndDocument => "end of file".into(),
+        _ => format!("{:?}", event),
+    }
 }
 
-fn parse_protocol<R: Read>(mut reader: EventReader<R>) -> Protocol {
-    let mut protocol = extract_from!(
-        reader => XmlEvent::StartElement { name, attributes, .. } => {
-            assert!(name.local_name == "protocol", "Missing protocol toplevel tag");
-            assert!(attributes[0].name.local_name == "name", "Protocol must have a name");
-            Protocol::new(attributes[0].value.clone())
+impl<R: Read> Parser<R> {
+    fn position(&self) -> Position {
+        let pos = self.reader.position();
+        Position {
+            line: pos.row + 1,
+            column: pos.column + 1,
         }
-    );
-
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => {
-                match &name.local_name[..] {
-                    "copyright" => {
-                        // parse the copyright
-                        let copyright = match reader.next() {
-                            Ok(XmlEvent::Characters(copyright)) | Ok(XmlEvent::CData(copyright)) => copyright,
-                            e => panic!("Ill-formed protocol file: {:?}", e),
-                        };
-
-                        extract_end_tag!(reader => "copyright");
-                        protocol.copyright = Some(copyright);
-                    }
-                    "interface" => {
-                        protocol.interfaces.push(parse_interface(&mut reader, attributes));
-                    }
-                    "description" => {
-                        protocol.description = Some(parse_description(&mut reader, attributes));
-                    }
-                    _ => panic!(
-                        "Ill-formed protocol file: unexpected token `{}` in protocol {}",
-                        name.local_name, protocol.name
-                    ),
-                }
+    }
+
+    fn next(&mut self) -> Result<XmlEvent, Diagnostic> {
+        match self.reader.next() {
+            Ok(XmlEvent::EndDocument) => {
+                Err(Diagnostic::new(self.position(), "unexpected end of file".into()))
             }
-            Ok(XmlEvent::EndElement { name }) => {
-                assert!(
-                    name.local_name == "protocol",
-                    "Unexpected closing token `{}`",
-                    name.local_name
-                );
-                break;
+            Ok(event) => Ok(event),
+            Err(e) => {
+                let pos = e.position();
+                Err(Diagnostic::new(
+                    Position {
+                        line: pos.row + 1,
+                        column: pos.column + 1,
+                    },
+                    format!("malformed XML: {}", e.msg()),
+                ))
             }
-            e => panic!("Ill-formed protocol file: {:?}", e),
         }
     }
 
-    protocol
-}
+    fn report(&mut self, position: Position, message: String) {
+        self.diagnostics.push(Diagnostic::new(position, message));
+    }
 
-fn parse_interface<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> Interface {
-    let mut interface = Interface::new();
-    for attr in attrs {
-        match &attr.name.local_name[..] {
-            "name" => interface.name = attr.value,
-            "version" => interface.version = attr.value.parse().unwrap(),
-            _ => {}
+    // report an unexpected element, and skip it
+    fn unexpected(&mut self, name: &str, parent: &str) -> Result<(), Diagnostic> {
+        let position = self.position();
+        self.report(
+            position,
+            format!("unexpected element `<{}>` in `<{}>`", name, parent),
+        );
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                XmlEvent::StartElement { .. } => depth += 1,
+                XmlEvent::EndElement { .. } => depth -= 1,
+                _ => {}
+            }
         }
+        Ok(())
     }
 
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => match &name.local_name[..] {
-                "description" => interface.description = Some(parse_description(reader, attributes)),
-                "request" => interface.requests.push(parse_request(reader, attributes)),
-                "event" => interface.events.push(parse_event(reader, attributes)),
-                "enum" => interface.enums.push(parse_enum(reader, attributes)),
-                _ => panic!("Unexpected tocken: `{}`", name.local_name),
-            },
-            Ok(XmlEvent::EndElement { ref name }) if name.local_name == "interface" => break,
-            _ => {}
+    fn number<T: FromStr + Default>(&mut self, position: Position, attr: &OwnedAttribute) -> T {
+        let value = &attr.value;
+        let parsed = match (value.get(..2), value.get(2..)) {
+            (Some("0x"), Some(hex)) => u64::from_str_radix(hex, 16)
+                .ok()
+                .and_then(|v| v.to_string().parse().ok()),
+            _ => value.parse().ok(),
+        };
+        parsed.unwrap_or_else(|| {
+            self.report(
+                position,
+                format!(
+                    "invalid value `{}` for attribute `{}`",
+                    value, attr.name.local_name
+                ),
+            );
+            T::default()
+        })
+    }
+
+    fn check_name(&mut self, position: Position, name: &str, tag: &str) {
+        if name.is_empty() {
+            self.report(position, format!("missing attribute `name` on `<{}>`", tag));
         }
     }
 
-    interface
-}
+    fn parse_protocol(&mut self) -> Result<Protocol, Diagnostic> {
+        let mut protocol = loop {
+            match self.next()? {
+                XmlEvent::StartDocument { .. } => {}
+                XmlEvent::StartElement { name, attributes, .. } => {
+                    if name.local_name != "protocol" {
+                        return Err(Diagnostic::new(
+                            self.position(),
+                            format!("expected `<protocol>`, found `<{}>`", name.local_name),
+                        ));
+                    }
+                    let position = self.position();
+                    let name = attributes
+                        .into_iter()
+                        .find(|attr| attr.name.local_name == "name")
+                        .map(|attr| attr.value)
+                        .unwrap_or_default();
+                    self.check_name(position, &name, "protocol");
+                    break Protocol::new(name);
+                }
+                e => {
+                    return Err(Diagnostic::new(
+                        self.position(),
+                        format!("expected `<protocol>`, found {}", describe(&e)),
+                    ))
+                }
+            }
+        };
 
-fn parse_description<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> (String, String) {
-    let mut summary = String::new();
-    for attr in attrs {
-        if &attr.name.local_name[..] == "summary" {
-            summary = attr.value.split_whitespace().collect::<Vec<_>>().join(" ");
+        loop {
+            match self.next()? {
+                XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                    "copyright" => protocol.copyright = Some(self.parse_text("copyright")?),
+                    "interface" => {
+                        let interface = self.parse_interface(attributes)?;
+                        protocol.interfaces.push(interface);
+                    }
+                    "description" => protocol.description = Some(self.parse_description(attributes)?),
+                    _ => self.unexpected(&name.local_name, "protocol")?,
+                },
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
         }
+
+        Ok(protocol)
     }
 
-    let description = match reader.next() {
-        Ok(XmlEvent::Characters(txt)) => {
-            extract_end_tag!(reader => "description");
-            txt
+    fn parse_interface(&mut self, attrs: Vec<OwnedAttribute>) -> Result<Interface, Diagnostic> {
+        let mut interface = Interface::new();
+        interface.position = self.position();
+        for attr in attrs {
+            match &attr.name.local_name[..] {
+                "name" => interface.name = attr.value,
+                "version" => interface.version = self.number(interface.position, &attr),
+                _ => {}
+            }
         }
-        Ok(XmlEvent::EndElement { ref name }) if name.local_name == "description" => String::new(),
-        e => panic!("Ill-formed protocol file: {:?}", e),
-    };
+        self.check_name(interface.position, &interface.name, "interface");
 
-    (summary, description)
-}
-
-fn parse_request<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> Message {
-    let mut request = Message::new();
-    for attr in attrs {
-        match &attr.name.local_name[..] {
-            "name" => request.name = attr.value,
-            "type" => request.typ = Some(parse_type(&attr.value)),
-            "since" => request.since = attr.value.parse().unwrap(),
-            _ => {}
+        loop {
+            match self.next()? {
+                XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                    "description" => interface.description = Some(self.parse_description(attributes)?),
+                    "request" => {
+                        let request = self.parse_message(attributes, "request")?;
+                        interface.requests.push(request);
+                    }
+                    "event" => {
+                        let event = self.parse_message(attributes, "event")?;
+                        interface.events.push(event);
+                    }
+                    "enum" => {
+                        let enu = self.parse_enum(attributes)?;
+                        interface.enums.push(enu);
+                    }
+                    _ => self.unexpected(&name.local_name, "interface")?,
+                },
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
         }
+
+        Ok(interface)
     }
 
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => match &name.local_name[..] {
-                "description" => request.description = Some(parse_description(reader, attributes)),
-                "arg" => request.args.push(parse_arg(reader, attributes)),
-                _ => panic!("Unexpected tocken: `{}`", name.local_name),
-            },
-            Ok(XmlEvent::EndElement { ref name }) if name.local_name == "request" => break,
-            _ => {}
+    // the text contents of an element
+    fn parse_text(&mut self, tag: &str) -> Result<String, Diagnostic> {
+        let mut text = String::new();
+        loop {
+            match self.next()? {
+                XmlEvent::Characters(txt) | XmlEvent::CData(txt) => text.push_str(&txt),
+                XmlEvent::StartElement { name, .. } => self.unexpected(&name.local_name, tag)?,
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
         }
+        Ok(text)
     }
 
-    request
-}
+    fn parse_description(&mut self, attrs: Vec<OwnedAttribute>) -> Result<(String, String), Diagnostic> {
+        let mut summary = String::new();
+        for attr in attrs {
+            if &attr.name.local_name[..] == "summary" {
+                summary = attr.value.split_whitespace().collect::<Vec<_>>().join(" ");
+            }
+        }
+
+        let description = self.parse_text("description")?;
+
+        Ok((summary, description))
+    }
 
-fn parse_enum<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> Enum {
-    let mut enu = Enum::new();
-    for attr in attrs {
-        match &attr.name.local_name[..] {
-            "name" => enu.name = attr.value,
-            "since" => enu.since = attr.value.parse().unwrap(),
-            "bitfield" => {
-                if &attr.value[..] == "true" {
-                    enu.bitfield = true
+    fn parse_message(&mut self, attrs: Vec<OwnedAttribute>, tag: &str) -> Result<Message, Diagnostic> {
+        let mut message = Message::new();
+        message.position = self.position();
+        for attr in attrs {
+            match &attr.name.local_name[..] {
+                "name" => message.name = attr.value,
+                "type" => {
+                    if attr.value == "destructor" {
+                        message.typ = Some(Type::Destructor);
+                    } else {
+                        let position = message.position;
+                        self.report(position, format!("invalid {} type `{}`", tag, attr.value));
+                    }
                 }
+                "since" => message.since = self.number(message.position, &attr),
+                _ => {}
             }
-            _ => {}
         }
-    }
+        self.check_name(message.position, &message.name, tag);
 
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => match &name.local_name[..] {
-                "description" => enu.description = Some(parse_description(reader, attributes)),
-                "entry" => enu.entries.push(parse_entry(reader, attributes)),
-                _ => panic!("Unexpected tocken: `{}`", name.local_name),
-            },
-            Ok(XmlEvent::EndElement { ref name }) if name.local_name == "enum" => break,
-            _ => {}
+        loop {
+            match self.next()? {
+                XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                    "description" => message.description = Some(self.parse_description(attributes)?),
+                    "arg" => {
+                        let arg = self.parse_arg(attributes)?;
+                        message.args.push(arg);
+                    }
+                    _ => self.unexpected(&name.local_name, tag)?,
+                },
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
         }
-    }
 
-    enu
-}
+        Ok(message)
+    }
 
-fn parse_event<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> Message {
-    let mut event = Message::new();
-    for attr in attrs {
-        match &attr.name.local_name[..] {
-            "name" => event.name = attr.value,
-            "since" => event.since = attr.value.parse().unwrap(),
-            _ => {}
+    fn parse_enum(&mut self, attrs: Vec<OwnedAttribute>) -> Result<Enum, Diagnostic> {
+        let mut enu = Enum::new();
+        enu.position = self.position();
+        for attr in attrs {
+            match &attr.name.local_name[..] {
+                "name" => enu.name = attr.value,
+                "since" => enu.since = self.number(enu.position, &attr),
+                "bitfield" => {
+                    if &attr.value[..] == "true" {
+                        enu.bitfield = true
+                    }
+                }
+                _ => {}
+            }
         }
-    }
+        self.check_name(enu.position, &enu.name, "enum");
 
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => match &name.local_name[..] {
-                "description" => event.description = Some(parse_description(reader, attributes)),
-                "arg" => event.args.push(parse_arg(reader, attributes)),
-                _ => panic!("Unexpected tocken: `{}`", name.local_name),
-            },
-            Ok(XmlEvent::EndElement { ref name }) if name.local_name == "event" => break,
-            _ => {}
+        loop {
+            match self.next()? {
+                XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                    "description" => enu.description = Some(self.parse_description(attributes)?),
+                    "entry" => {
+                        let entry = self.parse_entry(attributes)?;
+                        enu.entries.push(entry);
+                    }
+                    _ => self.unexpected(&name.local_name, "enum")?,
+                },
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
         }
-    }
 
-    event
-}
+        Ok(enu)
+    }
 
-fn parse_arg<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> Arg {
-    let mut arg = Arg::new();
-    for attr in attrs {
-        match &attr.name.local_name[..] {
-            "name" => arg.name = attr.value,
-            "type" => arg.typ = parse_type(&attr.value),
-            "summary" => arg.summary = Some(attr.value.split_whitespace().collect::<Vec<_>>().join(" ")),
-            "interface" => arg.interface = Some(attr.value),
-            "allow-null" => {
-                if attr.value == "true" {
-                    arg.allow_null = true
+    fn parse_arg(&mut self, attrs: Vec<OwnedAttribute>) -> Result<Arg, Diagnostic> {
+        let mut arg = Arg::new();
+        arg.position = self.position();
+        let mut has_type = false;
+        for attr in attrs {
+            match &attr.name.local_name[..] {
+                "name" => arg.name = attr.value,
+                "type" => {
+                    has_type = true;
+                    match parse_type(&attr.value) {
+                        Some(typ) => arg.typ = typ,
+                        None => {
+                            let position = arg.position;
+                            self.report(position, format!("invalid argument type `{}`", attr.value));
+                        }
+                    }
                 }
+                "summary" => arg.summary = Some(attr.value.split_whitespace().collect::<Vec<_>>().join(" ")),
+                "interface" => arg.interface = Some(attr.value),
+                "allow-null" => {
+                    if attr.value == "true" {
+                        arg.allow_null = true
+                    }
+                }
+                "enum" => arg.enum_ = Some(attr.value),
+                _ => {}
             }
-            "enum" => arg.enum_ = Some(attr.value),
-            _ => {}
         }
-    }
-
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => match &name.local_name[..] {
-                "description" => arg.description = Some(parse_description(reader, attributes)),
-                _ => panic!("Unexpected tocken: `{}`", name.local_name),
-            },
-            Ok(XmlEvent::EndElement { ref name }) if name.local_name == "arg" => break,
-            _ => {}
+        self.check_name(arg.position, &arg.name, "arg");
+        if !has_type {
+            let position = arg.position;
+            self.report(position, "missing attribute `type` on `<arg>`".into());
         }
-    }
 
-    arg
-}
+        loop {
+            match self.next()? {
+                XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                    "description" => arg.description = Some(self.parse_description(attributes)?),
+                    _ => self.unexpected(&name.local_name, "arg")?,
+                },
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
+        }
 
-fn parse_type(txt: &str) -> Type {
-    match txt {
-        "int" => Type::Int,
-        "uint" => Type::Uint,
-        "fixed" => Type::Fixed,
-        "string" => Type::String,
-        "object" => Type::Object,
-        "new_id" => Type::NewId,
-        "array" => Type::Array,
-        "fd" => Type::Fd,
-        "destructor" => Type::Destructor,
-        e => panic!("Unexpected type: {}", e),
+        Ok(arg)
     }
-}
 
-fn parse_entry<R: Read>(reader: &mut EventReader<R>, attrs: Vec<OwnedAttribute>) -> Entry {
-    let mut entry = Entry::new();
-    for attr in attrs {
-        match &attr.name.local_name[..] {
-            "name" => entry.name = attr.value,
-            "value" => {
-                entry.value = if attr.value.starts_with("0x") {
-                    u32::from_str_radix(&attr.value[2..], 16).unwrap()
-                } else {
-                    attr.value.parse().unwrap()
-                };
+    fn parse_entry(&mut self, attrs: Vec<OwnedAttribute>) -> Result<Entry, Diagnostic> {
+        let mut entry = Entry::new();
+        entry.position = self.position();
+        for attr in attrs {
+            match &attr.name.local_name[..] {
+                "name" => entry.name = attr.value,
+                "value" => entry.value = self.number(entry.position, &attr),
+                "since" => entry.since = self.number(entry.position, &attr),
+                "summary" => {
+                    entry.summary = Some(attr.value.split_whitespace().collect::<Vec<_>>().join(" "))
+                }
+                _ => {}
             }
-            "since" => entry.since = attr.value.parse().unwrap(),
-            "summary" => entry.summary = Some(attr.value.split_whitespace().collect::<Vec<_>>().join(" ")),
-            _ => {}
         }
-    }
+        self.check_name(entry.position, &entry.name, "entry");
 
-    loop {
-        match reader.next() {
-            Ok(XmlEvent::StartElement { name, attributes, .. }) => match &name.local_name[..] {
-                "description" => entry.description = Some(parse_description(reader, attributes)),
-                _ => panic!("Unexpected tocken: `{}`", name.local_name),
-            },
-            Ok(XmlEvent::EndElement { ref name }) if name.local_name == "entry" => break,
-            _ => {}
+        loop {
+            match self.next()? {
+                XmlEvent::StartElement { name, attributes, .. } => match &name.local_name[..] {
+                    "description" => entry.description = Some(self.parse_description(attributes)?),
+                    _ => self.unexpected(&name.local_name, "entry")?,
+                },
+                XmlEvent::EndElement { .. } => break,
+                _ => {}
+            }
         }
+
+        Ok(entry)
     }
+}
 
-    entry
+fn parse_type(txt: &str) -> Option<Type> {
+    match txt {
+        "int" => Some(Type::Int),
+        "uint" => Some(Type::Uint),
+        "fixed" => Some(Type::Fixed),
+        "string" => Some(Type::String),
+        "object" => Some(Type::Object),
+        "new_id" => Some(Type::NewId),
+        "array" => Some(Type::Array),
+        "fd" => Some(Type::Fd),
+        _ => None,
+    }
 }
@@ -1,6 +1,13 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
+/// A position in a protocol file, starting at 1
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub line: u64,
+    pub column: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct Protocol {
     pub name: String,
@@ -23,6 +30,7 @@ impl Protocol {
 #[derive(Clone, Debug)]
 pub struct Interface {
     pub name: String,
+    pub position: Position,
     pub version: u32,
     pub description: Option<(String, String)>,
     pub requests: Vec<Message>,
@@ -34,6 +42,7 @@ impl Interface {
     pub fn new() -> Interface {
         Interface {
             name: String::new(),
+            position: Position::default(),
             version: 1,
             description: None,
             requests: Vec::new(),
@@ -46,6 +55,7 @@ impl Interface {
 #[derive(Clone, Debug)]
 pub struct Message {
     pub name: String,
+    pub position: Position,
     pub typ: Option<Type>,
     pub since: u32,
     pub description: Option<(String, String)>,
@@ -57,6 +67,7 @@ impl Message {
     pub fn new() -> Message {
         Message {
             name: String::new(),
+            position: Position::default(),
             typ: None,
             since: 1,
             description: None,
@@ -75,6 +86,7 @@ impl Message {
 #[derive(Clone, Debug)]
 pub struct Arg {
     pub name: String,
+    pub position: Position,
     pub typ: Type,
     pub interface: Option<String>,
     pub summary: Option<String>,
@@ -87,6 +99,7 @@ impl Arg {
     pub fn new() -> Arg {
         Arg {
             name: String::new(),
+            position: Position::default(),
             typ: Type::Object,
             interface: None,
             summary: None,
@@ -100,6 +113,7 @@ impl Arg {
 #[derive(Clone, Debug)]
 pub struct Enum {
     pub name: String,
+    pub position: Position,
     pub since: u16,
     pub description: Option<(String, String)>,
     pub entries: Vec<Entry>,
@@ -110,6 +124,7 @@ impl Enum {
     pub fn new() -> Enum {
         Enum {
             name: String::new(),
+            position: Position::default(),
             since: 1,
             description: None,
             entries: Vec::new(),
@@ -121,6 +136,7 @@ impl Enum {
 #[derive(Clone, Debug)]
 pub struct Entry {
     pub name: String,
+    pub position: Position,
     pub value: u32,
     pub since: u16,
     pub description: Option<(String, String)>,
@@ -131,6 +147,7 @@ impl Entry {
     pub fn new() -> Entry {
         Entry {
             name: String::new(),
+            position: Position::default(),
             value: 0,
             since: 1,
             description: None,
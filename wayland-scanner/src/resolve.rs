@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::protocol::*;
+use crate::validate::{Diagnostic, ProtocolError};
+use crate::{Error, Side};
+
+const CORE_INTERFACES: &[&str] = &[
+    "wl_display",
+    "wl_registry",
+    "wl_callback",
+    "wl_compositor",
+    "wl_shm_pool",
+    "wl_shm",
+    "wl_buffer",
+    "wl_data_offer",
+    "wl_data_source",
+    "wl_data_device",
+    "wl_data_device_manager",
+    "wl_shell",
+    "wl_shell_surface",
+    "wl_surface",
+    "wl_seat",
+    "wl_pointer",
+    "wl_keyboard",
+    "wl_touch",
+    "wl_output",
+    "wl_region",
+    "wl_subcompositor",
+    "wl_subsurface",
+];
+
+/// The rust paths of the interfaces of other protocols
+///
+/// The code generated for a protocol refers to the interfaces of other protocols (like
+/// `wl_surface`) through the modules of these interfaces. Given their paths, the scanner
+/// imports these modules itself rather than relying on the code including it.
+///
+/// The paths are the ones of the modules of the interfaces, as seen from the generated code,
+/// for example `wayland_client::protocol::wl_surface` or `crate::xdg_shell::client::xdg_surface`.
+#[derive(Clone, Debug, Default)]
+pub struct InterfacePaths {
+    paths: HashMap<String, String>,
+}
+
+impl InterfacePaths {
+    /// An empty set of paths
+    pub fn new() -> InterfacePaths {
+        InterfacePaths::default()
+    }
+
+    /// The paths of the interfaces of the core protocol
+    ///
+    /// They are the modules of `wayland_client::protocol` or `wayland_server::protocol`,
+    /// depending on the side.
+    pub fn core(side: Side) -> InterfacePaths {
+        let (krate, interfaces) = match side {
+            Side::Client => ("wayland_client", CORE_INTERFACES),
+            // display and registry are handled specially by the server
+            Side::Server => ("wayland_server", &CORE_INTERFACES[2..]),
+        };
+        let mut paths = InterfacePaths::new();
+        for interface in interfaces {
+            paths.insert(interface, &format!("{}::protocol::{}", krate, interface));
+        }
+        paths
+    }
+
+    /// Set the path of the module of an interface
+    ///
+    /// This replaces any previous path of this interface.
+    pub fn insert(&mut self, interface: &str, path: &str) {
+        self.paths.insert(interface.into(), path.into());
+    }
+
+    /// The path of the module of an interface, if known
+    pub fn get(&self, interface: &str) -> Option<&str> {
+        self.paths.get(interface).map(|p| &p[..])
+    }
+
+    /// Add the interfaces of a protocol file
+    ///
+    /// The modules of the interfaces are expected in given module, for example
+    /// `crate::xdg_shell::client`. The interfaces that already have a path keep it.
+    pub fn insert_protocol<P: AsRef<Path>>(&mut self, protocol: P, module: &str) -> Result<(), Error> {
+        let file = File::open(protocol.as_ref())?;
+        self.insert_protocol_stream(file, module)
+            .map_err(|e| e.with_file(protocol.as_ref()).into())
+    }
+
+    /// Add the interfaces of a protocol from an IO stream
+    ///
+    /// Like `insert_protocol`, but takes an IO stream rather than a filename.
+    pub fn insert_protocol_stream<R: Read>(
+        &mut self,
+        protocol: R,
+        module: &str,
+    ) -> Result<(), ProtocolError> {
+        let protocol = crate::load_protocol(protocol)?;
+        for interface in protocol.interfaces {
+            let path = format!("{}::{}", module, interface.name);
+            self.paths.entry(interface.name).or_insert(path);
+        }
+        Ok(())
+    }
+}
+
+/// The imports of the interfaces of other protocols a protocol refers to
+pub(crate) fn imports(protocol: &Protocol, paths: &InterfacePaths) -> Result<TokenStream, ProtocolError> {
+    // the referenced interfaces, with the position of their first reference
+    let mut referenced = BTreeMap::new();
+    for interface in &protocol.interfaces {
+        for msg in interface.requests.iter().chain(&interface.events) {
+            for arg in &msg.args {
+                let enum_interface = arg
+                    .enum_
+                    .as_ref()
+                    .and_then(|e| e.find('.').map(|i| e[..i].to_owned()));
+                for name in arg.interface.iter().cloned().chain(enum_interface) {
+                    referenced.entry(name).or_insert(arg.position);
+                }
+            }
+        }
+    }
+
+    let mut imports = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (name, position) in referenced {
+        if protocol.interfaces.iter().any(|i| i.name == name) {
+            continue;
+        }
+        let path = match paths.get(&name) {
+            Some(path) => path,
+            None => {
+                diagnostics.push(Diagnostic::new(
+                    position,
+                    format!(
+                        "interface `{}` is not defined by this protocol, and its path is unknown",
+                        name
+                    ),
+                ));
+                continue;
+            }
+        };
+        match TokenStream::from_str(path) {
+            Ok(tokens) if is_path(path) => {
+                let ident = Ident::new(&name, Span::call_site());
+                imports.push(quote!(use #tokens as #ident;));
+            }
+            _ => diagnostics.push(Diagnostic::new(
+                position,
+                format!("invalid path `{}` for interface `{}`", path, name),
+            )),
+        }
+    }
+
+    if diagnostics.is_empty() {
+        Ok(quote!(#(#imports)*))
+    } else {
+        diagnostics.sort_by_key(|d| (d.line, d.column));
+        Err(ProtocolError { diagnostics })
+    }
+}
+
+fn is_path(path: &str) -> bool {
+    path.split("::").enumerate().all(|(i, segment)| {
+        let mut chars = segment.chars();
+        match chars.next() {
+            // a leading `::`
+            None => i == 0 && path.len() > 2,
+            Some(c) => {
+                (c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+        }
+    })
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::protocol::*;
+
+/// A problem found in a protocol file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The protocol file, if it was read from a file
+    pub file: Option<PathBuf>,
+    /// The line of the problem, starting at 1
+    pub line: u64,
+    /// The column of the problem, starting at 1
+    pub column: u64,
+    /// A description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(position: Position, message: String) -> Diagnostic {
+        Diagnostic {
+            file: None,
+            line: position.line,
+            column: position.column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref file) = self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// The problems found in a protocol file
+///
+/// A malformed XML file stops the parsing at the first error, while all the
+/// semantic errors of a well-formed file are reported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolError {
+    /// The problems found, in the order of the file
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ProtocolError {
+    pub(crate) fn with_file(mut self, file: &Path) -> ProtocolError {
+        for diagnostic in &mut self.diagnostics {
+            diagnostic.file = Some(file.to_owned());
+        }
+        self
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl ::std::error::Error for ProtocolError {}
+
+/// Check the semantic consistency of a protocol
+pub(crate) fn validate_protocol(protocol: &Protocol) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_duplicates(
+        protocol.interfaces.iter().map(|i| (&i.name[..], i.position)),
+        &format!("interface of protocol `{}`", protocol.name),
+        &mut diagnostics,
+    );
+    for interface in &protocol.interfaces {
+        validate_interface(protocol, interface, &mut diagnostics);
+    }
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+fn validate_interface(protocol: &Protocol, interface: &Interface, diagnostics: &mut Vec<Diagnostic>) {
+    let iname = &interface.name;
+    check_duplicates(
+        interface.requests.iter().map(|r| (&r.name[..], r.position)),
+        &format!("request of `{}`", iname),
+        diagnostics,
+    );
+    check_duplicates(
+        interface.events.iter().map(|e| (&e.name[..], e.position)),
+        &format!("event of `{}`", iname),
+        diagnostics,
+    );
+    check_duplicates(
+        interface.enums.iter().map(|e| (&e.name[..], e.position)),
+        &format!("enum of `{}`", iname),
+        diagnostics,
+    );
+
+    for &(kind, messages) in &[("request", &interface.requests), ("event", &interface.events)] {
+        // the messages are expected in the order they were added to the interface
+        let mut previous_since = 1;
+        for msg in messages {
+            if msg.since > interface.version {
+                diagnostics.push(Diagnostic::new(
+                    msg.position,
+                    format!(
+                        "{} `{}.{}` is available since version {}, but `{}` only has version {}",
+                        kind, iname, msg.name, msg.since, iname, interface.version
+                    ),
+                ));
+            } else if msg.since < previous_since {
+                diagnostics.push(Diagnostic::new(
+                    msg.position,
+                    format!(
+                        "{} `{}.{}` is available since version {}, lower than the version {} of a previous {}",
+                        kind, iname, msg.name, msg.since, previous_since, kind
+                    ),
+                ));
+            }
+            if msg.since <= interface.version {
+                previous_since = previous_since.max(msg.since);
+            }
+            validate_message(protocol, interface, kind, msg, diagnostics);
+        }
+    }
+
+    for enu in &interface.enums {
+        if u32::from(enu.since) > interface.version {
+            diagnostics.push(Diagnostic::new(
+                enu.position,
+                format!(
+                    "enum `{}.{}` is available since version {}, but `{}` only has version {}",
+                    iname, enu.name, enu.since, iname, interface.version
+                ),
+            ));
+        }
+        check_duplicates(
+            enu.entries.iter().map(|e| (&e.name[..], e.position)),
+            &format!("entry of `{}.{}`", iname, enu.name),
+            diagnostics,
+        );
+        for entry in &enu.entries {
+            if u32::from(entry.since) > interface.version {
+                diagnostics.push(Diagnostic::new(
+                    entry.position,
+                    format!(
+                        "entry `{}.{}.{}` is available since version {}, but `{}` only has version {}",
+                        iname, enu.name, entry.name, entry.since, iname, interface.version
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn validate_message(
+    protocol: &Protocol,
+    interface: &Interface,
+    kind: &str,
+    msg: &Message,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let name = format!("{}.{}", interface.name, msg.name);
+    check_duplicates(
+        msg.args.iter().map(|a| (&a.name[..], a.position)),
+        &format!("argument of `{}`", name),
+        diagnostics,
+    );
+
+    let mut new_ids = msg.args.iter().filter(|arg| arg.typ == Type::NewId);
+    if let Some(arg) = new_ids.nth(1) {
+        diagnostics.push(Diagnostic::new(
+            arg.position,
+            format!("{} `{}` creates more than one object", kind, name),
+        ));
+    }
+
+    for arg in &msg.args {
+        if arg.typ == Type::NewId && arg.interface.is_none() && kind != "request" {
+            diagnostics.push(Diagnostic::new(
+                arg.position,
+                format!(
+                    "argument `{}` of {} `{}` is a `new_id` without an interface, which is only \
+                     supported in requests like `wl_registry.bind`",
+                    arg.name, kind, name
+                ),
+            ));
+        }
+        if let Some(ref enum_) = arg.enum_ {
+            if arg.typ != Type::Int && arg.typ != Type::Uint {
+                diagnostics.push(Diagnostic::new(
+                    arg.position,
+                    format!(
+                        "argument `{}` of `{}` references enum `{}`, but is not an `int` or `uint`",
+                        arg.name, name, enum_
+                    ),
+                ));
+            }
+            let (iface_name, enum_name) = match enum_.find('.') {
+                Some(i) => (&enum_[..i], &enum_[i + 1..]),
+                None => (&interface.name[..], &enum_[..]),
+            };
+            // the enums of the interfaces of other protocols cannot be checked
+            let target = protocol.interfaces.iter().find(|i| i.name == iface_name);
+            if let Some(target) = target {
+                if !target.enums.iter().any(|e| e.name == enum_name) {
+                    diagnostics.push(Diagnostic::new(
+                        arg.position,
+                        format!(
+                            "argument `{}` of `{}` references enum `{}`, which does not exist",
+                            arg.name, name, enum_
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn check_duplicates<'a, I>(items: I, what: &str, diagnostics: &mut Vec<Diagnostic>)
+where
+    I: Iterator<Item = (&'a str, Position)>,
+{
+    let mut seen = HashMap::new();
+    for (name, position) in items {
+        if let Some(first) = seen.insert(name, position) {
+            diagnostics.push(Diagnostic::new(
+                position,
+                format!(
+                    "duplicate {} `{}`, first defined at {}:{}",
+                    what, name, first.line, first.column
+                ),
+            ));
+            // report the next duplicates relative to the first definition
+            seen.insert(name, first);
+        }
+    }
+}
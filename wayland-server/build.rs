@@ -11,10 +11,12 @@ fn main() {
     let out_dir = Path::new(&out_dir_str);
 
     println!("cargo:rerun-if-changed={}", protocol_file);
-    generate_code_with_destructor_events(
-        protocol_file,
-        out_dir.join("wayland_api.rs"),
-        Side::Server,
-        &[("wl_callback", "done")],
-    );
+    let result = Scanner::new(protocol_file, Side::Server)
+        .destructor_events(&[("wl_callback", "done")])
+        .handlers(true)
+        .output(out_dir.join("wayland_api.rs"))
+        .generate();
+    if let Err(e) = result {
+        panic!("{}", e);
+    }
 }
@@ -5,7 +5,7 @@ use wayland_sys::server::wl_client;
 
 use crate::imp::ClientInner;
 
-use crate::{Interface, Main, Resource, UserDataMap};
+use crate::{AnonymousObject, Interface, Main, ObjectTreeSnapshot, Resource, UserDataMap};
 
 /// A handle to a client connected to your server
 ///
@@ -95,4 +95,37 @@ impl Client {
     ) -> Option<Main<I>> {
         self.inner.create_resource::<I>(version).map(Main::wrap)
     }
+
+    /// Iterate over the resources currently owned by this client
+    ///
+    /// The resources are given as anonymous handles, their interface can be retrieved
+    /// with `Resource::interface_name()`. Use `get_resource()` to retrieve a typed handle
+    /// to one of them.
+    ///
+    /// The returned handles are a snapshot of the resources of the client at the time
+    /// of this call. If the client is dead, the iterator is empty.
+    pub fn resources(&self) -> impl Iterator<Item = Resource<AnonymousObject>> {
+        self.inner.resources().into_iter().map(Resource::wrap)
+    }
+
+    /// Retrieve a resource of this client from its id
+    ///
+    /// Returns `None` if this client has no object with this id, or if it is not
+    /// of interface `I`.
+    pub fn get_resource<I: Interface + From<Resource<I>> + AsRef<Resource<I>>>(&self, id: u32) -> Option<I> {
+        self.inner
+            .get_resource::<I>(id)
+            .map(|inner| Resource::wrap(inner).into())
+    }
+
+    /// Take a snapshot of the protocol objects currently owned by this client
+    ///
+    /// This is intended as a debugging tool, for example to track leaked objects by
+    /// comparing periodic snapshots with `ObjectTreeSnapshot::diff()`.
+    ///
+    /// When using the system libwayland, the message that created each object is not
+    /// known, and objects not managed by this crate have no age nor user data.
+    pub fn snapshot_objects(&self) -> ObjectTreeSnapshot {
+        self.inner.snapshot_objects()
+    }
 }
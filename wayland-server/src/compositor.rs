@@ -0,0 +1,710 @@
+//! Utilities for handling the surfaces of your clients
+//!
+//! This module provides a ready-to-use implementation of the `wl_compositor` and
+//! `wl_subcompositor` globals, which you can insert in your `Display` with
+//! `init_compositor()`. It implements the core requests of `wl_surface`, `wl_subsurface`
+//! and `wl_region`, tracking the state of the surfaces for you.
+//!
+//! ## Double-buffered state
+//!
+//! The state of a `wl_surface` is double-buffered: the requests of the client modify
+//! its pending state, which only becomes the current state of the surface when the client
+//! commits it. The state of synchronized subsurfaces is further cached on commit, and only
+//! applied when their parent state is applied.
+//!
+//! This module tracks these different states for the core state of the surfaces (see
+//! `SurfaceAttributes`), as well as for any additional state you attach to them. Extensions
+//! can define their own double-buffered state by implementing the `Cacheable` trait,
+//! and access it with the `pending()` and `current()` methods of the `SurfaceStates`
+//! of a surface (see `surface_states()`).
+//!
+//! Whenever the state of a surface is applied, the commit hook you provided to
+//! `init_compositor()` is called with this surface.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! # use wayland_server::Display;
+//! use wayland_server::compositor::{init_compositor, surface_states, SurfaceAttributes};
+//!
+//! # let mut display = Display::new();
+//! let (compositor_global, subcompositor_global) = init_compositor(&mut display, |surface| {
+//!     let states = surface_states(surface).unwrap();
+//!     let attributes = states.current::<SurfaceAttributes>();
+//!     // schedule a redraw of this surface...
+//! });
+//! ```
+
+use std::cell::{Cell, RefCell, RefMut};
+use std::rc::Rc;
+
+use wayland_commons::user_data::UserDataMap;
+
+use crate::protocol::wl_buffer::WlBuffer;
+use crate::protocol::wl_callback::WlCallback;
+use crate::protocol::wl_compositor::{self, WlCompositor};
+use crate::protocol::wl_output::Transform;
+use crate::protocol::wl_subcompositor::{self, WlSubcompositor};
+use crate::protocol::wl_subsurface::{self, WlSubsurface};
+use crate::protocol::wl_surface::{self, WlSurface};
+use crate::region::{get_region, implement_region, Rectangle, Region};
+use crate::{Display, Filter, Global, Main};
+
+type CommitHook = Rc<RefCell<dyn FnMut(&WlSurface)>>;
+
+/// Create the `wl_compositor` and `wl_subcompositor` globals in this display
+///
+/// The `commit_hook` is called with a surface whenever its state is applied, which
+/// happens when the client commits it, or, for synchronized subsurfaces, when the state
+/// of their parent is applied. In the latter case, the hook is called for the subsurfaces
+/// before their parent.
+///
+/// The returned global handles can be used to remove the globals.
+pub fn init_compositor<F>(
+    display: &mut Display,
+    commit_hook: F,
+) -> (Global<WlCompositor>, Global<WlSubcompositor>)
+where
+    F: FnMut(&WlSurface) + 'static,
+{
+    let hook: CommitHook = Rc::new(RefCell::new(commit_hook));
+
+    let compositor_hook = hook.clone();
+    let compositor = display.create_global::<WlCompositor, _>(4, move |compositor, _version| {
+        let hook = compositor_hook.clone();
+        compositor.assign_mono(move |_, request| match request {
+            wl_compositor::Request::CreateSurface { id } => implement_surface(id, hook.clone()),
+            wl_compositor::Request::CreateRegion { id } => implement_region(id),
+            _ => unreachable!(),
+        });
+    });
+
+    let subcompositor = display.create_global::<WlSubcompositor, _>(1, move |subcompositor, _version| {
+        let hook = hook.clone();
+        subcompositor.assign_mono(move |subcompositor, request| {
+            if let wl_subcompositor::Request::GetSubsurface { id, surface, parent } = request {
+                implement_subsurface(&subcompositor, id, surface, parent, hook.clone());
+            }
+        });
+    });
+
+    (compositor, subcompositor)
+}
+
+/*
+ * Double-buffered state
+ */
+
+/// A double-buffered state of surfaces
+///
+/// The state of a surface exists in three versions: the pending state, modified by the
+/// requests of the client, the cached state, holding the state committed by synchronized
+/// subsurfaces until their parent is applied, and the current state, used by the compositor.
+/// The two methods of this trait move the state along these versions.
+///
+/// The pending state of a surface starts with the `Default` value of this type.
+pub trait Cacheable: Default + 'static {
+    /// Extract the state to apply from the pending state, on commit
+    ///
+    /// The pending state should be left with what it is supposed to contain after a
+    /// commit: accumulated state (like damage) should be taken from it, while the state
+    /// persisting between commits (like the buffer scale) should be copied.
+    fn commit(&mut self) -> Self;
+    /// Apply this committed state on top of an older one
+    ///
+    /// This is used to merge successive commits of a synchronized subsurface in its cached
+    /// state, and to apply the cached state of a surface to its current state.
+    fn merge_into(self, into: &mut Self);
+}
+
+struct CachedState<T> {
+    pending: RefCell<T>,
+    cached: RefCell<Option<T>>,
+    current: RefCell<T>,
+}
+
+// type-erased operations on the cached states, to process them all regardless of their type
+trait AnyCache {
+    fn commit_to_cache(&self);
+    fn has_cached(&self) -> bool;
+    fn apply_cache(&self);
+}
+
+impl<T: Cacheable> AnyCache for CachedState<T> {
+    fn commit_to_cache(&self) {
+        let state = self.pending.borrow_mut().commit();
+        let mut cached = self.cached.borrow_mut();
+        match *cached {
+            Some(ref mut cached) => state.merge_into(cached),
+            None => *cached = Some(state),
+        }
+    }
+
+    fn has_cached(&self) -> bool {
+        self.cached.borrow().is_some()
+    }
+
+    fn apply_cache(&self) {
+        let cached = self.cached.borrow_mut().take();
+        if let Some(state) = cached {
+            state.merge_into(&mut self.current.borrow_mut());
+        }
+    }
+}
+
+/// The double-buffered states of a surface
+///
+/// A state is attached to the surface the first time it is accessed.
+pub struct SurfaceStates {
+    map: UserDataMap,
+    caches: RefCell<Vec<Rc<dyn AnyCache>>>,
+}
+
+impl SurfaceStates {
+    fn new() -> SurfaceStates {
+        SurfaceStates {
+            map: UserDataMap::new(),
+            caches: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn cache<T: Cacheable>(&self) -> &CachedState<T> {
+        let inserted = self.map.insert_if_missing(|| {
+            Rc::new(CachedState {
+                pending: RefCell::new(T::default()),
+                cached: RefCell::new(None),
+                current: RefCell::new(T::default()),
+            })
+        });
+        let cache = self.map.get::<Rc<CachedState<T>>>().unwrap();
+        if inserted {
+            self.caches.borrow_mut().push(cache.clone());
+        }
+        cache
+    }
+
+    /// Access the pending version of a state of this surface
+    ///
+    /// Panics if this state is already borrowed.
+    pub fn pending<T: Cacheable>(&self) -> RefMut<'_, T> {
+        self.cache::<T>().pending.borrow_mut()
+    }
+
+    /// Access the current version of a state of this surface
+    ///
+    /// Panics if this state is already borrowed.
+    pub fn current<T: Cacheable>(&self) -> RefMut<'_, T> {
+        self.cache::<T>().current.borrow_mut()
+    }
+
+    fn caches(&self) -> Vec<Rc<dyn AnyCache>> {
+        self.caches.borrow().clone()
+    }
+
+    fn commit_to_cache(&self) {
+        for cache in self.caches() {
+            cache.commit_to_cache();
+        }
+    }
+
+    fn has_cached(&self) -> bool {
+        self.caches().iter().any(|cache| cache.has_cached())
+    }
+
+    fn apply_cache(&self) {
+        for cache in self.caches() {
+            cache.apply_cache();
+        }
+    }
+}
+
+/// Access the double-buffered states of a surface
+///
+/// Returns `None` if this surface was not created by the `wl_compositor` of this module.
+pub fn surface_states(surface: &WlSurface) -> Option<&SurfaceStates> {
+    surface
+        .as_ref()
+        .user_data()
+        .get::<SurfaceData>()
+        .map(|data| &data.states)
+}
+
+/*
+ * Core surface state
+ */
+
+/// A change of the buffer of a surface
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub enum BufferAssignment {
+    /// The surface no longer has a buffer
+    Removed,
+    /// A new buffer was attached to the surface
+    NewBuffer {
+        /// The buffer
+        buffer: WlBuffer,
+        /// Location of the new buffer relative to the previous one, in surface coordinates
+        delta: (i32, i32),
+    },
+}
+
+/// A damaged area of a surface
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Damage {
+    /// A rectangle in surface coordinates
+    Surface(Rectangle),
+    /// A rectangle in buffer coordinates
+    Buffer(Rectangle),
+}
+
+/// The core double-buffered state of a surface
+pub struct SurfaceAttributes {
+    /// Change of the buffer of the surface
+    ///
+    /// In the pending state, `None` means that the buffer is not changed by the next commit.
+    /// In the current state, it holds the last committed change not yet processed by the
+    /// compositor: you should `take()` it when processing it, and send the `release` event
+    /// to the previous buffer once you no longer use it.
+    pub buffer: Option<BufferAssignment>,
+    /// Scale of the buffer
+    pub buffer_scale: i32,
+    /// Transform of the buffer
+    pub buffer_transform: Transform,
+    /// Opaque region of the surface, `None` meaning it is empty
+    pub opaque_region: Option<Region>,
+    /// Input region of the surface, `None` meaning it is infinite
+    pub input_region: Option<Region>,
+    /// Damage of the surface
+    ///
+    /// Damage accumulates in the current state until you clear it, which you should do
+    /// once you redrew the surface.
+    pub damage: Vec<Damage>,
+    /// Frame callbacks of the surface
+    ///
+    /// They accumulate in the current state until you send them the `done` event.
+    pub frame_callbacks: Vec<WlCallback>,
+}
+
+impl Default for SurfaceAttributes {
+    fn default() -> SurfaceAttributes {
+        SurfaceAttributes {
+            buffer: None,
+            buffer_scale: 1,
+            buffer_transform: Transform::Normal,
+            opaque_region: None,
+            input_region: None,
+            damage: Vec::new(),
+            frame_callbacks: Vec::new(),
+        }
+    }
+}
+
+impl Cacheable for SurfaceAttributes {
+    fn commit(&mut self) -> SurfaceAttributes {
+        SurfaceAttributes {
+            buffer: self.buffer.take(),
+            buffer_scale: self.buffer_scale,
+            buffer_transform: self.buffer_transform,
+            opaque_region: self.opaque_region.clone(),
+            input_region: self.input_region.clone(),
+            damage: ::std::mem::replace(&mut self.damage, Vec::new()),
+            frame_callbacks: ::std::mem::replace(&mut self.frame_callbacks, Vec::new()),
+        }
+    }
+
+    fn merge_into(self, into: &mut SurfaceAttributes) {
+        if self.buffer.is_some() {
+            into.buffer = self.buffer;
+        }
+        into.buffer_scale = self.buffer_scale;
+        into.buffer_transform = self.buffer_transform;
+        into.opaque_region = self.opaque_region;
+        into.input_region = self.input_region;
+        into.damage.extend(self.damage);
+        into.frame_callbacks.extend(self.frame_callbacks);
+    }
+}
+
+/*
+ * Roles and subsurfaces
+ */
+
+/// An entry of the stack of subsurfaces of a surface
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, PartialEq, Eq)]
+pub enum StackEntry {
+    /// The surface itself
+    Parent,
+    /// One of its subsurfaces
+    Subsurface(WlSurface),
+}
+
+struct SubsurfaceState {
+    sync: bool,
+    position: (i32, i32),
+    pending_position: (i32, i32),
+}
+
+struct SurfaceData {
+    states: SurfaceStates,
+    role: Cell<Option<&'static str>>,
+    parent: RefCell<Option<WlSurface>>,
+    subsurface: RefCell<Option<SubsurfaceState>>,
+    // the stacking order of the subsurfaces, from bottom to top,
+    // the pending one being applied with the state of the surface
+    stack: RefCell<Vec<StackEntry>>,
+    pending_stack: RefCell<Vec<StackEntry>>,
+}
+
+impl SurfaceData {
+    fn new() -> SurfaceData {
+        SurfaceData {
+            states: SurfaceStates::new(),
+            role: Cell::new(None),
+            parent: RefCell::new(None),
+            subsurface: RefCell::new(None),
+            stack: RefCell::new(vec![StackEntry::Parent]),
+            pending_stack: RefCell::new(vec![StackEntry::Parent]),
+        }
+    }
+
+    fn get(surface: &WlSurface) -> &SurfaceData {
+        surface
+            .as_ref()
+            .user_data()
+            .get::<SurfaceData>()
+            .expect("The surface was not created by this wl_compositor.")
+    }
+}
+
+/// The surface already has a different role
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AlreadyHasRole;
+
+impl ::std::fmt::Display for AlreadyHasRole {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str("The surface already has a different role")
+    }
+}
+
+impl ::std::error::Error for AlreadyHasRole {}
+
+/// Give a role to a surface
+///
+/// A surface can only ever have a single role, this fails if it already has a
+/// different one. The roles are identified by their name, the role of subsurfaces
+/// being `"subsurface"`.
+///
+/// Panics if this surface was not created by the `wl_compositor` of this module.
+pub fn give_role(surface: &WlSurface, role: &'static str) -> Result<(), AlreadyHasRole> {
+    let data = SurfaceData::get(surface);
+    match data.role.get() {
+        Some(current) if current != role => Err(AlreadyHasRole),
+        _ => {
+            data.role.set(Some(role));
+            Ok(())
+        }
+    }
+}
+
+/// Get the role of a surface
+///
+/// Panics if this surface was not created by the `wl_compositor` of this module.
+pub fn get_role(surface: &WlSurface) -> Option<&'static str> {
+    SurfaceData::get(surface).role.get()
+}
+
+/// Get the parent of a subsurface
+///
+/// Panics if this surface was not created by the `wl_compositor` of this module.
+pub fn get_parent(surface: &WlSurface) -> Option<WlSurface> {
+    SurfaceData::get(surface).parent.borrow().clone()
+}
+
+/// Get the current stacking order of the subsurfaces of a surface, from bottom to top
+///
+/// Panics if this surface was not created by the `wl_compositor` of this module.
+pub fn subsurface_stack(surface: &WlSurface) -> Vec<StackEntry> {
+    SurfaceData::get(surface).stack.borrow().clone()
+}
+
+/// Get the current position of a subsurface relative to its parent
+///
+/// Returns `None` if this surface is not a subsurface.
+///
+/// Panics if this surface was not created by the `wl_compositor` of this module.
+pub fn subsurface_position(surface: &WlSurface) -> Option<(i32, i32)> {
+    SurfaceData::get(surface)
+        .subsurface
+        .borrow()
+        .as_ref()
+        .map(|state| state.position)
+}
+
+/// Check if a surface is an effectively synchronized subsurface
+///
+/// This is the case if it is in synchronized mode, or if its parent is an effectively
+/// synchronized subsurface.
+///
+/// Panics if this surface was not created by the `wl_compositor` of this module.
+pub fn is_effectively_sync(surface: &WlSurface) -> bool {
+    let data = SurfaceData::get(surface);
+    if data.subsurface.borrow().as_ref().map(|s| s.sync).unwrap_or(false) {
+        return true;
+    }
+    match *data.parent.borrow() {
+        Some(ref parent) => is_effectively_sync(parent),
+        None => false,
+    }
+}
+
+fn is_ancestor(ancestor: &WlSurface, surface: &WlSurface) -> bool {
+    match get_parent(surface) {
+        Some(ref parent) if parent == ancestor => true,
+        Some(ref parent) => is_ancestor(ancestor, parent),
+        None => false,
+    }
+}
+
+/*
+ * Implementations
+ */
+
+fn commit(surface: &WlSurface, hook: &CommitHook) {
+    let data = SurfaceData::get(surface);
+    data.states.commit_to_cache();
+    if !is_effectively_sync(surface) {
+        apply(surface, hook);
+    }
+}
+
+// apply the cached state of a surface, as well as the one of its synchronized subsurfaces
+fn apply(surface: &WlSurface, hook: &CommitHook) {
+    let data = SurfaceData::get(surface);
+    data.states.apply_cache();
+    let stack = data.pending_stack.borrow().clone();
+    for entry in &stack {
+        if let StackEntry::Subsurface(ref child) = *entry {
+            if let Some(ref mut state) = *SurfaceData::get(child).subsurface.borrow_mut() {
+                state.position = state.pending_position;
+            }
+            if is_effectively_sync(child) {
+                apply(child, hook);
+            }
+        }
+    }
+    *data.stack.borrow_mut() = stack;
+    (*hook.borrow_mut())(surface);
+}
+
+fn implement_surface(surface: Main<WlSurface>, hook: CommitHook) {
+    surface.as_ref().user_data().set(SurfaceData::new);
+    surface.assign_mono(move |surface, request| {
+        let data = SurfaceData::get(&surface);
+        match request {
+            wl_surface::Request::Attach { buffer, x, y } => {
+                data.states.pending::<SurfaceAttributes>().buffer = Some(match buffer {
+                    Some(buffer) => BufferAssignment::NewBuffer {
+                        buffer,
+                        delta: (x, y),
+                    },
+                    None => BufferAssignment::Removed,
+                });
+            }
+            wl_surface::Request::Damage { x, y, width, height } => {
+                data.states
+                    .pending::<SurfaceAttributes>()
+                    .damage
+                    .push(Damage::Surface(Rectangle { x, y, width, height }));
+            }
+            wl_surface::Request::DamageBuffer { x, y, width, height } => {
+                data.states
+                    .pending::<SurfaceAttributes>()
+                    .damage
+                    .push(Damage::Buffer(Rectangle { x, y, width, height }));
+            }
+            wl_surface::Request::Frame { callback } => {
+                callback.assign_mono(|_, _| {});
+                data.states
+                    .pending::<SurfaceAttributes>()
+                    .frame_callbacks
+                    .push((*callback).clone());
+            }
+            wl_surface::Request::SetOpaqueRegion { region } => {
+                data.states.pending::<SurfaceAttributes>().opaque_region =
+                    region.and_then(|region| get_region(&region));
+            }
+            wl_surface::Request::SetInputRegion { region } => {
+                data.states.pending::<SurfaceAttributes>().input_region =
+                    region.and_then(|region| get_region(&region));
+            }
+            wl_surface::Request::Commit => commit(&surface, &hook),
+            wl_surface::Request::SetBufferTransform { transform } => {
+                data.states.pending::<SurfaceAttributes>().buffer_transform = transform;
+            }
+            wl_surface::Request::SetBufferScale { scale } => {
+                if scale < 1 {
+                    surface.as_ref().post_error(
+                        wl_surface::Error::InvalidScale as u32,
+                        "Buffer scale must be at least 1.".into(),
+                    );
+                    return;
+                }
+                data.states.pending::<SurfaceAttributes>().buffer_scale = scale;
+            }
+            wl_surface::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    });
+    surface.assign_destructor(Filter::new(|surface: WlSurface, _| {
+        // unlink the surface from its parent and its children, which also breaks
+        // the reference cycles between them
+        let data = match surface.as_ref().user_data().get::<SurfaceData>() {
+            Some(data) => data,
+            None => return,
+        };
+        remove_subsurface(&surface);
+        for entry in data.stack.borrow_mut().drain(..) {
+            if let StackEntry::Subsurface(child) = entry {
+                if let Some(child_data) = child.as_ref().user_data().get::<SurfaceData>() {
+                    *child_data.parent.borrow_mut() = None;
+                    *child_data.subsurface.borrow_mut() = None;
+                }
+            }
+        }
+        data.pending_stack.borrow_mut().clear();
+    }));
+}
+
+fn implement_subsurface(
+    subcompositor: &WlSubcompositor,
+    subsurface: Main<WlSubsurface>,
+    surface: WlSurface,
+    parent: WlSurface,
+    hook: CommitHook,
+) {
+    let data = SurfaceData::get(&surface);
+    if surface == parent || is_ancestor(&surface, &parent) {
+        subcompositor.as_ref().post_error(
+            wl_subcompositor::Error::BadSurface as u32,
+            "A surface cannot be a subsurface of itself or of its descendants.".into(),
+        );
+        return;
+    }
+    if data.subsurface.borrow().is_some() || give_role(&surface, "subsurface").is_err() {
+        subcompositor.as_ref().post_error(
+            wl_subcompositor::Error::BadSurface as u32,
+            "The surface already has a role.".into(),
+        );
+        return;
+    }
+
+    *data.subsurface.borrow_mut() = Some(SubsurfaceState {
+        sync: true,
+        position: (0, 0),
+        pending_position: (0, 0),
+    });
+    *data.parent.borrow_mut() = Some(parent.clone());
+    // new subsurfaces are placed on top of their parent
+    let parent_data = SurfaceData::get(&parent);
+    let entry = StackEntry::Subsurface(surface.clone());
+    parent_data.stack.borrow_mut().push(entry.clone());
+    parent_data.pending_stack.borrow_mut().push(entry);
+
+    let role_surface = surface.clone();
+    subsurface.assign_mono(move |subsurface, request| {
+        let data = SurfaceData::get(&surface);
+        if data.subsurface.borrow().is_none() {
+            // the parent of this subsurface was destroyed, it no longer does anything
+            return;
+        }
+        match request {
+            wl_subsurface::Request::SetPosition { x, y } => {
+                if let Some(ref mut state) = *data.subsurface.borrow_mut() {
+                    state.pending_position = (x, y);
+                }
+            }
+            wl_subsurface::Request::PlaceAbove { sibling } => {
+                if !place_relative(&surface, &sibling, true) {
+                    subsurface.as_ref().post_error(
+                        wl_subsurface::Error::BadSurface as u32,
+                        "The surface is not a sibling or the parent.".into(),
+                    );
+                }
+            }
+            wl_subsurface::Request::PlaceBelow { sibling } => {
+                if !place_relative(&surface, &sibling, false) {
+                    subsurface.as_ref().post_error(
+                        wl_subsurface::Error::BadSurface as u32,
+                        "The surface is not a sibling or the parent.".into(),
+                    );
+                }
+            }
+            wl_subsurface::Request::SetSync => {
+                if let Some(ref mut state) = *data.subsurface.borrow_mut() {
+                    state.sync = true;
+                }
+            }
+            wl_subsurface::Request::SetDesync => {
+                if let Some(ref mut state) = *data.subsurface.borrow_mut() {
+                    state.sync = false;
+                }
+                // a subsurface switching to desynchronized mode applies its cached state
+                if !is_effectively_sync(&surface) && data.states.has_cached() {
+                    apply(&surface, &hook);
+                }
+            }
+            wl_subsurface::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    });
+    subsurface.assign_destructor(Filter::new(move |_: WlSubsurface, _| {
+        // the surface is unmapped immediately, but keeps its role
+        remove_subsurface(&role_surface);
+    }));
+}
+
+// move a subsurface above or below its sibling, returns false if the sibling is invalid
+fn place_relative(surface: &WlSurface, sibling: &WlSurface, above: bool) -> bool {
+    let parent = match get_parent(surface) {
+        Some(parent) => parent,
+        None => return false,
+    };
+    let sibling_entry = if *sibling == parent {
+        StackEntry::Parent
+    } else {
+        StackEntry::Subsurface(sibling.clone())
+    };
+    let mut stack = SurfaceData::get(&parent).pending_stack.borrow_mut();
+    let entry = StackEntry::Subsurface(surface.clone());
+    if sibling_entry == entry || !stack.contains(&sibling_entry) {
+        return false;
+    }
+    stack.retain(|e| *e != entry);
+    let idx = stack.iter().position(|e| *e == sibling_entry).unwrap();
+    stack.insert(if above { idx + 1 } else { idx }, entry);
+    true
+}
+
+// remove a subsurface from its parent
+fn remove_subsurface(surface: &WlSurface) {
+    let data = match surface.as_ref().user_data().get::<SurfaceData>() {
+        Some(data) => data,
+        None => return,
+    };
+    let parent = data.parent.borrow_mut().take();
+    if let Some(parent_data) = parent
+        .as_ref()
+        .and_then(|p| p.as_ref().user_data().get::<SurfaceData>())
+    {
+        // a destroyed surface no longer compares equal to anything, so the
+        // dead entries are removed as well
+        let entry = StackEntry::Subsurface(surface.clone());
+        let keep = |e: &StackEntry| match *e {
+            StackEntry::Parent => true,
+            StackEntry::Subsurface(ref s) => s.as_ref().is_alive() && *e != entry,
+        };
+        parent_data.stack.borrow_mut().retain(keep);
+        parent_data.pending_stack.borrow_mut().retain(keep);
+    }
+    *data.subsurface.borrow_mut() = None;
+}
@@ -5,13 +5,18 @@ use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 use std::os::unix::io::{IntoRawFd, RawFd};
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use nix::poll::{poll, PollFd, PollFlags};
 
 #[cfg(feature = "use_system_lib")]
 use wayland_sys::server::wl_display;
 
 use crate::imp::DisplayInner;
 
-use crate::{Client, Global, Interface, Main, Resource};
+use crate::event_sources::{EventSource, FdEvent, FdInterest, Signal, SignalEvent, TimerEvent};
+use crate::handle::DisplayHandle;
+use crate::{Client, Filter, Global, Interface, Main, Resource};
 
 /// The wayland display
 ///
@@ -20,6 +25,7 @@ use crate::{Client, Global, Interface, Main, Resource};
 /// you to manage listening sockets and clients.
 pub struct Display {
     inner: Rc<RefCell<DisplayInner>>,
+    handle: Option<DisplayHandle>,
 }
 
 impl Display {
@@ -34,6 +40,7 @@ impl Display {
     pub fn new() -> Display {
         Display {
             inner: DisplayInner::new(),
+            handle: None,
         }
     }
 
@@ -63,7 +70,7 @@ impl Display {
         Global::create(
             self.inner
                 .borrow_mut()
-                .create_global(version, implementation, None::<fn(_) -> bool>),
+                .create_global(version, implementation, None::<fn(_) -> u32>),
         )
     }
 
@@ -99,7 +106,52 @@ impl Display {
         Global::create(self.inner.borrow_mut().create_global(
             version,
             implementation,
-            Some(move |client_inner| filter(Client::make(client_inner))),
+            Some(move |client_inner| {
+                if filter(Client::make(client_inner)) {
+                    version
+                } else {
+                    0
+                }
+            }),
+        ))
+    }
+
+    /// Create a new global object advertising a different version to each client
+    ///
+    /// This behaves like `create_global()`, but the version advertised to each client
+    /// is given by the `version_cap` callback, which is evaluated when the client builds
+    /// its registry (and when the global is created, for the registries that already exist).
+    /// The returned version is capped to `version`, and returning 0 hides the global from
+    /// this client, as would a filter of `create_global_with_filter()`.
+    ///
+    /// The callback is evaluated again when the client binds the global, and the client is
+    /// killed with a protocol error if it tries to bind a higher version than advertised. It
+    /// should thus always give the same answer for a given client.
+    ///
+    /// When using the system libwayland, all clients are still advertised the same version,
+    /// the cap is only enforced when they bind the global.
+    pub fn create_global_with_version_cap<I, F1, F2>(
+        &mut self,
+        version: u32,
+        implementation: F1,
+        mut version_cap: F2,
+    ) -> Global<I>
+    where
+        I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+        F1: FnMut(Main<I>, u32) + 'static,
+        F2: FnMut(Client) -> u32 + 'static,
+    {
+        assert!(
+            version <= I::VERSION,
+            "Cannot create global {} with version {}, maximum protocol version is {}.",
+            I::NAME,
+            version,
+            I::VERSION
+        );
+        Global::create(self.inner.borrow_mut().create_global(
+            version,
+            implementation,
+            Some(move |client_inner| version_cap(Client::make(client_inner))),
         ))
     }
 
@@ -111,6 +163,14 @@ impl Display {
         self.inner.borrow_mut().flush_clients()
     }
 
+    /// Iterate over the clients currently connected to this display
+    ///
+    /// The returned handles are a snapshot of the connected clients at the time
+    /// of this call.
+    pub fn clients(&self) -> impl Iterator<Item = Client> {
+        self.inner.borrow().clients().into_iter().map(Client::make)
+    }
+
     /// Dispatches all pending messages to their respective filters
     ///
     /// This method will block waiting for messages until one of these occur:
@@ -145,6 +205,109 @@ impl Display {
     pub fn get_poll_fd(&self) -> RawFd {
         self.inner.borrow().get_poll_fd()
     }
+
+    /// Gracefully shut down this display
+    ///
+    /// Rather than dropping the `Display`, which disconnects all clients immediately,
+    /// this method:
+    ///
+    /// 1. stops accepting new client connections
+    /// 2. destroys all globals, notifying the clients with `wl_registry.global_remove`
+    /// 3. flushes the pending events to the clients, waiting at most `timeout` for their
+    ///    sockets to accept them
+    /// 4. disconnects the clients in the order they connected, running for each of them
+    ///    the destructors of its resources and then its own destructors
+    /// 5. removes the listening sockets from the filesystem
+    ///
+    /// An error of kind `TimedOut` is returned if some events could not be sent before
+    /// the timeout expired, the shutdown is completed nonetheless.
+    ///
+    /// When using the system libwayland, as it does not expose whether events are still
+    /// pending for a client, the flush waits until the sockets of all clients are writable.
+    pub fn shutdown(self, timeout: Duration) -> IoResult<()> {
+        let deadline = Instant::now() + timeout;
+        if self.inner.borrow_mut().shutdown(deadline)? {
+            Ok(())
+        } else {
+            Err(IoError::new(
+                ErrorKind::TimedOut,
+                "Timed out while flushing the events to the clients",
+            ))
+        }
+    }
+}
+
+impl Display {
+    /// Insert a file descriptor source in the event loop of this display
+    ///
+    /// The filter will be sent an `FdEvent` whenever the file descriptor becomes
+    /// ready for one of the kinds of readiness given in `interest`, or if an error
+    /// occurs on it. This is dispatched as part of the `dispatch()` method.
+    ///
+    /// The file descriptor is not owned by the source, you are responsible for closing
+    /// it once it is no longer needed, after having removed the source.
+    pub fn add_fd_event_source(
+        &mut self,
+        fd: RawFd,
+        interest: FdInterest,
+        filter: Filter<FdEvent>,
+    ) -> IoResult<EventSource<FdEvent>> {
+        self.inner
+            .borrow_mut()
+            .add_fd_event_source(fd, interest, filter)
+            .map(EventSource::make)
+    }
+
+    /// Insert a timer source in the event loop of this display
+    ///
+    /// The timer is initially disarmed, use `EventSource::set_delay_ms()` to arm it.
+    /// The filter will be sent a `TimerEvent` each time the delay expires. This is
+    /// dispatched as part of the `dispatch()` method.
+    pub fn add_timer_event_source(
+        &mut self,
+        filter: Filter<TimerEvent>,
+    ) -> IoResult<EventSource<TimerEvent>> {
+        self.inner
+            .borrow_mut()
+            .add_timer_event_source(filter)
+            .map(EventSource::make)
+    }
+
+    /// Insert a signal source in the event loop of this display
+    ///
+    /// The filter will be sent a `SignalEvent` each time the process receives the given
+    /// signal. This is dispatched as part of the `dispatch()` method.
+    ///
+    /// To do so, the signal is blocked for the thread calling this method, and is not
+    /// unblocked when the source is removed. You should thus register your signal sources
+    /// before spawning any other thread, so that they inherit this signal mask.
+    pub fn add_signal_event_source(
+        &mut self,
+        signal: Signal,
+        filter: Filter<SignalEvent>,
+    ) -> IoResult<EventSource<SignalEvent>> {
+        self.inner
+            .borrow_mut()
+            .add_signal_event_source(signal, filter)
+            .map(EventSource::make)
+    }
+
+    /// Get a threadsafe handle to this display
+    ///
+    /// This handle allows you to send events and to create or destroy globals from
+    /// other threads, see `DisplayHandle` for details. The first call to this method
+    /// inserts the source waking up the event loop of this display, subsequent calls
+    /// return clones of the same handle.
+    pub fn handle(&mut self) -> IoResult<DisplayHandle> {
+        if let Some(ref handle) = self.handle {
+            return Ok(handle.clone());
+        }
+        let handle = DisplayHandle::new()?;
+        let filter = handle.filter(self.inner.borrow().global_creator());
+        self.add_fd_event_source(handle.eventfd(), FdInterest::READ, filter)?;
+        self.handle = Some(handle.clone());
+        Ok(handle)
+    }
 }
 
 impl Display {
@@ -225,6 +388,30 @@ impl Display {
     }
 }
 
+// wait until all these fds are writable, returns false if the deadline was reached first
+pub(crate) fn wait_writable(fds: &[RawFd], deadline: Instant) -> IoResult<bool> {
+    let mut pending = fds
+        .iter()
+        .map(|&fd| PollFd::new(fd, PollFlags::POLLOUT))
+        .collect::<Vec<_>>();
+    while !pending.is_empty() {
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(false);
+        }
+        let timeout = (deadline - now).as_millis().min(::std::i32::MAX as u128).max(1) as i32;
+        match poll(&mut pending, timeout) {
+            Ok(_) => {}
+            Err(::nix::Error::Sys(::nix::errno::Errno::EINTR)) => continue,
+            Err(::nix::Error::Sys(errno)) => return Err(errno.into()),
+            Err(_) => unreachable!(),
+        }
+        // errors and hangups count as writable, there is nothing more to wait for
+        pending.retain(|fd| fd.revents().map(|r| r.is_empty()).unwrap_or(true));
+    }
+    Ok(true)
+}
+
 pub(crate) fn get_runtime_dir() -> IoResult<PathBuf> {
     match env::var_os("XDG_RUNTIME_DIR") {
         Some(s) => Ok(s.into()),
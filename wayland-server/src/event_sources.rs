@@ -0,0 +1,91 @@
+use std::io::Error as IoError;
+use std::os::unix::io::RawFd;
+
+pub use nix::sys::signal::Signal;
+
+use crate::imp::EventSourceInner;
+
+bitflags! {
+    /// Flags to register interest on a file descriptor
+    pub struct FdInterest: u32 {
+        /// Interest to be notified when the file descriptor is readable
+        const READ  = 0x01;
+        /// Interest to be notified when the file descriptor is writable
+        const WRITE = 0x02;
+    }
+}
+
+/// An event generated by a file descriptor source
+pub enum FdEvent {
+    /// The file descriptor is ready
+    Ready {
+        /// The file descriptor that became ready
+        fd: RawFd,
+        /// The kinds of readiness that were triggered
+        mask: FdInterest,
+    },
+    /// An error occurred on the file descriptor, or it was hung up
+    Error {
+        /// The file descriptor that errored
+        fd: RawFd,
+        /// The error that occurred
+        error: IoError,
+    },
+}
+
+/// An event generated by a timer source
+///
+/// It is sent each time the delay of the timer expires.
+pub struct TimerEvent;
+
+/// An event generated by a signal source
+///
+/// It contains the signal that was received.
+pub struct SignalEvent(pub Signal);
+
+/// A handle to an event source registered in the `Display`
+///
+/// This is given to you when you insert a file descriptor, timer or signal
+/// source in the `Display` event loop. It allows you to modify or remove the
+/// source later.
+///
+/// If you know you will never need to remove this source, you can let this
+/// handle go out of scope, the source will stay registered as long as the
+/// `Display` is alive.
+///
+/// Once the `Display` is dropped, its sources are removed and this handle
+/// does nothing anymore.
+pub struct EventSource<E> {
+    inner: EventSourceInner<E>,
+}
+
+impl<E> EventSource<E> {
+    pub(crate) fn make(inner: EventSourceInner<E>) -> EventSource<E> {
+        EventSource { inner }
+    }
+
+    /// Removes this source from the event loop
+    ///
+    /// Its filter will no longer be called. If the source owned
+    /// a file descriptor (timer and signal sources), it is closed.
+    pub fn remove(self) {
+        self.inner.remove()
+    }
+}
+
+impl EventSource<FdEvent> {
+    /// Change the interest of this file descriptor source
+    pub fn update_interest(&mut self, interest: FdInterest) {
+        self.inner.update_interest(interest)
+    }
+}
+
+impl EventSource<TimerEvent> {
+    /// Arm the timer to fire once after given delay, in milliseconds
+    ///
+    /// Setting a delay of `0` disarms the timer. Re-arming an already armed
+    /// timer replaces its previous delay.
+    pub fn set_delay_ms(&mut self, delay: u32) {
+        self.inner.set_delay_ms(delay)
+    }
+}
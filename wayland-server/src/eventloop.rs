@@ -0,0 +1,77 @@
+use std::io;
+use std::time::Duration;
+
+use calloop::generic::{Fd, Generic};
+use calloop::{EventSource, Interest, Mode, Poll, Readiness, Token};
+
+use crate::Display;
+
+/// A calloop event source for a wayland `Display`
+///
+/// Inserting it into a `calloop::EventLoop` will process the requests of the
+/// clients as they arrive, and flush the events sent in response before the
+/// event loop goes back to sleep.
+///
+/// Once the messages of the clients have been dispatched, your callback is invoked
+/// with a `&mut Display`, allowing you to access the display from within the event
+/// loop. All events sent by the time this callback returns are flushed to the clients.
+///
+/// Events sent from the callbacks of other event sources are not automatically flushed,
+/// you need to call `Display::flush_clients()` from these callbacks as well.
+pub struct WaylandSource {
+    display: Display,
+    fd: Generic<Fd>,
+}
+
+impl WaylandSource {
+    /// Wrap a `Display` into a calloop event source
+    pub fn new(display: Display) -> WaylandSource {
+        let fd = display.get_poll_fd();
+        WaylandSource {
+            display,
+            fd: Generic::from_fd(fd, Interest::Readable, Mode::Level),
+        }
+    }
+
+    /// Access the underlying `Display`
+    pub fn display(&mut self) -> &mut Display {
+        &mut self.display
+    }
+
+    /// Unwrap the source to retrieve the underlying `Display`
+    pub fn into_display(self) -> Display {
+        self.display
+    }
+}
+
+impl EventSource for WaylandSource {
+    type Event = ();
+    type Metadata = Display;
+    type Ret = ();
+
+    fn process_events<F>(&mut self, readiness: Readiness, token: Token, mut callback: F) -> io::Result<()>
+    where
+        F: FnMut((), &mut Display),
+    {
+        let display = &mut self.display;
+        self.fd.process_events(readiness, token, |_, _| {
+            display.dispatch(Duration::from_millis(0))?;
+            callback((), display);
+            // flush the events before the event loop goes back to sleep
+            display.flush_clients();
+            Ok(())
+        })
+    }
+
+    fn register(&mut self, poll: &mut Poll, token: Token) -> io::Result<()> {
+        self.fd.register(poll, token)
+    }
+
+    fn reregister(&mut self, poll: &mut Poll, token: Token) -> io::Result<()> {
+        self.fd.reregister(poll, token)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> io::Result<()> {
+        self.fd.unregister(poll)
+    }
+}
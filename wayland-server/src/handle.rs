@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::io::Result as IoResult;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use nix::sys::eventfd::{eventfd, EfdFlags};
+
+use crate::imp::GlobalCreator;
+use crate::{FdEvent, Filter, Global, Interface, Main, Resource};
+
+type SendRequest = Box<dyn FnOnce() + Send>;
+type CreateGlobalRequest = Box<dyn FnOnce(&GlobalCreator) -> Box<dyn AnyGlobal> + Send>;
+
+enum Request {
+    Send(SendRequest),
+    CreateGlobal(usize, CreateGlobalRequest),
+    DestroyGlobal(usize),
+}
+
+// type-erased globals, to store them regardless of their interface
+trait AnyGlobal {
+    fn destroy(self: Box<Self>);
+}
+
+impl<I: Interface + AsRef<Resource<I>> + From<Resource<I>>> AnyGlobal for Global<I> {
+    fn destroy(self: Box<Self>) {
+        Global::destroy(*self)
+    }
+}
+
+struct HandleQueue {
+    requests: Mutex<Vec<Request>>,
+    eventfd: RawFd,
+    next_global_id: AtomicUsize,
+}
+
+impl Drop for HandleQueue {
+    fn drop(&mut self) {
+        let _ = nix::unistd::close(self.eventfd);
+    }
+}
+
+/// A threadsafe handle to a `Display`
+///
+/// This handle can be sent to other threads, to send events to the clients or to create and destroy
+/// globals from there. These actions are queued and executed by the thread hosting the `Display`
+/// the next time its event loop is dispatched, which the handle wakes up as needed. They are executed
+/// in the order they were queued.
+///
+/// As for events sent from your callbacks, the events sent through this handle are only sent to the
+/// clients once you call `Display::flush_clients()`.
+///
+/// The actions queued after the `Display` has been dropped are never executed.
+///
+/// It is obtained with `Display::handle()`.
+#[derive(Clone)]
+pub struct DisplayHandle {
+    queue: Arc<HandleQueue>,
+}
+
+/// A global created from a `DisplayHandle`
+///
+/// It can be used to destroy this global with `DisplayHandle::destroy_global()`.
+pub struct RemoteGlobal {
+    id: usize,
+}
+
+impl DisplayHandle {
+    pub(crate) fn new() -> IoResult<DisplayHandle> {
+        let fd = match eventfd(0, EfdFlags::EFD_CLOEXEC | EfdFlags::EFD_NONBLOCK) {
+            Ok(fd) => fd,
+            Err(::nix::Error::Sys(errno)) => return Err(errno.into()),
+            Err(_) => unreachable!(),
+        };
+        Ok(DisplayHandle {
+            queue: Arc::new(HandleQueue {
+                requests: Mutex::new(Vec::new()),
+                eventfd: fd,
+                next_global_id: AtomicUsize::new(0),
+            }),
+        })
+    }
+
+    pub(crate) fn eventfd(&self) -> RawFd {
+        self.queue.eventfd
+    }
+
+    // the filter executing the queued requests, to be registered in the event loop
+    pub(crate) fn filter(&self, creator: GlobalCreator) -> Filter<FdEvent> {
+        let queue = self.queue.clone();
+        let mut globals = HashMap::<usize, Box<dyn AnyGlobal>>::new();
+        Filter::new(move |_: FdEvent, _| {
+            // reset the eventfd counter
+            let mut buf = [0u8; 8];
+            let _ = nix::unistd::read(queue.eventfd, &mut buf);
+            let requests = ::std::mem::replace(&mut *queue.requests.lock().unwrap(), Vec::new());
+            for request in requests {
+                match request {
+                    Request::Send(send) => send(),
+                    Request::CreateGlobal(id, create) => {
+                        globals.insert(id, create(&creator));
+                    }
+                    Request::DestroyGlobal(id) => {
+                        if let Some(global) = globals.remove(&id) {
+                            global.destroy();
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn push(&self, request: Request) {
+        self.queue.requests.lock().unwrap().push(request);
+        // wake up the event loop
+        let _ = nix::unistd::write(self.queue.eventfd, &1u64.to_ne_bytes());
+    }
+
+    /// Send an event through given resource
+    ///
+    /// The event will be sent the next time the event loop of the `Display` is dispatched.
+    pub fn send_event<I>(&self, resource: &Resource<I>, event: I::Event)
+    where
+        I: Interface + From<Resource<I>> + AsRef<Resource<I>> + Sync,
+        I::Event: Send,
+    {
+        let resource = resource.clone();
+        self.push(Request::Send(Box::new(move || resource.send(event))));
+    }
+
+    /// Create a new global object
+    ///
+    /// This behaves like `Display::create_global()`, but the global is only created the
+    /// next time the event loop of the `Display` is dispatched. Your implementation will
+    /// be called from the thread hosting the `Display`.
+    pub fn create_global<I, F>(&self, version: u32, implementation: F) -> RemoteGlobal
+    where
+        I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+        F: FnMut(Main<I>, u32) + Send + 'static,
+    {
+        assert!(
+            version <= I::VERSION,
+            "Cannot create global {} with version {}, maximum protocol version is {}.",
+            I::NAME,
+            version,
+            I::VERSION
+        );
+        let id = self.queue.next_global_id.fetch_add(1, Ordering::Relaxed);
+        self.push(Request::CreateGlobal(
+            id,
+            Box::new(move |creator: &GlobalCreator| {
+                let global =
+                    Global::create(creator.create_global(version, implementation, None::<fn(_) -> u32>));
+                Box::new(global) as Box<dyn AnyGlobal>
+            }),
+        ));
+        RemoteGlobal { id }
+    }
+
+    /// Destroy a global created from a `DisplayHandle`
+    ///
+    /// The global is destroyed the next time the event loop of the `Display` is dispatched.
+    pub fn destroy_global(&self, global: RemoteGlobal) {
+        self.push(Request::DestroyGlobal(global.id));
+    }
+}
@@ -67,9 +67,18 @@
 //! process Wayland messages. Once this object is created, you can configure it to listen on one
 //! or more sockets for incoming client connections (see the `Display` docs for details).
 //!
-//! To properly function, this Wayland implementation also needs an event loop structure,
-//! which is here provided by the `calloop` crate. It is a public dependency and is reexported
-//! as `wayland_server::calloop`.
+//! To properly function, this Wayland implementation also needs an event loop structure.
+//! The `Display` exposes a file descriptor via its `get_poll_fd()` method, which you can
+//! integrate into your own event loop, and its `dispatch(..)` method processes the pending
+//! messages.
+//!
+//! If you don't need a full-fledged event loop, the `Display` can also host your own event
+//! sources: file descriptors, timers and signals can be inserted into it using the
+//! `add_*_event_source(..)` methods, and will be processed as part of the `dispatch(..)`
+//! method.
+//!
+//! If you use `calloop` as your event loop, enabling the `calloop` cargo feature of this crate
+//! provides a `WaylandSource`, wrapping your `Display` into a ready-to-insert calloop event source.
 
 #![warn(missing_docs)]
 
@@ -80,16 +89,29 @@ extern crate bitflags;
 extern crate wayland_sys;
 
 mod client;
+pub mod compositor;
 mod display;
+mod event_sources;
+#[cfg(feature = "calloop")]
+mod eventloop;
 mod globals;
+mod handle;
+pub mod region;
 mod resource;
+pub mod serial;
+pub mod shm;
 
 pub use client::Client;
 pub use display::Display;
+pub use event_sources::{EventSource, FdEvent, FdInterest, Signal, SignalEvent, TimerEvent};
+#[cfg(feature = "calloop")]
+pub use eventloop::WaylandSource;
 pub use globals::Global;
-pub use resource::{Main, Resource};
+pub use handle::{DisplayHandle, RemoteGlobal};
+pub use resource::{HandledBy, Main, Resource};
 
 pub use anonymous_object::AnonymousObject;
+pub use wayland_commons::introspection::{ObjectCreator, ObjectInfo, ObjectTreeSnapshot, SnapshotDiff};
 pub use wayland_commons::user_data::UserDataMap;
 pub use wayland_commons::{filter::Filter, Interface, MessageGroup, NoMessage};
 
@@ -118,7 +140,7 @@ pub mod protocol {
     #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
     #![allow(missing_docs, clippy::all)]
 
-    pub(crate) use crate::{AnonymousObject, Main, Resource, ResourceMap};
+    pub(crate) use crate::{AnonymousObject, HandledBy, Main, Resource, ResourceMap};
     pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
     pub(crate) use wayland_commons::smallvec;
     pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
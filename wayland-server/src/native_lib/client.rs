@@ -4,6 +4,7 @@ use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use wayland_commons::introspection::{timestamp, ObjectTreeSnapshot};
 use wayland_commons::ThreadGuard;
 use wayland_sys::server::*;
 
@@ -152,6 +153,63 @@ impl ClientInner {
             Some(ResourceInner::init_from_c_ptr::<I>(ptr))
         }
     }
+
+    pub(crate) fn resources(&self) -> Vec<ResourceInner> {
+        let mut resources = Vec::new();
+        if !self.alive() {
+            return resources;
+        }
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            ffi_dispatch!(
+                WAYLAND_SERVER_HANDLE,
+                wl_client_for_each_resource,
+                self.ptr,
+                resource_iterator,
+                &mut resources as *mut Vec<ResourceInner> as *mut c_void
+            );
+        }
+        resources
+    }
+
+    pub(crate) fn snapshot_objects(&self) -> ObjectTreeSnapshot {
+        let taken_at = timestamp();
+        let objects = self
+            .resources()
+            .iter()
+            .filter(|r| r.is_alive())
+            .map(|r| r.object_info(taken_at))
+            .collect();
+        ObjectTreeSnapshot::new(taken_at, objects)
+    }
+
+    pub(crate) fn get_resource<I: Interface + From<Resource<I>> + AsRef<Resource<I>>>(
+        &self,
+        id: u32,
+    ) -> Option<ResourceInner> {
+        if !self.alive() {
+            return None;
+        }
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_client_get_object, self.ptr, id);
+            if ptr.is_null() {
+                return None;
+            }
+            let resource = ResourceInner::from_c_ptr::<I>(ptr);
+            if resource.interface_name() == I::NAME {
+                Some(resource)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn resource_iterator(resource: *mut wl_resource, data: *mut c_void) -> wl_iterator_result {
+    let resources = &mut *(data as *mut Vec<ResourceInner>);
+    resources.push(ResourceInner::from_anonymous_c_ptr(resource));
+    wl_iterator_result::WL_ITERATOR_CONTINUE
 }
 
 unsafe extern "C" fn client_destroy(listener: *mut wl_listener, _data: *mut c_void) {
@@ -1,23 +1,32 @@
 use std::cell::RefCell;
+use std::env;
 use std::ffi::{CStr, CString, OsStr, OsString};
+use std::fs;
 use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 use std::os::raw::c_void;
 use std::os::unix::ffi::{OsStrExt, OsStringExt};
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
 use std::ptr;
 use std::rc::Rc;
+use std::time::Instant;
 
 use wayland_sys::server::*;
 
+use super::event_sources::SourceList;
 use super::globals::GlobalData;
-use super::{ClientInner, GlobalInner};
+use super::{ClientInner, EventSourceInner, GlobalInner};
 
 use crate::display::get_runtime_dir;
-use crate::{Interface, Main, Resource};
+use crate::event_sources::{FdEvent, FdInterest, Signal, SignalEvent, TimerEvent};
+use crate::{Filter, Interface, Main, Resource};
 
 pub(crate) struct DisplayInner {
     pub(crate) ptr: *mut wl_display,
     rust_globals: Rc<RefCell<Vec<*mut wl_global>>>,
+    sources: SourceList,
+    // the paths of the sockets created by add_socket() and add_socket_auto()
+    socket_paths: Vec<PathBuf>,
 }
 
 impl Drop for DisplayInner {
@@ -25,6 +34,10 @@ impl Drop for DisplayInner {
         {
             let _c_safety_guard = super::C_SAFETY.lock();
             unsafe {
+                // the event loop does not remove its sources when destroyed
+                for source in self.sources.borrow_mut().drain(..) {
+                    source.remove();
+                }
                 ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_destroy_clients, self.ptr);
                 ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_destroy, self.ptr);
             }
@@ -32,6 +45,45 @@ impl Drop for DisplayInner {
     }
 }
 
+// Allows creating globals without borrowing the DisplayInner, which is
+// already borrowed while the event loop is being dispatched
+pub(crate) struct GlobalCreator {
+    ptr: *mut wl_display,
+    rust_globals: Rc<RefCell<Vec<*mut wl_global>>>,
+}
+
+impl GlobalCreator {
+    pub(crate) fn create_global<I, F1, F2>(
+        &self,
+        version: u32,
+        implementation: F1,
+        filter: Option<F2>,
+    ) -> GlobalInner<I>
+    where
+        I: Interface + From<Resource<I>> + AsRef<Resource<I>>,
+        F1: FnMut(Main<I>, u32) + 'static,
+        F2: FnMut(ClientInner) -> u32 + 'static,
+    {
+        let data = Box::new(GlobalData::new(implementation, filter));
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let ptr = ffi_dispatch!(
+                WAYLAND_SERVER_HANDLE,
+                wl_global_create,
+                self.ptr,
+                I::c_interface(),
+                version as i32,
+                &*data as *const GlobalData<I> as *mut _,
+                super::globals::global_bind::<I>
+            );
+
+            self.rust_globals.borrow_mut().push(ptr);
+
+            GlobalInner::create(ptr, data, self.rust_globals.clone())
+        }
+    }
+}
+
 impl DisplayInner {
     pub(crate) fn new() -> Rc<RefCell<DisplayInner>> {
         let _c_safety_guard = super::C_SAFETY.lock();
@@ -55,7 +107,12 @@ impl DisplayInner {
                 &*rust_globals as *const RefCell<Vec<*mut wl_global>> as *mut _
             );
 
-            Rc::new(RefCell::new(DisplayInner { ptr, rust_globals }))
+            Rc::new(RefCell::new(DisplayInner {
+                ptr,
+                rust_globals,
+                sources: Rc::new(RefCell::new(Vec::new())),
+                socket_paths: Vec::new(),
+            }))
         }
     }
 
@@ -72,24 +129,16 @@ impl DisplayInner {
     where
         I: Interface + From<Resource<I>> + AsRef<Resource<I>>,
         F1: FnMut(Main<I>, u32) + 'static,
-        F2: FnMut(ClientInner) -> bool + 'static,
+        F2: FnMut(ClientInner) -> u32 + 'static,
     {
-        let data = Box::new(GlobalData::new(implementation, filter));
-        let _c_safety_guard = super::C_SAFETY.lock();
-        unsafe {
-            let ptr = ffi_dispatch!(
-                WAYLAND_SERVER_HANDLE,
-                wl_global_create,
-                self.ptr,
-                I::c_interface(),
-                version as i32,
-                &*data as *const GlobalData<I> as *mut _,
-                super::globals::global_bind::<I>
-            );
-
-            self.rust_globals.borrow_mut().push(ptr);
+        self.global_creator()
+            .create_global(version, implementation, filter)
+    }
 
-            GlobalInner::create(ptr, data, self.rust_globals.clone())
+    pub(crate) fn global_creator(&self) -> GlobalCreator {
+        GlobalCreator {
+            ptr: self.ptr,
+            rust_globals: self.rust_globals.clone(),
         }
     }
 
@@ -98,6 +147,70 @@ impl DisplayInner {
         unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_flush_clients, self.ptr) };
     }
 
+    pub(crate) fn clients(&self) -> Vec<ClientInner> {
+        let _c_safety_guard = super::C_SAFETY.lock();
+        let mut clients = Vec::new();
+        unsafe {
+            let list = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_get_client_list, self.ptr);
+            let mut link = (*list).next;
+            while link != list {
+                let client_ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_client_from_link, link);
+                clients.push(ClientInner::from_ptr(client_ptr));
+                link = (*link).next;
+            }
+        }
+        clients
+    }
+
+    pub(crate) fn shutdown(&mut self, deadline: Instant) -> IoResult<bool> {
+        // as the event loop is no longer dispatched, no new client will be accepted
+        {
+            let _c_safety_guard = super::C_SAFETY.lock();
+            // the global filter is called while destroying, and needs to recognize the
+            // globals to only notify the clients that could see them
+            let globals = self.rust_globals.borrow().clone();
+            for global in globals {
+                unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_global_destroy, global) };
+            }
+            self.rust_globals.borrow_mut().clear();
+        }
+        self.flush_clients();
+        // libwayland does not tell us if some events are still pending, so wait until
+        // all clients can receive data, and flush again
+        let fds = self
+            .clients()
+            .iter()
+            .map(|client| {
+                let _c_safety_guard = super::C_SAFETY.lock();
+                unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_client_get_fd, client.ptr()) }
+            })
+            .collect::<Vec<_>>();
+        let drained = crate::display::wait_writable(&fds, deadline);
+        self.flush_clients();
+        // libwayland runs the destroy listeners of a client before destroying its
+        // resources, so destroy our resources first for their destructors to run
+        // before the ones of the client
+        for client in self.clients() {
+            for resource in client.resources() {
+                if !resource.is_external() && resource.is_alive() {
+                    let _c_safety_guard = super::C_SAFETY.lock();
+                    unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_destroy, resource.c_ptr()) };
+                }
+            }
+            if client.alive() {
+                let _c_safety_guard = super::C_SAFETY.lock();
+                unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_client_destroy, client.ptr()) };
+            }
+        }
+        // libwayland only closes the listening sockets when the display is destroyed,
+        // so remove them from the filesystem now. The lock files are kept for no other
+        // server to take their names until then.
+        for path in self.socket_paths.drain(..) {
+            let _ = fs::remove_file(path);
+        }
+        drained
+    }
+
     pub(crate) fn dispatch(&mut self, timeout: i32) -> IoResult<()> {
         let _c_safety_guard = super::C_SAFETY.lock();
         let ret = unsafe {
@@ -119,6 +232,45 @@ impl DisplayInner {
         }
     }
 
+    pub(crate) fn add_fd_event_source(
+        &mut self,
+        fd: RawFd,
+        interest: FdInterest,
+        filter: Filter<FdEvent>,
+    ) -> IoResult<EventSourceInner<FdEvent>> {
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let evl_ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_get_event_loop, self.ptr);
+            EventSourceInner::<FdEvent>::new(evl_ptr, &self.sources, fd, interest, filter)
+                .ok_or_else(IoError::last_os_error)
+        }
+    }
+
+    pub(crate) fn add_timer_event_source(
+        &mut self,
+        filter: Filter<TimerEvent>,
+    ) -> IoResult<EventSourceInner<TimerEvent>> {
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let evl_ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_get_event_loop, self.ptr);
+            EventSourceInner::<TimerEvent>::new(evl_ptr, &self.sources, filter)
+                .ok_or_else(IoError::last_os_error)
+        }
+    }
+
+    pub(crate) fn add_signal_event_source(
+        &mut self,
+        signal: Signal,
+        filter: Filter<SignalEvent>,
+    ) -> IoResult<EventSourceInner<SignalEvent>> {
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let evl_ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_display_get_event_loop, self.ptr);
+            EventSourceInner::<SignalEvent>::new(evl_ptr, &self.sources, signal, filter)
+                .ok_or_else(IoError::last_os_error)
+        }
+    }
+
     pub(crate) fn add_socket<S>(&mut self, name: Option<S>) -> IoResult<()>
     where
         S: AsRef<OsStr>,
@@ -142,18 +294,20 @@ impl DisplayInner {
                 cname.as_ref().map(|s| s.as_ptr()).unwrap_or(ptr::null())
             )
         };
+        let mut socket_path = get_runtime_dir()?;
+        match name {
+            Some(s) => socket_path.push(s.as_ref()),
+            // this is the name libwayland picks by default
+            None => socket_path.push(env::var_os("WAYLAND_DISPLAY").unwrap_or_else(|| "wayland-0".into())),
+        }
         if ret == -1 {
             // lets try to be helpful
-            let mut socket_name = get_runtime_dir()?;
-            match name {
-                Some(s) => socket_name.push(s.as_ref()),
-                None => socket_name.push("wayland-0"),
-            }
             Err(IoError::new(
                 ErrorKind::PermissionDenied,
-                format!("could not bind socket {}", socket_name.to_string_lossy()),
+                format!("could not bind socket {}", socket_path.to_string_lossy()),
             ))
         } else {
+            self.socket_paths.push(socket_path);
             Ok(())
         }
     }
@@ -170,7 +324,9 @@ impl DisplayInner {
             ))
         } else {
             let sockname = unsafe { CStr::from_ptr(ret) };
-            Ok(<OsString as OsStringExt>::from_vec(sockname.to_bytes().into()))
+            let sockname = <OsString as OsStringExt>::from_vec(sockname.to_bytes().into());
+            self.socket_paths.push(get_runtime_dir()?.join(&sockname));
+            Ok(sockname)
         }
     }
 
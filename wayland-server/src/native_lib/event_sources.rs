@@ -0,0 +1,243 @@
+use std::cell::RefCell;
+use std::io::{Error as IoError, ErrorKind};
+use std::os::raw::{c_int, c_void};
+use std::os::unix::io::RawFd;
+use std::rc::{Rc, Weak};
+
+use nix::sys::signal::Signal;
+
+use wayland_sys::server::*;
+
+use crate::event_sources::{FdEvent, FdInterest, SignalEvent, TimerEvent};
+use crate::Filter;
+
+// The sources still registered in the event loop of a display, along with
+// their filters, so that the display can remove them before being destroyed.
+pub(crate) type SourceList = Rc<RefCell<Vec<RegisteredSource>>>;
+
+pub(crate) struct RegisteredSource {
+    ptr: *mut wl_event_source,
+    data: *mut c_void,
+    free: unsafe fn(*mut c_void),
+}
+
+impl RegisteredSource {
+    pub(crate) unsafe fn remove(self) {
+        ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_event_source_remove, self.ptr);
+        (self.free)(self.data);
+    }
+}
+
+unsafe fn free_filter<E>(data: *mut c_void) {
+    drop(Box::from_raw(data as *mut Filter<E>));
+}
+
+pub(crate) struct EventSourceInner<E> {
+    ptr: *mut wl_event_source,
+    // once the display is destroyed, its sources are removed and their filters
+    // freed, so the pointer must not be used anymore
+    sources: Weak<RefCell<Vec<RegisteredSource>>>,
+    _e: ::std::marker::PhantomData<*const E>,
+}
+
+impl<E> EventSourceInner<E> {
+    pub(crate) fn remove(self) {
+        if let Some(sources) = self.sources.upgrade() {
+            let mut sources = sources.borrow_mut();
+            if let Some(i) = sources.iter().position(|s| s.ptr == self.ptr) {
+                let _c_safety_guard = super::C_SAFETY.lock();
+                unsafe { sources.remove(i).remove() };
+            }
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.sources.upgrade().is_some()
+    }
+}
+
+impl EventSourceInner<FdEvent> {
+    pub(crate) unsafe fn new(
+        evl_ptr: *mut wl_event_loop,
+        sources: &SourceList,
+        fd: RawFd,
+        interest: FdInterest,
+        filter: Filter<FdEvent>,
+    ) -> Option<EventSourceInner<FdEvent>> {
+        let data = Box::into_raw(Box::new(filter));
+        let ptr = ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_event_loop_add_fd,
+            evl_ptr,
+            fd,
+            interest.bits(),
+            event_source_fd_dispatcher,
+            data as *mut c_void
+        );
+        EventSourceInner::checked(ptr, data, sources)
+    }
+
+    pub(crate) fn update_interest(&mut self, interest: FdInterest) {
+        if !self.is_alive() {
+            return;
+        }
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            ffi_dispatch!(
+                WAYLAND_SERVER_HANDLE,
+                wl_event_source_fd_update,
+                self.ptr,
+                interest.bits()
+            );
+        }
+    }
+}
+
+impl EventSourceInner<TimerEvent> {
+    pub(crate) unsafe fn new(
+        evl_ptr: *mut wl_event_loop,
+        sources: &SourceList,
+        filter: Filter<TimerEvent>,
+    ) -> Option<EventSourceInner<TimerEvent>> {
+        let data = Box::into_raw(Box::new(filter));
+        let ptr = ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_event_loop_add_timer,
+            evl_ptr,
+            event_source_timer_dispatcher,
+            data as *mut c_void
+        );
+        EventSourceInner::checked(ptr, data, sources)
+    }
+
+    pub(crate) fn set_delay_ms(&mut self, delay: u32) {
+        if !self.is_alive() {
+            return;
+        }
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            ffi_dispatch!(
+                WAYLAND_SERVER_HANDLE,
+                wl_event_source_timer_update,
+                self.ptr,
+                delay as c_int
+            );
+        }
+    }
+}
+
+impl EventSourceInner<SignalEvent> {
+    pub(crate) unsafe fn new(
+        evl_ptr: *mut wl_event_loop,
+        sources: &SourceList,
+        signal: Signal,
+        filter: Filter<SignalEvent>,
+    ) -> Option<EventSourceInner<SignalEvent>> {
+        let data = Box::into_raw(Box::new(filter));
+        let ptr = ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_event_loop_add_signal,
+            evl_ptr,
+            signal as c_int,
+            event_source_signal_dispatcher,
+            data as *mut c_void
+        );
+        EventSourceInner::checked(ptr, data, sources)
+    }
+}
+
+impl<E> EventSourceInner<E> {
+    unsafe fn checked(
+        ptr: *mut wl_event_source,
+        data: *mut Filter<E>,
+        sources: &SourceList,
+    ) -> Option<EventSourceInner<E>> {
+        if ptr.is_null() {
+            // creation failed, free the filter
+            drop(Box::from_raw(data));
+            None
+        } else {
+            sources.borrow_mut().push(RegisteredSource {
+                ptr,
+                data: data as *mut c_void,
+                free: free_filter::<E>,
+            });
+            Some(EventSourceInner {
+                ptr,
+                sources: Rc::downgrade(sources),
+                _e: ::std::marker::PhantomData,
+            })
+        }
+    }
+}
+
+unsafe extern "C" fn event_source_fd_dispatcher(fd: c_int, mask: u32, data: *mut c_void) -> c_int {
+    // We don't need to worry about panic-safeness, because if there is a panic,
+    // we'll abort the process, so no access to corrupted data is possible.
+    let ret = ::std::panic::catch_unwind(move || {
+        let filter = &*(data as *const Filter<FdEvent>);
+        // WL_EVENT_ERROR
+        if mask & 0x08 > 0 {
+            let error = match nix::sys::socket::getsockopt(fd, nix::sys::socket::sockopt::SocketError) {
+                Ok(err) => IoError::from_raw_os_error(err),
+                Err(_) => IoError::new(ErrorKind::Other, "fd event source errored"),
+            };
+            filter.send(FdEvent::Error { fd, error });
+        // WL_EVENT_HANGUP without WL_EVENT_READABLE
+        } else if mask & 0x04 > 0 && mask & 0x01 == 0 {
+            filter.send(FdEvent::Error {
+                fd,
+                error: IoError::new(ErrorKind::ConnectionAborted, "fd event source hung up"),
+            });
+        } else {
+            filter.send(FdEvent::Ready {
+                fd,
+                mask: FdInterest::from_bits_truncate(mask),
+            });
+        }
+    });
+    match ret {
+        Ok(()) => 0, // all went well
+        Err(_) => {
+            // a panic occurred
+            eprintln!("[wayland-server error] A handler for fd event source panicked, aborting.");
+            ::libc::abort();
+        }
+    }
+}
+
+unsafe extern "C" fn event_source_timer_dispatcher(data: *mut c_void) -> c_int {
+    // We don't need to worry about panic-safeness, because if there is a panic,
+    // we'll abort the process, so no access to corrupted data is possible.
+    let ret = ::std::panic::catch_unwind(move || {
+        let filter = &*(data as *const Filter<TimerEvent>);
+        filter.send(TimerEvent);
+    });
+    match ret {
+        Ok(()) => 0, // all went well
+        Err(_) => {
+            // a panic occurred
+            eprintln!("[wayland-server error] A handler for timer event source panicked, aborting.");
+            ::libc::abort();
+        }
+    }
+}
+
+unsafe extern "C" fn event_source_signal_dispatcher(signal: c_int, data: *mut c_void) -> c_int {
+    // We don't need to worry about panic-safeness, because if there is a panic,
+    // we'll abort the process, so no access to corrupted data is possible.
+    let ret = ::std::panic::catch_unwind(move || {
+        let filter = &*(data as *const Filter<SignalEvent>);
+        if let Ok(signal) = Signal::from_c_int(signal) {
+            filter.send(SignalEvent(signal));
+        }
+    });
+    match ret {
+        Ok(()) => 0, // all went well
+        Err(_) => {
+            // a panic occurred
+            eprintln!("[wayland-server error] A handler for signal event source panicked, aborting.");
+            ::libc::abort();
+        }
+    }
+}
@@ -11,7 +11,8 @@ use crate::{Main, Resource};
 
 pub(crate) struct GlobalData<I: Interface + AsRef<Resource<I>> + From<Resource<I>>> {
     pub(crate) bind: Box<dyn FnMut(Main<I>, u32)>,
-    pub(crate) filter: Option<Box<dyn FnMut(ClientInner) -> bool>>,
+    // returns the version of the global advertised to the client, 0 meaning it is hidden from it
+    pub(crate) filter: Option<Box<dyn FnMut(ClientInner) -> u32>>,
 }
 
 impl<I: Interface + AsRef<Resource<I>> + From<Resource<I>>> GlobalData<I> {
@@ -19,7 +20,7 @@ impl<I: Interface + AsRef<Resource<I>> + From<Resource<I>>> GlobalData<I> {
     where
         I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
         F1: FnMut(Main<I>, u32) + 'static,
-        F2: FnMut(ClientInner) -> bool + 'static,
+        F2: FnMut(ClientInner) -> u32 + 'static,
     {
         GlobalData {
             bind: Box::new(bind) as Box<_>,
@@ -53,10 +54,14 @@ where
     pub fn destroy(self) {
         let _c_safety_guard = super::C_SAFETY.lock();
         unsafe {
-            // destroy the global
-            ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_global_destroy, self.ptr);
-            // remove from the list
-            self.rust_globals.borrow_mut().retain(|&g| g != self.ptr);
+            // destroy the global, unless it was already destroyed by a display shutdown
+            let is_registered = self.rust_globals.borrow().contains(&self.ptr);
+            if is_registered {
+                // the global filter is called while destroying, so don't keep the list borrowed
+                ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_global_destroy, self.ptr);
+                // remove from the list
+                self.rust_globals.borrow_mut().retain(|&g| g != self.ptr);
+            }
             // free the user data
             let data = Box::from_raw(self.data);
             drop(data);
@@ -73,6 +78,28 @@ pub(crate) unsafe extern "C" fn global_bind<I: Interface + AsRef<Resource<I>> +
     // safety of this function is the same as dispatch_func
     let ret = ::std::panic::catch_unwind(move || {
         let data = &mut *(data as *mut GlobalData<I>);
+        // libwayland advertises the same version to all clients, enforce the per-client one here
+        if let Some(ref mut filter) = data.filter {
+            let advertised = filter(ClientInner::from_ptr(client));
+            if advertised < version {
+                let display = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_client_get_object, client, 1);
+                let msg = ::std::ffi::CString::new(format!(
+                    "Invalid version for global {}: have {}, wanted {}",
+                    I::NAME,
+                    advertised,
+                    version
+                ))
+                .unwrap();
+                ffi_dispatch!(
+                    WAYLAND_SERVER_HANDLE,
+                    wl_resource_post_error,
+                    display,
+                    0, // WL_DISPLAY_ERROR_INVALID_OBJECT
+                    msg.as_ptr()
+                );
+                return;
+            }
+        }
         let ptr = ffi_dispatch!(
             WAYLAND_SERVER_HANDLE,
             wl_resource_create,
@@ -119,7 +146,7 @@ pub(crate) unsafe extern "C" fn global_filter(
         let client = ClientInner::from_ptr(client as *mut _);
         let filter = &mut (*global_data).filter;
         if let Some(ref mut filter) = *filter {
-            filter(client)
+            filter(client) > 0
         } else {
             true
         }
@@ -4,11 +4,13 @@ use crate::{Interface, Main, Resource};
 
 mod client;
 mod display;
+mod event_sources;
 mod globals;
 mod resource;
 
 pub(crate) use self::client::ClientInner;
-pub(crate) use self::display::DisplayInner;
+pub(crate) use self::display::{DisplayInner, GlobalCreator};
+pub(crate) use self::event_sources::EventSourceInner;
 pub(crate) use self::globals::GlobalInner;
 pub(crate) use self::resource::ResourceInner;
 
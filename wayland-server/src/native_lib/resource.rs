@@ -1,11 +1,14 @@
 use std::cell::RefCell;
+use std::ffi::CStr;
 use std::os::raw::{c_int, c_void};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use wayland_sys::common::*;
 use wayland_sys::server::*;
 
+use wayland_commons::introspection::{timestamp, ObjectInfo, ObjectOrigin};
 use wayland_commons::user_data::UserData;
 
 use crate::{Interface, Main, MessageGroup, Resource};
@@ -14,6 +17,7 @@ use super::ClientInner;
 
 pub(crate) struct ResourceInternal {
     alive: AtomicBool,
+    created: Duration,
     user_data: UserData,
 }
 
@@ -21,6 +25,7 @@ impl ResourceInternal {
     fn new(user_data: UserData) -> ResourceInternal {
         ResourceInternal {
             alive: AtomicBool::new(true),
+            created: timestamp(),
             user_data,
         }
     }
@@ -84,6 +89,19 @@ impl ResourceInner {
         unsafe { ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_get_version, self.ptr) as u32 }
     }
 
+    pub(crate) fn interface_name(&self) -> &'static str {
+        if !self.is_alive() {
+            return "<dead>";
+        }
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let class = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_get_class, self.ptr);
+            // interface descriptions are static data, their name lives as long as the program
+            let name: &'static CStr = &*(CStr::from_ptr(class) as *const CStr);
+            name.to_str().unwrap_or("<invalid>")
+        }
+    }
+
     pub(crate) fn is_external(&self) -> bool {
         self.internal.is_none()
     }
@@ -124,6 +142,29 @@ impl ResourceInner {
         }
     }
 
+    pub(crate) fn post_invalid_method(&self, msg: String) {
+        if !self.is_alive() {
+            return;
+        }
+        let _c_safety_guard = super::C_SAFETY.lock();
+        unsafe {
+            let client_ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_get_client, self.ptr);
+            // the wl_display of a client is always its object 1
+            let display_ptr = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_client_get_object, client_ptr, 1);
+            if display_ptr.is_null() {
+                return;
+            }
+            let cstring = ::std::ffi::CString::from_vec_unchecked(msg.into());
+            ffi_dispatch!(
+                WAYLAND_SERVER_HANDLE,
+                wl_resource_post_error,
+                display_ptr,
+                1,
+                cstring.as_ptr()
+            )
+        }
+    }
+
     pub(crate) fn client(&self) -> Option<ClientInner> {
         if self.is_alive() {
             let _c_safety_guard = super::C_SAFETY.lock();
@@ -157,6 +198,7 @@ impl ResourceInner {
             return ResourceInner {
                 internal: Some(Arc::new(ResourceInternal {
                     alive: AtomicBool::new(false),
+                    created: timestamp(),
                     user_data: UserData::new(),
                 })),
                 ptr,
@@ -175,6 +217,14 @@ impl ResourceInner {
             Box::into_raw(new_user_data) as *mut _,
             Some(resource_destroy::<I>)
         );
+        // tag the resource as managed by us, as anonymous resources can't be checked
+        // with wl_resource_instance_of()
+        ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_resource_add_destroy_listener,
+            ptr,
+            signal::rust_listener_create(resource_managed_marker)
+        );
 
         ResourceInner { internal, ptr }
     }
@@ -187,6 +237,7 @@ impl ResourceInner {
             return ResourceInner {
                 internal: Some(Arc::new(ResourceInternal {
                     alive: AtomicBool::new(false),
+                    created: timestamp(),
                     user_data: UserData::new(),
                 })),
                 ptr,
@@ -212,6 +263,28 @@ impl ResourceInner {
         ResourceInner { internal, ptr }
     }
 
+    // Anonymous objects have no C interface to check the resource against, so we
+    // only check whether it is managed by us.
+    pub(crate) unsafe fn from_anonymous_c_ptr(ptr: *mut wl_resource) -> Self {
+        let _c_safety_guard = super::C_SAFETY.lock();
+        let is_managed = !ffi_dispatch!(
+            WAYLAND_SERVER_HANDLE,
+            wl_resource_get_destroy_listener,
+            ptr,
+            resource_managed_marker
+        )
+        .is_null();
+        let internal = if is_managed {
+            // the layout of ResourceUserData does not depend on its interface
+            let user_data = ffi_dispatch!(WAYLAND_SERVER_HANDLE, wl_resource_get_user_data, ptr)
+                as *mut ResourceUserData<crate::AnonymousObject>;
+            Some((*user_data).internal.clone())
+        } else {
+            None
+        };
+        ResourceInner { internal, ptr }
+    }
+
     pub unsafe fn make_child_for<J: Interface + From<Resource<J>> + AsRef<Resource<J>>>(
         &self,
         id: u32,
@@ -248,6 +321,24 @@ impl ResourceInner {
         }
     }
 
+    pub(crate) fn object_info(&self, taken_at: Duration) -> ObjectInfo {
+        // the creator of objects is not tracked by libwayland
+        let origin = self.internal.as_ref().map(|inner| ObjectOrigin {
+            time: inner.created,
+            creator: None,
+        });
+        ObjectInfo::new(
+            self.id(),
+            self.interface_name(),
+            self.version(),
+            origin.as_ref(),
+            self.internal
+                .as_ref()
+                .and_then(|inner| inner.user_data.type_name()),
+            taken_at,
+        )
+    }
+
     pub(crate) fn clone(&self) -> ResourceInner {
         ResourceInner {
             internal: self.internal.clone(),
@@ -290,6 +381,7 @@ impl ResourceInner {
 type BoxedHandler<I> = Box<dyn Fn(<I as Interface>::Request, Main<I>)>;
 type BoxedDest<I> = Box<dyn Fn(Resource<I>)>;
 
+#[repr(C)]
 pub(crate) struct ResourceUserData<I: Interface + From<Resource<I>> + AsRef<Resource<I>>> {
     _i: ::std::marker::PhantomData<*const I>,
     pub(crate) internal: Arc<ResourceInternal>,
@@ -399,3 +491,7 @@ where
         ::libc::abort()
     }
 }
+
+unsafe extern "C" fn resource_managed_marker(listener: *mut wl_listener, _data: *mut c_void) {
+    signal::rust_listener_destroy(listener);
+}
@@ -0,0 +1,318 @@
+//! Regions, and a ready-to-use implementation of `wl_region`
+//!
+//! The clients describe regions (like the input or opaque regions of their surfaces) by
+//! adding and subtracting rectangles to and from `wl_region` objects. This module provides
+//! the `Region` type, supporting these operations as well as the other usual set operations,
+//! and `implement_region()`, which implements a `wl_region` by accumulating its requests in
+//! a `Region` stored in its `UserData`.
+//!
+//! The `wl_compositor` of the `compositor` module uses this implementation for the regions
+//! its clients create.
+
+use std::cell::RefCell;
+use std::cmp::{max, min};
+
+use crate::protocol::wl_region::{self, WlRegion};
+use crate::Main;
+
+/// A rectangle
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rectangle {
+    /// Horizontal coordinate of the top-left corner
+    pub x: i32,
+    /// Vertical coordinate of the top-left corner
+    pub y: i32,
+    /// Width of the rectangle
+    pub width: i32,
+    /// Height of the rectangle
+    pub height: i32,
+}
+
+impl Rectangle {
+    // the edges are computed in 64 bits, as clients commonly use huge rectangles
+    // to describe infinite regions
+    fn right(&self) -> i64 {
+        i64::from(self.x) + i64::from(self.width)
+    }
+
+    fn bottom(&self) -> i64 {
+        i64::from(self.y) + i64::from(self.height)
+    }
+
+    fn from_edges(left: i64, top: i64, right: i64, bottom: i64) -> Rectangle {
+        Rectangle {
+            x: left as i32,
+            y: top as i32,
+            width: (right - left) as i32,
+            height: (bottom - top) as i32,
+        }
+    }
+
+    /// Check if this rectangle is empty
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    /// Check if this rectangle contains given point
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (i64::from(x), i64::from(y));
+        x >= i64::from(self.x) && x < self.right() && y >= i64::from(self.y) && y < self.bottom()
+    }
+
+    /// Compute the intersection of this rectangle with another one
+    ///
+    /// Returns `None` if they do not overlap.
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        let left = max(i64::from(self.x), i64::from(other.x));
+        let top = max(i64::from(self.y), i64::from(other.y));
+        let right = min(self.right(), other.right());
+        let bottom = min(self.bottom(), other.bottom());
+        if left < right && top < bottom {
+            Some(Rectangle::from_edges(left, top, right, bottom))
+        } else {
+            None
+        }
+    }
+
+    // the parts of this rectangle outside of the other one, as up to 4 disjoint rectangles
+    fn subtract(&self, other: &Rectangle) -> Vec<Rectangle> {
+        let inter = match self.intersection(other) {
+            Some(inter) => inter,
+            None => return vec![*self],
+        };
+        let (left, top, right, bottom) = (i64::from(self.x), i64::from(self.y), self.right(), self.bottom());
+        let (ileft, itop, iright, ibottom) = (
+            i64::from(inter.x),
+            i64::from(inter.y),
+            inter.right(),
+            inter.bottom(),
+        );
+        let mut pieces = Vec::with_capacity(4);
+        // full-width bands above and below the intersection
+        if top < itop {
+            pieces.push(Rectangle::from_edges(left, top, right, itop));
+        }
+        if ibottom < bottom {
+            pieces.push(Rectangle::from_edges(left, ibottom, right, bottom));
+        }
+        // and the parts on its sides
+        if left < ileft {
+            pieces.push(Rectangle::from_edges(left, itop, ileft, ibottom));
+        }
+        if iright < right {
+            pieces.push(Rectangle::from_edges(iright, itop, right, ibottom));
+        }
+        pieces
+    }
+}
+
+/// A region, as a set of points of the plane
+///
+/// It is stored as a list of disjoint rectangles.
+#[derive(Clone, Debug, Default)]
+pub struct Region {
+    rects: Vec<Rectangle>,
+}
+
+impl Region {
+    /// Create an empty region
+    pub fn new() -> Region {
+        Region { rects: Vec::new() }
+    }
+
+    /// Create a region covering given rectangle
+    pub fn from_rect(rect: Rectangle) -> Region {
+        let mut region = Region::new();
+        region.add_rect(rect);
+        region
+    }
+
+    /// The disjoint rectangles making up this region
+    pub fn rects(&self) -> &[Rectangle] {
+        &self.rects
+    }
+
+    /// Check if this region is empty
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Add a rectangle to this region
+    pub fn add_rect(&mut self, rect: Rectangle) {
+        if rect.is_empty() {
+            return;
+        }
+        self.subtract_rect(rect);
+        self.rects.push(rect);
+    }
+
+    /// Remove a rectangle from this region
+    pub fn subtract_rect(&mut self, rect: Rectangle) {
+        if rect.is_empty() {
+            return;
+        }
+        self.rects = self.rects.iter().flat_map(|r| r.subtract(&rect)).collect();
+    }
+
+    /// Compute the union of this region with another one
+    pub fn union(&self, other: &Region) -> Region {
+        let mut union = self.clone();
+        for &rect in &other.rects {
+            union.add_rect(rect);
+        }
+        union
+    }
+
+    /// Compute the points of this region that are not in the other one
+    pub fn subtract(&self, other: &Region) -> Region {
+        let mut difference = self.clone();
+        for &rect in &other.rects {
+            difference.subtract_rect(rect);
+        }
+        difference
+    }
+
+    /// Compute the intersection of this region with another one
+    pub fn intersect(&self, other: &Region) -> Region {
+        // the rectangles of both regions are disjoint, so are their intersections
+        Region {
+            rects: self
+                .rects
+                .iter()
+                .flat_map(|a| other.rects.iter().filter_map(move |b| a.intersection(b)))
+                .collect(),
+        }
+    }
+
+    /// Check if this region contains given point
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.rects.iter().any(|r| r.contains(x, y))
+    }
+
+    /// Compute the smallest rectangle containing this region
+    ///
+    /// Returns `None` if this region is empty.
+    pub fn bounding_box(&self) -> Option<Rectangle> {
+        let first = self.rects.first()?;
+        let (mut left, mut top, mut right, mut bottom) = (
+            i64::from(first.x),
+            i64::from(first.y),
+            first.right(),
+            first.bottom(),
+        );
+        for rect in &self.rects[1..] {
+            left = min(left, i64::from(rect.x));
+            top = min(top, i64::from(rect.y));
+            right = max(right, rect.right());
+            bottom = max(bottom, rect.bottom());
+        }
+        // the size of the bounding box may not fit in 32 bits, saturate it
+        Some(Rectangle {
+            x: left as i32,
+            y: top as i32,
+            width: min(right - left, i64::from(::std::i32::MAX)) as i32,
+            height: min(bottom - top, i64::from(::std::i32::MAX)) as i32,
+        })
+    }
+}
+
+/// Implement a `wl_region`
+///
+/// The `add` and `subtract` requests of the client are applied to a `Region` stored
+/// in the `UserData` of the `wl_region`, which you can retrieve with `get_region()`.
+pub fn implement_region(region: Main<WlRegion>) {
+    region.as_ref().user_data().set(|| RefCell::new(Region::new()));
+    region.assign_mono(|region, request| {
+        let data = region.as_ref().user_data().get::<RefCell<Region>>().unwrap();
+        match request {
+            wl_region::Request::Add { x, y, width, height } => {
+                data.borrow_mut().add_rect(Rectangle { x, y, width, height })
+            }
+            wl_region::Request::Subtract { x, y, width, height } => {
+                data.borrow_mut().subtract_rect(Rectangle { x, y, width, height })
+            }
+            wl_region::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    });
+}
+
+/// Retrieve the current contents of a `wl_region`
+///
+/// Returns `None` if this region was not implemented with `implement_region()`.
+pub fn get_region(region: &WlRegion) -> Option<Region> {
+    region
+        .as_ref()
+        .user_data()
+        .get::<RefCell<Region>>()
+        .map(|data| data.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::i32::{MAX, MIN};
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rectangle {
+        Rectangle { x, y, width, height }
+    }
+
+    fn area(region: &Region) -> i64 {
+        region
+            .rects()
+            .iter()
+            .map(|r| i64::from(r.width) * i64::from(r.height))
+            .sum()
+    }
+
+    #[test]
+    fn add_subtract() {
+        let mut region = Region::from_rect(rect(0, 0, 10, 10));
+        region.add_rect(rect(5, 5, 10, 10));
+        assert_eq!(area(&region), 175);
+        assert!(region.contains(12, 12));
+        assert!(!region.contains(12, 2));
+
+        region.subtract_rect(rect(2, 2, 4, 4));
+        assert_eq!(area(&region), 159);
+        assert!(!region.contains(3, 3));
+        assert!(region.contains(1, 3));
+        assert_eq!(region.bounding_box(), Some(rect(0, 0, 15, 15)));
+
+        region.subtract_rect(rect(-100, -100, 200, 200));
+        assert!(region.is_empty());
+        assert_eq!(region.bounding_box(), None);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = Region::from_rect(rect(0, 0, 10, 10));
+        let b = Region::from_rect(rect(5, 0, 10, 5));
+
+        let union = a.union(&b);
+        assert_eq!(area(&union), 125);
+
+        let inter = a.intersect(&b);
+        assert_eq!(area(&inter), 25);
+        assert_eq!(inter.bounding_box(), Some(rect(5, 0, 5, 5)));
+
+        let diff = a.subtract(&b);
+        assert_eq!(area(&diff), 75);
+        assert!(!diff.contains(7, 2));
+        assert!(diff.contains(7, 7));
+    }
+
+    #[test]
+    fn infinite_region() {
+        let mut region = Region::from_rect(rect(MIN, MIN, MAX, MAX));
+        region.add_rect(rect(0, 0, MAX, MAX));
+        assert!(region.contains(MAX - 1, MAX - 1));
+        assert!(region.contains(-5, -3));
+        assert!(!region.contains(-5, 3));
+        region.subtract_rect(rect(0, 0, 10, 10));
+        assert!(!region.contains(5, 5));
+        assert!(region.contains(15, 5));
+        let bbox = region.bounding_box().unwrap();
+        assert_eq!((bbox.x, bbox.y, bbox.width), (MIN, MIN, MAX));
+    }
+}
@@ -6,6 +6,16 @@ use wayland_sys::server::*;
 use crate::imp::ResourceInner;
 use crate::{Client, Filter};
 
+/// Delivery of the requests of an interface to a handler
+///
+/// This trait is implemented by the code generated with handler traits: the interfaces
+/// implement it for all the implementors of their `RequestHandler` trait. It is used by
+/// `Main::assign_handler`.
+pub trait HandledBy<H>: Interface + AsRef<Resource<Self>> + From<Resource<Self>> {
+    /// Deliver a request of an object to the handler
+    fn handle(handler: &mut H, object: Main<Self>, request: Self::Request);
+}
+
 /// An handle to a wayland resource
 ///
 /// This represents a wayland object instantiated in a client
@@ -113,6 +123,17 @@ where
         self.inner.post_error(error_code, msg)
     }
 
+    /// Posts an `invalid_method` protocol error about a request of this resource
+    ///
+    /// Like libwayland does for the requests it cannot dispatch, the error is posted
+    /// on the `wl_display` of the client, so the message should name this resource
+    /// and the offending request.
+    ///
+    /// An error is fatal to the client that caused it.
+    pub fn post_invalid_method(&self, msg: String) {
+        self.inner.post_invalid_method(msg)
+    }
+
     /// Access the UserData associated to this object
     ///
     /// Each wayland object has an associated UserData, that can store
@@ -135,6 +156,14 @@ where
     pub fn id(&self) -> u32 {
         self.inner.id()
     }
+
+    /// Retrieve the name of the interface of this wayland object
+    ///
+    /// This is mostly useful for resources of unknown type, like the `Resource<AnonymousObject>`
+    /// returned by `Client::resources()`. Dead objects may report a placeholder name.
+    pub fn interface_name(&self) -> &'static str {
+        self.inner.interface_name()
+    }
 }
 
 impl<I> Resource<I>
@@ -307,6 +336,20 @@ where
         self.assign(Filter::new(move |(proxy, event), _| f(proxy, event)))
     }
 
+    /// Assign a handler to this object
+    ///
+    /// Behaves similarly as `assign_mono(..)`, but the requests are delivered to the
+    /// methods of the `RequestHandler` trait generated for the interface, which
+    /// `handler` implements.
+    pub fn assign_handler<H>(&self, mut handler: H)
+    where
+        I: Interface + AsRef<Resource<I>> + From<Resource<I>> + HandledBy<H>,
+        H: 'static,
+        I::Request: MessageGroup<Map = crate::ResourceMap>,
+    {
+        self.assign_mono(move |resource, request| I::handle(&mut handler, resource, request))
+    }
+
     /// Assign a destructor to this object
     ///
     /// The filter will be called upon destruction of this object
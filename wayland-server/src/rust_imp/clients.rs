@@ -1,13 +1,16 @@
 use std::cell::RefCell;
 use std::ffi::CString;
-use std::os::unix::io::{FromRawFd, IntoRawFd, RawFd};
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, ThreadId};
+use std::time::Instant;
 
 use nix::Result as NixResult;
 
+use wayland_commons::introspection::{timestamp, ObjectInfo, ObjectOrigin, ObjectTreeSnapshot};
 use wayland_commons::map::{Object, ObjectMap, ObjectMetadata, SERVER_ID_LIMIT};
 use wayland_commons::socket::{BufferedSocket, Socket};
 use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc, MessageParseError};
@@ -155,7 +158,12 @@ impl ClientConnection {
         };
 
         // create a new object if applicable
-        if let Some(child) = object.request_child(msg.opcode) {
+        if let Some(mut child) = object.request_child(msg.opcode) {
+            child.meta.origin = ObjectOrigin::created_by(
+                object.interface,
+                msg.sender_id,
+                object.requests[msg.opcode as usize].name,
+            );
             let new_id = msg
                 .args
                 .iter()
@@ -301,6 +309,49 @@ impl ClientInner {
         Some(ResourceInner::from_id(id, map, self.clone()).unwrap())
     }
 
+    pub(crate) fn resources(&self) -> Vec<ResourceInner> {
+        let mut resources = Vec::new();
+        if let Some(ref cx) = *self.data.lock().unwrap() {
+            cx.map.lock().unwrap().with_all(|id, obj| {
+                resources.push(ResourceInner {
+                    id,
+                    object: obj.clone(),
+                    client: self.clone(),
+                })
+            });
+        }
+        resources
+    }
+
+    pub(crate) fn snapshot_objects(&self) -> ObjectTreeSnapshot {
+        let taken_at = timestamp();
+        let mut objects = Vec::new();
+        if let Some(ref cx) = *self.data.lock().unwrap() {
+            cx.map.lock().unwrap().with_all(|id, obj| {
+                if !obj.meta.alive.load(Ordering::Acquire) {
+                    return;
+                }
+                objects.push(ObjectInfo::new(
+                    id,
+                    obj.interface,
+                    obj.version,
+                    Some(&obj.meta.origin),
+                    obj.meta.user_data.type_name(),
+                    taken_at,
+                ));
+            });
+        }
+        ObjectTreeSnapshot::new(taken_at, objects)
+    }
+
+    pub(crate) fn get_resource<I: Interface>(&self, id: u32) -> Option<ResourceInner> {
+        let map = match *self.data.lock().unwrap() {
+            Some(ref cx) => cx.map.clone(),
+            None => return None,
+        };
+        ResourceInner::from_id(id, map, self.clone()).filter(|resource| resource.is_interface::<I>())
+    }
+
     pub(crate) fn set_dispatcher_for(
         &self,
         id: u32,
@@ -400,6 +451,14 @@ impl ClientManager {
         client
     }
 
+    pub(crate) fn clients(&self) -> Vec<ClientInner> {
+        self.clients
+            .iter()
+            .map(|(_, client)| client.clone())
+            .filter(ClientInner::alive)
+            .collect()
+    }
+
     pub(crate) fn flush_all(&mut self) {
         // flush all clients and cleanup dead ones
         let epoll_mgr = self.epoll_mgr.clone();
@@ -422,6 +481,34 @@ impl ClientManager {
         }
     }
 
+    // flush all clients, waiting for their sockets to be writable until the deadline
+    // returns whether all the pending messages could be sent
+    pub(crate) fn drain_all(&mut self, deadline: Instant) -> io::Result<bool> {
+        loop {
+            let pending = self
+                .clients
+                .iter()
+                .filter_map(|(_, client)| {
+                    let mut guard = client.data.lock().unwrap();
+                    let cx = guard.as_mut()?;
+                    match cx.socket.flush() {
+                        Err(::nix::Error::Sys(::nix::errno::Errno::EAGAIN)) => {
+                            Some(cx.socket.get_socket().as_raw_fd())
+                        }
+                        // other errors mean the client is already disconnected
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            if pending.is_empty() {
+                return Ok(true);
+            }
+            if !crate::display::wait_writable(&pending, deadline)? {
+                return Ok(false);
+            }
+        }
+    }
+
     // kill & cleanup all clients
     pub(crate) fn kill_all(&mut self) {
         for &(_, ref client) in &self.clients {
@@ -6,14 +6,16 @@ use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::unix::net::UnixListener;
 use std::path::Path;
 use std::rc::Rc;
+use std::time::Instant;
 
 use crate::display::get_runtime_dir;
-use crate::{Interface, Main, Resource};
+use crate::event_sources::{FdEvent, FdInterest, Signal, SignalEvent, TimerEvent};
+use crate::{Filter, Interface, Main, Resource};
 
 use super::clients::ClientManager;
 use super::event_loop_glue::{FdManager, Token};
 use super::globals::GlobalManager;
-use super::{ClientInner, GlobalInner};
+use super::{ClientInner, EventSourceInner, GlobalInner};
 
 pub(crate) const DISPLAY_ERROR_INVALID_OBJECT: u32 = 0;
 pub(crate) const DISPLAY_ERROR_INVALID_METHOD: u32 = 1;
@@ -24,7 +26,7 @@ pub(crate) struct DisplayInner {
     epoll_mgr: Rc<FdManager>,
     pub(crate) clients_mgr: Rc<RefCell<ClientManager>>,
     global_mgr: Rc<RefCell<GlobalManager>>,
-    listeners: Vec<Token>,
+    listeners: Vec<(Token, Rc<WaylandListener>)>,
 }
 
 impl DisplayInner {
@@ -54,30 +56,56 @@ impl DisplayInner {
     where
         I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
         F1: FnMut(Main<I>, u32) + 'static,
-        F2: FnMut(ClientInner) -> bool + 'static,
+        F2: FnMut(ClientInner) -> u32 + 'static,
     {
-        self.global_mgr
-            .borrow_mut()
-            .add_global(version, implementation, filter)
+        self.global_creator()
+            .create_global(version, implementation, filter)
+    }
+
+    pub(crate) fn global_creator(&self) -> GlobalCreator {
+        GlobalCreator {
+            global_mgr: self.global_mgr.clone(),
+        }
     }
 
     pub(crate) fn flush_clients(&mut self) {
         self.clients_mgr.borrow_mut().flush_all()
     }
 
+    pub(crate) fn clients(&self) -> Vec<ClientInner> {
+        self.clients_mgr.borrow().clients()
+    }
+
+    pub(crate) fn shutdown(&mut self, deadline: Instant) -> io::Result<bool> {
+        // stop accepting clients, but keep the listeners alive so that the sockets
+        // are only removed from the filesystem once everything else is done
+        let mut listeners = Vec::new();
+        for (token, listener) in self.listeners.drain(..) {
+            self.epoll_mgr.deregister(token);
+            listeners.push(listener);
+        }
+        self.global_mgr.borrow_mut().destroy_all();
+        let drained = self.clients_mgr.borrow_mut().drain_all(deadline);
+        // disconnect the clients even if the flushing failed
+        self.clients_mgr.borrow_mut().kill_all();
+        drop(listeners);
+        drained
+    }
+
     fn add_unix_listener(&mut self, listener: UnixListener) -> io::Result<()> {
         listener.set_nonblocking(true)?;
         // The WaylandListener will automatically remove the filesystem socket
         // on drop, if any.
-        let listener = WaylandListener(listener);
+        let listener = Rc::new(WaylandListener(listener));
 
         let client_mgr = self.clients_mgr.clone();
+        let listener2 = listener.clone();
 
         let token = self
             .epoll_mgr
             .register(listener.0.as_raw_fd(), move || {
                 loop {
-                    match listener.0.accept() {
+                    match listener2.0.accept() {
                         Ok((stream, _)) => unsafe {
                             client_mgr.borrow_mut().init_client(stream.into_raw_fd());
                         },
@@ -87,14 +115,14 @@ impl DisplayInner {
                         }
                         Err(e) => {
                             // this is a legitimate error
-                            listener.eprint_error(e);
+                            listener2.eprint_error(e);
                         }
                     }
                 }
             })
             .map_err(|e| std::io::Error::from(e.as_errno().unwrap_or(nix::errno::Errno::EINVAL)))?;
 
-        self.listeners.push(token);
+        self.listeners.push((token, listener));
         Ok(())
     }
 
@@ -154,12 +182,63 @@ impl DisplayInner {
     pub(crate) fn get_poll_fd(&self) -> RawFd {
         self.epoll_mgr.get_poll_fd()
     }
+
+    pub(crate) fn add_fd_event_source(
+        &mut self,
+        fd: RawFd,
+        interest: FdInterest,
+        filter: Filter<FdEvent>,
+    ) -> io::Result<EventSourceInner<FdEvent>> {
+        EventSourceInner::<FdEvent>::new(&self.epoll_mgr, fd, interest, filter)
+            .map_err(|e| From::from(e.as_errno().unwrap_or(nix::errno::Errno::EINVAL)))
+    }
+
+    pub(crate) fn add_timer_event_source(
+        &mut self,
+        filter: Filter<TimerEvent>,
+    ) -> io::Result<EventSourceInner<TimerEvent>> {
+        EventSourceInner::<TimerEvent>::new(&self.epoll_mgr, filter)
+            .map_err(|e| From::from(e.as_errno().unwrap_or(nix::errno::Errno::EINVAL)))
+    }
+
+    pub(crate) fn add_signal_event_source(
+        &mut self,
+        signal: Signal,
+        filter: Filter<SignalEvent>,
+    ) -> io::Result<EventSourceInner<SignalEvent>> {
+        EventSourceInner::<SignalEvent>::new(&self.epoll_mgr, signal, filter)
+            .map_err(|e| From::from(e.as_errno().unwrap_or(nix::errno::Errno::EINVAL)))
+    }
+}
+
+// Allows creating globals without borrowing the DisplayInner, which is
+// already borrowed while the event loop is being dispatched
+pub(crate) struct GlobalCreator {
+    global_mgr: Rc<RefCell<GlobalManager>>,
+}
+
+impl GlobalCreator {
+    pub(crate) fn create_global<I, F1, F2>(
+        &self,
+        version: u32,
+        implementation: F1,
+        filter: Option<F2>,
+    ) -> GlobalInner<I>
+    where
+        I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
+        F1: FnMut(Main<I>, u32) + 'static,
+        F2: FnMut(ClientInner) -> u32 + 'static,
+    {
+        self.global_mgr
+            .borrow_mut()
+            .add_global(version, implementation, filter)
+    }
 }
 
 impl Drop for DisplayInner {
     fn drop(&mut self) {
-        for l in self.listeners.drain(..) {
-            self.epoll_mgr.deregister(l);
+        for (token, _) in self.listeners.drain(..) {
+            self.epoll_mgr.deregister(token);
         }
         self.clients_mgr.borrow_mut().kill_all();
     }
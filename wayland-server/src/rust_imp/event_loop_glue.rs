@@ -3,7 +3,7 @@ use std::os::unix::io::RawFd;
 
 use nix::sys::epoll::*;
 
-type FdData = (RawFd, Option<Box<dyn FnMut()>>);
+type FdData = (RawFd, Option<Box<dyn FnMut(EpollFlags)>>);
 
 #[derive(Copy, Clone)]
 pub(crate) struct Token(usize);
@@ -23,11 +23,20 @@ impl FdManager {
         })
     }
 
-    pub(crate) fn register<F: FnMut() + 'static>(&self, fd: RawFd, cb: F) -> nix::Result<Token> {
+    pub(crate) fn register<F: FnMut() + 'static>(&self, fd: RawFd, mut cb: F) -> nix::Result<Token> {
+        self.register_with_interest(fd, EpollFlags::EPOLLIN, move |_| cb())
+    }
+
+    pub(crate) fn register_with_interest<F: FnMut(EpollFlags) + 'static>(
+        &self,
+        fd: RawFd,
+        interest: EpollFlags,
+        cb: F,
+    ) -> nix::Result<Token> {
         let mut callbacks = self.callbacks.borrow_mut();
         // find the first free id
         let free_id = callbacks.iter().position(|c| c.is_none());
-        let cb = Some(Box::new(cb) as Box<dyn FnMut()>);
+        let cb = Some(Box::new(cb) as Box<dyn FnMut(EpollFlags)>);
         let id = match free_id {
             Some(i) => {
                 callbacks[i] = Some((fd, cb));
@@ -38,7 +47,7 @@ impl FdManager {
                 callbacks.len() - 1
             }
         };
-        let mut evt = EpollEvent::new(EpollFlags::EPOLLIN, id as u64);
+        let mut evt = EpollEvent::new(interest, id as u64);
         let ret = epoll_ctl(self.epoll_fd, EpollOp::EpollCtlAdd, fd, &mut evt);
         match ret {
             Ok(()) => Ok(Token(id)),
@@ -49,6 +58,15 @@ impl FdManager {
         }
     }
 
+    pub(crate) fn update_interest(&self, token: Token, interest: EpollFlags) -> nix::Result<()> {
+        if let Some((fd, _)) = self.callbacks.borrow()[token.0] {
+            let mut evt = EpollEvent::new(interest, token.0 as u64);
+            epoll_ctl(self.epoll_fd, EpollOp::EpollCtlMod, fd, &mut evt)
+        } else {
+            Ok(())
+        }
+    }
+
     pub(crate) fn deregister(&self, token: Token) {
         if let Some((fd, _)) = self.callbacks.borrow_mut()[token.0].take() {
             let _ = epoll_ctl(self.epoll_fd, EpollOp::EpollCtlDel, fd, None);
@@ -66,7 +84,7 @@ impl FdManager {
                 .as_mut()
                 .and_then(|(_, ref mut cb)| cb.take());
             if let Some(mut cb) = cb {
-                cb();
+                cb(event.events());
                 // now, put it back in place
                 if let Some(ref mut place) = self.callbacks.borrow_mut()[id] {
                     if place.1.is_none() {
@@ -76,11 +94,11 @@ impl FdManager {
                     // deleted and replaced by a new one while `cb` was running, in which case we should
                     // not put it back in place
                 }
-            // If self.callbacks[id] is None, this means that our callback has been deleted while running,
-            // in which case we should not put it back in place
-            } else {
-                panic!("Received a readiness for a deregistered fd?!");
+                // If self.callbacks[id] is None, this means that our callback has been deleted while running,
+                // in which case we should not put it back in place
             }
+            // If there was no callback, this fd has been deregistered by a previous callback
+            // of this same poll round, we can just ignore its readiness.
         }
 
         Ok(())
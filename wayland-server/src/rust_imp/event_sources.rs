@@ -0,0 +1,186 @@
+use std::io::{Error as IoError, ErrorKind};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::rc::Rc;
+
+use nix::sys::epoll::EpollFlags;
+use nix::sys::signal::{SigSet, Signal};
+use nix::sys::signalfd::{SfdFlags, SignalFd};
+
+use crate::event_sources::{FdEvent, FdInterest, SignalEvent, TimerEvent};
+use crate::Filter;
+
+use super::event_loop_glue::{FdManager, Token};
+
+pub(crate) struct EventSourceInner<E> {
+    _e: ::std::marker::PhantomData<*const E>,
+    epoll_mgr: Rc<FdManager>,
+    token: Token,
+    // the fd owned by this source, if any
+    timerfd: Option<RawFd>,
+}
+
+impl<E> EventSourceInner<E> {
+    pub(crate) fn remove(self) {
+        // the callback owns the underlying file descriptor if any, so dropping
+        // it closes the fd
+        self.epoll_mgr.deregister(self.token);
+    }
+}
+
+impl EventSourceInner<FdEvent> {
+    pub(crate) fn new(
+        epoll_mgr: &Rc<FdManager>,
+        fd: RawFd,
+        interest: FdInterest,
+        filter: Filter<FdEvent>,
+    ) -> nix::Result<EventSourceInner<FdEvent>> {
+        let token = epoll_mgr.register_with_interest(fd, interest_to_epoll(interest), move |flags| {
+            filter.send(epoll_to_event(fd, flags))
+        })?;
+        Ok(EventSourceInner {
+            _e: ::std::marker::PhantomData,
+            epoll_mgr: epoll_mgr.clone(),
+            token,
+            timerfd: None,
+        })
+    }
+
+    pub(crate) fn update_interest(&mut self, interest: FdInterest) {
+        if let Err(e) = self
+            .epoll_mgr
+            .update_interest(self.token, interest_to_epoll(interest))
+        {
+            eprintln!("[wayland-server] Failed to update fd source interest: {:?}", e);
+        }
+    }
+}
+
+impl EventSourceInner<TimerEvent> {
+    pub(crate) fn new(
+        epoll_mgr: &Rc<FdManager>,
+        filter: Filter<TimerEvent>,
+    ) -> nix::Result<EventSourceInner<TimerEvent>> {
+        let fd =
+            unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC | libc::TFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(nix::Error::last());
+        }
+        let timer = TimerFd(fd);
+        let token = epoll_mgr.register(fd, move || {
+            // read the expiration count to reset the readiness of the timer
+            let mut buffer = [0u8; 8];
+            match nix::unistd::read(timer.0, &mut buffer) {
+                Ok(_) => filter.send(TimerEvent),
+                Err(nix::Error::Sys(nix::errno::Errno::EAGAIN)) => {}
+                Err(e) => eprintln!("[wayland-server] Failed to read timer source: {:?}", e),
+            }
+        })?;
+        Ok(EventSourceInner {
+            _e: ::std::marker::PhantomData,
+            epoll_mgr: epoll_mgr.clone(),
+            token,
+            timerfd: Some(fd),
+        })
+    }
+
+    pub(crate) fn set_delay_ms(&mut self, delay: u32) {
+        if let Some(fd) = self.timerfd {
+            let spec = libc::itimerspec {
+                it_interval: libc::timespec {
+                    tv_sec: 0,
+                    tv_nsec: 0,
+                },
+                it_value: libc::timespec {
+                    tv_sec: (delay / 1000) as libc::time_t,
+                    tv_nsec: ((delay % 1000) * 1_000_000) as libc::c_long,
+                },
+            };
+            let ret = unsafe { libc::timerfd_settime(fd, 0, &spec, ::std::ptr::null_mut()) };
+            if ret < 0 {
+                eprintln!(
+                    "[wayland-server] Failed to set timer source delay: {}",
+                    IoError::last_os_error()
+                );
+            }
+        }
+    }
+}
+
+impl EventSourceInner<SignalEvent> {
+    pub(crate) fn new(
+        epoll_mgr: &Rc<FdManager>,
+        signal: Signal,
+        filter: Filter<SignalEvent>,
+    ) -> nix::Result<EventSourceInner<SignalEvent>> {
+        let mut mask = SigSet::empty();
+        mask.add(signal);
+        // the signal must be blocked for it to be delivered through the signalfd
+        mask.thread_block()?;
+        let mut sfd = SignalFd::with_flags(&mask, SfdFlags::SFD_CLOEXEC | SfdFlags::SFD_NONBLOCK)?;
+        let token = epoll_mgr.register(sfd.as_raw_fd(), move || loop {
+            match sfd.read_signal() {
+                Ok(Some(info)) => {
+                    if let Ok(signal) = Signal::from_c_int(info.ssi_signo as i32) {
+                        filter.send(SignalEvent(signal));
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("[wayland-server] Failed to read signal source: {:?}", e);
+                    break;
+                }
+            }
+        })?;
+        Ok(EventSourceInner {
+            _e: ::std::marker::PhantomData,
+            epoll_mgr: epoll_mgr.clone(),
+            token,
+            timerfd: None,
+        })
+    }
+}
+
+// Owned timerfd, closed when the source callback is dropped
+struct TimerFd(RawFd);
+
+impl Drop for TimerFd {
+    fn drop(&mut self) {
+        let _ = nix::unistd::close(self.0);
+    }
+}
+
+fn interest_to_epoll(interest: FdInterest) -> EpollFlags {
+    let mut flags = EpollFlags::empty();
+    if interest.contains(FdInterest::READ) {
+        flags.insert(EpollFlags::EPOLLIN);
+    }
+    if interest.contains(FdInterest::WRITE) {
+        flags.insert(EpollFlags::EPOLLOUT);
+    }
+    flags
+}
+
+fn epoll_to_event(fd: RawFd, flags: EpollFlags) -> FdEvent {
+    if flags.contains(EpollFlags::EPOLLERR) {
+        let error = match nix::sys::socket::getsockopt(fd, nix::sys::socket::sockopt::SocketError) {
+            Ok(err) => IoError::from_raw_os_error(err),
+            Err(_) => IoError::new(ErrorKind::Other, "fd event source errored"),
+        };
+        FdEvent::Error { fd, error }
+    } else if flags.contains(EpollFlags::EPOLLHUP) && !flags.contains(EpollFlags::EPOLLIN) {
+        // if there is still data to read, let the user consume it first
+        FdEvent::Error {
+            fd,
+            error: IoError::new(ErrorKind::ConnectionAborted, "fd event source hung up"),
+        }
+    } else {
+        let mut mask = FdInterest::empty();
+        if flags.contains(EpollFlags::EPOLLIN) {
+            mask.insert(FdInterest::READ);
+        }
+        if flags.contains(EpollFlags::EPOLLOUT) {
+            mask.insert(FdInterest::WRITE);
+        }
+        FdEvent::Ready { fd, mask }
+    }
+}
@@ -2,6 +2,7 @@ use std::cell::{Cell, RefCell};
 use std::ffi::CString;
 use std::rc::Rc;
 
+use wayland_commons::introspection::ObjectOrigin;
 use wayland_commons::map::Object;
 use wayland_commons::smallvec;
 use wayland_commons::wire::{Argument, Message};
@@ -11,7 +12,10 @@ use crate::{Interface, Main, Resource};
 use super::resources::ObjectMeta;
 use super::{ClientInner, ResourceInner};
 
-type GlobalFilter = Rc<RefCell<dyn FnMut(ClientInner) -> bool>>;
+// returns the version of the global advertised to the client, 0 meaning it is hidden from it
+type GlobalFilter = Rc<RefCell<dyn FnMut(ClientInner) -> u32>>;
+// arguments are the registry id, the new object id, the version and the client
+type GlobalImplem = dyn Fn(u32, u32, u32, ClientInner) -> Result<(), ()>;
 
 pub(crate) struct GlobalInner<I: Interface> {
     _i: ::std::marker::PhantomData<*const I>,
@@ -36,7 +40,7 @@ struct GlobalData {
     version: u32,
     interface: &'static str,
     destroyed: Rc<Cell<bool>>,
-    implem: Box<dyn Fn(u32, u32, ClientInner) -> Result<(), ()>>,
+    implem: Box<GlobalImplem>,
     filter: Option<GlobalFilter>,
 }
 
@@ -62,23 +66,25 @@ impl GlobalManager {
     where
         I: Interface + AsRef<Resource<I>> + From<Resource<I>>,
         F1: FnMut(Main<I>, u32) + 'static,
-        F2: FnMut(ClientInner) -> bool + 'static,
+        F2: FnMut(ClientInner) -> u32 + 'static,
     {
         let implem = RefCell::new(implementation);
         let data = GlobalData {
             version,
             interface: I::NAME,
             destroyed: Rc::new(Cell::new(false)),
-            implem: Box::new(move |newid, version, client| {
+            implem: Box::new(move |registry_id, newid, version, client| {
                 // insert the object in the map, and call the global bind callback
                 // This is done in two times to ensure the client lock is not locked during
                 // the callback
                 let map = if let Some(ref clientconn) = *client.data.lock().unwrap() {
+                    let mut meta = ObjectMeta::new();
+                    meta.origin = ObjectOrigin::created_by("wl_registry", registry_id, "bind");
                     clientconn
                         .map
                         .lock()
                         .unwrap()
-                        .insert_at(newid, Object::from_interface::<I>(version, ObjectMeta::new()))?;
+                        .insert_at(newid, Object::from_interface::<I>(version, meta))?;
                     Some(clientconn.map.clone())
                 } else {
                     None
@@ -126,13 +132,12 @@ impl GlobalManager {
             if global.destroyed.get() {
                 continue;
             }
-            if let Some(ref filter) = global.filter {
-                if !(&mut *filter.borrow_mut())(reg.1.clone()) {
-                    continue;
-                }
+            let version = advertised_version(global.version, global.filter.as_ref().map(|f| &**f), &reg.1);
+            if version == 0 {
+                continue;
             }
             let interface = CString::new(global.interface.as_bytes().to_owned()).unwrap();
-            send_global_msg(&reg, id as u32 + 1, interface, global.version);
+            send_global_msg(&reg, id as u32 + 1, interface, version);
         }
         self.registries.borrow_mut().push(reg);
 
@@ -150,12 +155,13 @@ impl GlobalManager {
         client: ClientInner,
     ) -> Result<(), ()> {
         if let Some(ref global_data) = self.globals.get((global_id - 1) as usize) {
-            if !global_data
-                .filter
-                .as_ref()
-                .map(|f| (&mut *f.borrow_mut())(client.clone()))
-                .unwrap_or(true)
-            {
+            // the version this client was told about, which it may not exceed
+            let advertised = advertised_version(
+                global_data.version,
+                global_data.filter.as_ref().map(|f| &**f),
+                &client,
+            );
+            if advertised == 0 {
                 // client is not allowed to see this global
                 client.post_error(
                     registry_id,
@@ -180,18 +186,18 @@ impl GlobalManager {
                         interface, global_id
                     ),
                 );
-            } else if global_data.version < version {
+            } else if advertised < version {
                 client.post_error(
                     registry_id,
                     super::display::DISPLAY_ERROR_INVALID_OBJECT,
                     format!(
                         "Invalid version for global {} ({}): have {}, wanted {}",
-                        interface, global_id, global_data.version, version
+                        interface, global_id, advertised, version
                     ),
                 );
             } else {
                 // all is good, we insert the object in the map and send it the events
-                return (global_data.implem)(resource_newid, version, client);
+                return (global_data.implem)(registry_id, resource_newid, version, client);
             }
         } else {
             client.post_error(
@@ -203,6 +209,20 @@ impl GlobalManager {
         Ok(())
     }
 
+    pub(crate) fn destroy_all(&mut self) {
+        self.self_cleanup();
+        let registries = self.registries.borrow();
+        for (idx, global) in self.globals.iter().enumerate() {
+            if !global.destroyed.replace(true) {
+                send_destroyed_global(
+                    &registries,
+                    (idx + 1) as u32,
+                    global.filter.as_ref().map(|f| &**f),
+                );
+            }
+        }
+    }
+
     fn self_cleanup(&self) {
         self.registries
             .borrow_mut()
@@ -224,38 +244,43 @@ fn send_global_msg(reg: &(u32, ClientInner), global_id: u32, interface: CString,
     }
 }
 
+fn advertised_version(
+    version: u32,
+    filter: Option<&RefCell<dyn FnMut(ClientInner) -> u32>>,
+    client: &ClientInner,
+) -> u32 {
+    match filter {
+        Some(filter) => ::std::cmp::min(version, (&mut *filter.borrow_mut())(client.clone())),
+        None => version,
+    }
+}
+
 fn send_new_global(
     registries: &[(u32, ClientInner)],
     global_id: u32,
     interface: &str,
     version: u32,
-    filter: Option<&RefCell<dyn FnMut(ClientInner) -> bool>>,
+    filter: Option<&RefCell<dyn FnMut(ClientInner) -> u32>>,
 ) {
     let iface = CString::new(interface.as_bytes().to_owned()).unwrap();
-    if let Some(filter) = filter {
-        let mut filter = filter.borrow_mut();
-        for reg in registries {
-            if !(&mut *filter)(reg.1.clone()) {
-                continue;
-            }
-            send_global_msg(reg, global_id, iface.clone(), version)
-        }
-    } else {
-        for reg in registries {
-            send_global_msg(reg, global_id, iface.clone(), version)
+    for reg in registries {
+        let version = advertised_version(version, filter, &reg.1);
+        if version == 0 {
+            continue;
         }
+        send_global_msg(reg, global_id, iface.clone(), version)
     }
 }
 
 fn send_destroyed_global(
     registries: &[(u32, ClientInner)],
     global_id: u32,
-    filter: Option<&RefCell<dyn FnMut(ClientInner) -> bool>>,
+    filter: Option<&RefCell<dyn FnMut(ClientInner) -> u32>>,
 ) {
     if let Some(filter) = filter {
         let mut filter = filter.borrow_mut();
         for &(id, ref client) in registries {
-            if !(&mut *filter)(client.clone()) {
+            if (&mut *filter)(client.clone()) == 0 {
                 continue;
             }
             if let Some(ref mut clientconn) = *client.data.lock().unwrap() {
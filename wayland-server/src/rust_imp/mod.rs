@@ -12,11 +12,13 @@ use crate::{Filter, Interface, Main, Resource};
 mod clients;
 mod display;
 mod event_loop_glue;
+mod event_sources;
 mod globals;
 mod resources;
 
 pub(crate) use self::clients::ClientInner;
-pub(crate) use self::display::DisplayInner;
+pub(crate) use self::display::{DisplayInner, GlobalCreator};
+pub(crate) use self::event_sources::EventSourceInner;
 pub(crate) use self::globals::GlobalInner;
 pub(crate) use self::resources::ResourceInner;
 
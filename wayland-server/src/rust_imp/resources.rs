@@ -4,6 +4,7 @@ use std::sync::{Arc, Mutex};
 
 use crate::{Interface, Main, Resource};
 
+use wayland_commons::introspection::ObjectOrigin;
 use wayland_commons::map::{Object, ObjectMap, ObjectMetadata};
 use wayland_commons::user_data::UserData;
 use wayland_commons::{MessageGroup, ThreadGuard};
@@ -17,7 +18,8 @@ pub(crate) struct ObjectMeta {
     pub(crate) dispatcher: Arc<ThreadGuard<RefCell<dyn Dispatcher>>>,
     pub(crate) destructor: Option<Arc<ThreadGuard<ResourceDestructor>>>,
     pub(crate) alive: Arc<AtomicBool>,
-    user_data: Arc<UserData>,
+    pub(crate) origin: ObjectOrigin,
+    pub(crate) user_data: Arc<UserData>,
 }
 
 impl ObjectMetadata for ObjectMeta {
@@ -30,6 +32,7 @@ impl ObjectMeta {
     pub(crate) fn new() -> ObjectMeta {
         ObjectMeta {
             alive: Arc::new(AtomicBool::new(true)),
+            origin: ObjectOrigin::now(),
             user_data: Arc::new(UserData::new()),
             dispatcher: super::default_dispatcher(),
             destructor: None,
@@ -39,6 +42,7 @@ impl ObjectMeta {
     pub(crate) fn with_dispatcher<D: Dispatcher>(disp: D) -> ObjectMeta {
         ObjectMeta {
             alive: Arc::new(AtomicBool::new(true)),
+            origin: ObjectOrigin::now(),
             user_data: Arc::new(UserData::new()),
             dispatcher: Arc::new(ThreadGuard::new(RefCell::new(disp))),
             destructor: None,
@@ -107,6 +111,10 @@ impl ResourceInner {
         self.object.version
     }
 
+    pub(crate) fn interface_name(&self) -> &'static str {
+        self.object.interface
+    }
+
     pub(crate) fn equals(&self, other: &ResourceInner) -> bool {
         self.is_alive() && Arc::ptr_eq(&self.object.meta.alive, &other.object.meta.alive)
     }
@@ -119,6 +127,11 @@ impl ResourceInner {
         self.client.post_error(self.id, error_code, msg)
     }
 
+    pub(crate) fn post_invalid_method(&self, msg: String) {
+        // the wl_display of a client is always its object 1
+        self.client.post_error(1, 1, msg)
+    }
+
     pub(crate) fn user_data(&self) -> &UserData {
         &self.object.meta.user_data
     }
@@ -0,0 +1,64 @@
+//! Serial generation
+//!
+//! The compositor attaches serials to the events it sends for the clients to refer to them
+//! in their requests: input events, `configure` events, etc. This module provides the
+//! `SerialCounter` to generate them, and re-exports the `Serial` type, whose comparisons
+//! account for the wrapping of the counter.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub use wayland_commons::serial::Serial;
+
+/// A counter generating serials
+///
+/// The serials it generates start at 1 and wrap around. It can be shared between threads.
+#[derive(Debug)]
+pub struct SerialCounter {
+    // only the lower 32 bits are used
+    serial: AtomicUsize,
+}
+
+impl SerialCounter {
+    /// Create a new counter
+    pub fn new() -> SerialCounter {
+        SerialCounter {
+            serial: AtomicUsize::new(1),
+        }
+    }
+
+    /// Generate the next serial
+    pub fn next_serial(&self) -> Serial {
+        let serial = self.serial.fetch_add(1, Ordering::AcqRel) as u32;
+        if serial == 0 {
+            // never generate 0 on wrapping, clients may use it as a "no serial" value
+            Serial::from(self.serial.fetch_add(1, Ordering::AcqRel) as u32)
+        } else {
+            Serial::from(serial)
+        }
+    }
+
+    /// The last serial generated by this counter, if any
+    pub fn last_serial(&self) -> Option<Serial> {
+        match (self.serial.load(Ordering::Acquire) as u32).wrapping_sub(1) {
+            0 => None,
+            serial => Some(Serial::from(serial)),
+        }
+    }
+
+    /// Check if given serial was generated by this counter, and is no older than `oldest`
+    ///
+    /// This is useful to validate the serials the clients send in requests like `set_selection`,
+    /// for example by checking them against the serial of the last input event of the seat.
+    pub fn is_valid(&self, serial: Serial, oldest: Serial) -> bool {
+        match self.last_serial() {
+            Some(last) => serial.is_no_older_than(&oldest) && last.is_no_older_than(&serial),
+            None => false,
+        }
+    }
+}
+
+impl Default for SerialCounter {
+    fn default() -> SerialCounter {
+        SerialCounter::new()
+    }
+}
@@ -0,0 +1,417 @@
+//! A ready-to-use implementation of the `wl_shm` global
+//!
+//! Nearly all compositors need to support the `wl_shm` global, which allows clients to share
+//! their buffers with the compositor through shared memory. This module provides a complete
+//! implementation of it, which you can insert in your `Display` with `init_shm_global()`.
+//!
+//! It handles the creation and resizing of the memory pools, and the validation of the buffers
+//! the clients create from them. The contents of these buffers can then be accessed with
+//! `with_buffer_contents()`.
+//!
+//! Clients can shrink the file backing a pool after having shared it, in which case accessing
+//! the memory would raise a `SIGBUS`. Like libwayland does, this module handles it: the
+//! `SIGBUS` raised while accessing a buffer is caught, the faulty memory is replaced by blank
+//! pages, and the access reports an error after having killed the client.
+//!
+//! ```no_run
+//! # extern crate wayland_server;
+//! # use wayland_server::Display;
+//! use wayland_server::protocol::wl_shm::Format;
+//! use wayland_server::shm::init_shm_global;
+//!
+//! # let mut display = Display::new();
+//! // Argb8888 and Xrgb8888 are always advertised, list the other formats you support
+//! let shm_global = init_shm_global(&mut display, vec![Format::Yuyv]);
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::rc::Rc;
+use std::sync::atomic::{compiler_fence, Ordering};
+use std::sync::Once;
+
+use nix::sys::mman;
+use nix::sys::signal::{self, SigAction, SigHandler, SigSet, Signal};
+use nix::unistd;
+
+use crate::protocol::wl_buffer::{self, WlBuffer};
+use crate::protocol::wl_shm::{self, Format, WlShm};
+use crate::protocol::wl_shm_pool::{self, WlShmPool};
+use crate::{Display, Global, Main};
+
+/// Create the `wl_shm` global in this display
+///
+/// The formats `Argb8888` and `Xrgb8888` are mandatory and always advertised,
+/// `formats` lists the additional formats your compositor supports. The clients
+/// trying to create buffers with other formats are killed with a protocol error.
+///
+/// The returned global handle can be used to remove the global.
+pub fn init_shm_global(display: &mut Display, mut formats: Vec<Format>) -> Global<WlShm> {
+    for &format in &[Format::Argb8888, Format::Xrgb8888] {
+        if !formats.contains(&format) {
+            formats.push(format);
+        }
+    }
+    let formats = Rc::new(formats);
+
+    display.create_global::<WlShm, _>(1, move |shm, _version| {
+        let pool_formats = formats.clone();
+        shm.assign_mono(move |shm, request| {
+            if let wl_shm::Request::CreatePool { id, fd, size } = request {
+                implement_pool(&shm, id, fd, size, pool_formats.clone());
+            }
+        });
+        for &format in formats.iter() {
+            shm.format(format);
+        }
+    })
+}
+
+fn implement_pool(shm: &WlShm, pool: Main<WlShmPool>, fd: RawFd, size: i32, formats: Rc<Vec<Format>>) {
+    if size <= 0 {
+        shm.as_ref().post_error(
+            wl_shm::Error::InvalidStride as u32,
+            "Invalid size for a new wl_shm_pool.".into(),
+        );
+        let _ = unistd::close(fd);
+        return;
+    }
+    let map = match MemMap::new(fd, size as usize) {
+        Ok(map) => map,
+        Err(()) => {
+            shm.as_ref().post_error(
+                wl_shm::Error::InvalidFd as u32,
+                format!("Failed to mmap fd {}.", fd),
+            );
+            let _ = unistd::close(fd);
+            return;
+        }
+    };
+    let pool_data = Rc::new(Pool {
+        map: RefCell::new(map),
+    });
+
+    pool.assign_mono(move |pool, request| match request {
+        wl_shm_pool::Request::CreateBuffer {
+            id,
+            offset,
+            width,
+            height,
+            stride,
+            format,
+        } => {
+            if !formats.contains(&format) {
+                pool.as_ref().post_error(
+                    wl_shm::Error::InvalidFormat as u32,
+                    format!("SHM format {:?} is not supported.", format),
+                );
+                return;
+            }
+            let pool_size = pool_data.map.borrow().size;
+            if offset < 0
+                || width <= 0
+                || height <= 0
+                || stride < width
+                || ::std::i32::MAX / stride < height
+                || offset as usize > pool_size
+                || (stride * height) as usize > pool_size - offset as usize
+            {
+                pool.as_ref().post_error(
+                    wl_shm::Error::InvalidStride as u32,
+                    "Invalid width, height or stride.".into(),
+                );
+                return;
+            }
+            let data = BufferData {
+                offset,
+                width,
+                height,
+                stride,
+                format,
+            };
+            let pool_data = pool_data.clone();
+            // the only request is the destructor, the pool is released once all
+            // its buffers are gone
+            id.assign_mono(|_, _: wl_buffer::Request| {});
+            id.as_ref().user_data().set(move || ShmBuffer {
+                pool: pool_data,
+                data,
+            });
+        }
+        wl_shm_pool::Request::Resize { size } => {
+            let mut map = pool_data.map.borrow_mut();
+            if size <= 0 || (size as usize) < map.size {
+                pool.as_ref().post_error(
+                    wl_shm::Error::InvalidFd as u32,
+                    "Invalid new size for a wl_shm_pool.".into(),
+                );
+                return;
+            }
+            if map.resize(size as usize).is_err() {
+                pool.as_ref().post_error(
+                    wl_shm::Error::InvalidFd as u32,
+                    "Failed to remap the wl_shm_pool.".into(),
+                );
+            }
+        }
+        wl_shm_pool::Request::Destroy => {}
+        _ => unreachable!(),
+    });
+}
+
+/// Details of the contents of a shm buffer
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BufferData {
+    /// Offset of the start of the buffer relative to the beginning of the pool in bytes
+    pub offset: i32,
+    /// Width of the buffer in pixels
+    pub width: i32,
+    /// Height of the buffer in pixels
+    pub height: i32,
+    /// Number of bytes between the beginning of two consecutive lines
+    pub stride: i32,
+    /// Format used by this buffer
+    pub format: Format,
+}
+
+/// Errors that can occur when accessing the contents of a shm buffer
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BufferAccessError {
+    /// This buffer is not managed by the `wl_shm` implementation of this module
+    NotManaged,
+    /// An error occurred while accessing the memory of the buffer
+    ///
+    /// The client has probably shrunk the file backing its pool. It was killed
+    /// with a protocol error.
+    BadMap,
+}
+
+impl ::std::fmt::Display for BufferAccessError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            BufferAccessError::NotManaged => f.write_str("The buffer is not managed by wl_shm"),
+            BufferAccessError::BadMap => f.write_str("The memory of the buffer could not be accessed"),
+        }
+    }
+}
+
+impl ::std::error::Error for BufferAccessError {}
+
+/// Access the contents of a shm buffer
+///
+/// The callback is given the contents of the buffer, as described by the `BufferData`
+/// it is also given: the slice starts at the first pixel of the buffer and spans
+/// `stride * height` bytes.
+///
+/// If the client made its memory inaccessible, the callback sees blank memory instead,
+/// the client is killed with a protocol error and `BufferAccessError::BadMap` is returned
+/// once the callback returns.
+///
+/// If the buffer was not created by the `wl_shm` global of this module,
+/// `BufferAccessError::NotManaged` is returned and the callback is not called.
+pub fn with_buffer_contents<F, T>(buffer: &WlBuffer, f: F) -> Result<T, BufferAccessError>
+where
+    F: FnOnce(&[u8], BufferData) -> T,
+{
+    let shm_buffer = match buffer.as_ref().user_data().get::<ShmBuffer>() {
+        Some(shm_buffer) => shm_buffer,
+        None => return Err(BufferAccessError::NotManaged),
+    };
+
+    let map = shm_buffer.pool.map.borrow();
+    if map.ptr.is_null() {
+        // a resize of the pool failed, the client has already been killed
+        return Err(BufferAccessError::BadMap);
+    }
+    let data = shm_buffer.data;
+    let (result, faulted) = map.with_access(|contents| {
+        let start = data.offset as usize;
+        let end = start + (data.stride * data.height) as usize;
+        f(&contents[start..end], data)
+    });
+
+    if faulted {
+        buffer.as_ref().post_error(
+            wl_shm::Error::InvalidFd as u32,
+            "Error accessing SHM buffer.".into(),
+        );
+        Err(BufferAccessError::BadMap)
+    } else {
+        Ok(result)
+    }
+}
+
+struct ShmBuffer {
+    pool: Rc<Pool>,
+    data: BufferData,
+}
+
+struct Pool {
+    map: RefCell<MemMap>,
+}
+
+struct MemMap {
+    ptr: *mut u8,
+    fd: RawFd,
+    size: usize,
+}
+
+impl MemMap {
+    fn new(fd: RawFd, size: usize) -> Result<MemMap, ()> {
+        Ok(MemMap {
+            ptr: map(fd, size)?,
+            fd,
+            size,
+        })
+    }
+
+    fn resize(&mut self, newsize: usize) -> Result<(), ()> {
+        if newsize <= self.size {
+            return Ok(());
+        }
+        let _ = unmap(self.ptr, self.size);
+        match map(self.fd, newsize) {
+            Ok(ptr) => {
+                self.ptr = ptr;
+                self.size = newsize;
+                Ok(())
+            }
+            Err(()) => {
+                self.ptr = ptr::null_mut();
+                Err(())
+            }
+        }
+    }
+
+    fn contains(&self, addr: usize) -> bool {
+        let start = self.ptr as usize;
+        addr >= start && addr < start + self.size
+    }
+
+    // replace the contents of the map by anonymous memory, to recover from a SIGBUS
+    fn nullify(&self) -> Result<(), ()> {
+        nullify_map(self.ptr, self.size).map(|_| ())
+    }
+
+    fn with_access<T, F: FnOnce(&[u8]) -> T>(&self, f: F) -> (T, bool) {
+        SIGBUS_INIT.call_once(|| unsafe {
+            place_sigbus_handler();
+        });
+        // set the guard before accessing the memory, so that the SIGBUS handler can
+        // recognize this mapping
+        SIGBUS_GUARD.with(|guard| guard.set((self as *const MemMap, false)));
+        // unset it when the access ends, even if `f` panics, as this map may then be freed
+        let _reset = SigbusGuardReset;
+        // the accesses to the guard must not be reordered with the accesses to the memory
+        compiler_fence(Ordering::SeqCst);
+        let slice = unsafe { ::std::slice::from_raw_parts(self.ptr, self.size) };
+        let result = f(slice);
+        compiler_fence(Ordering::SeqCst);
+        let faulted = SIGBUS_GUARD.with(|guard| guard.get().1);
+        (result, faulted)
+    }
+}
+
+impl Drop for MemMap {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            let _ = unmap(self.ptr, self.size);
+        }
+        let _ = unistd::close(self.fd);
+    }
+}
+
+fn map(fd: RawFd, size: usize) -> Result<*mut u8, ()> {
+    let ret = unsafe {
+        mman::mmap(
+            ptr::null_mut(),
+            size,
+            mman::ProtFlags::PROT_READ,
+            mman::MapFlags::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+    ret.map(|ptr| ptr as *mut u8).map_err(|_| ())
+}
+
+fn unmap(ptr: *mut u8, size: usize) -> Result<(), ()> {
+    let ret = unsafe { mman::munmap(ptr as *mut _, size) };
+    ret.map_err(|_| ())
+}
+
+// map anonymous memory at the place of the given mapping (or anywhere if null)
+fn nullify_map(ptr: *mut u8, size: usize) -> Result<*mut u8, ()> {
+    let flags = if ptr.is_null() {
+        mman::MapFlags::MAP_ANONYMOUS | mman::MapFlags::MAP_PRIVATE
+    } else {
+        mman::MapFlags::MAP_ANONYMOUS | mman::MapFlags::MAP_PRIVATE | mman::MapFlags::MAP_FIXED
+    };
+    let ret = unsafe { mman::mmap(ptr as *mut _, size, mman::ProtFlags::PROT_READ, flags, -1, 0) };
+    ret.map(|ptr| ptr as *mut u8).map_err(|_| ())
+}
+
+/*
+ * SIGBUS handling
+ */
+
+thread_local! {
+    // the map currently being accessed by this thread, and whether a SIGBUS occurred
+    static SIGBUS_GUARD: Cell<(*const MemMap, bool)> = Cell::new((ptr::null(), false));
+}
+
+struct SigbusGuardReset;
+
+impl Drop for SigbusGuardReset {
+    fn drop(&mut self) {
+        SIGBUS_GUARD.with(|guard| guard.set((ptr::null(), false)));
+    }
+}
+
+static SIGBUS_INIT: Once = Once::new();
+static mut OLD_SIGBUS_HANDLER: *mut SigAction = ptr::null_mut();
+
+unsafe fn place_sigbus_handler() {
+    // create our sigbus handler
+    let action = SigAction::new(
+        SigHandler::SigAction(sigbus_handler),
+        signal::SaFlags::SA_NODEFER,
+        SigSet::empty(),
+    );
+    match signal::sigaction(Signal::SIGBUS, &action) {
+        Ok(old_signal) => {
+            OLD_SIGBUS_HANDLER = Box::into_raw(Box::new(old_signal));
+        }
+        Err(e) => panic!("sigaction failed for SIGBUS handler: {:?}", e),
+    }
+}
+
+unsafe fn reraise_sigbus() {
+    // reset the old sigaction and raise the signal again, it will be handled
+    // as if we did not intercept it
+    let _ = signal::sigaction(Signal::SIGBUS, &*OLD_SIGBUS_HANDLER);
+    let _ = signal::raise(Signal::SIGBUS);
+}
+
+extern "C" fn sigbus_handler(_signum: libc::c_int, info: *mut libc::siginfo_t, _context: *mut libc::c_void) {
+    let faulty_ptr = unsafe { (*info).si_addr() } as usize;
+    SIGBUS_GUARD.with(|guard| {
+        let (memmap, _) = guard.get();
+        match unsafe { memmap.as_ref() } {
+            Some(memmap) if memmap.contains(faulty_ptr) => {
+                // the SIGBUS was caused by the client shrinking its pool,
+                // mark it and replace the faulty memory by blank pages
+                guard.set((memmap, true));
+                if memmap.nullify().is_err() {
+                    // something terrible occurred
+                    unsafe { reraise_sigbus() };
+                }
+            }
+            _ => {
+                // the SIGBUS is not ours
+                unsafe { reraise_sigbus() };
+            }
+        }
+    });
+}
@@ -27,6 +27,14 @@ pub type wl_global_bind_func_t = unsafe extern "C" fn(*mut wl_client, *mut c_voi
 pub type wl_notify_func_t = unsafe extern "C" fn(*mut wl_listener, *mut c_void) -> ();
 pub type wl_resource_destroy_func_t = unsafe extern "C" fn(*mut wl_resource) -> ();
 pub type wl_display_global_filter_func_t = unsafe extern "C" fn(*const wl_client, *const wl_global, *mut c_void) -> bool;
+pub type wl_client_for_each_resource_iterator_func_t = unsafe extern "C" fn(*mut wl_resource, *mut c_void) -> wl_iterator_result;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum wl_iterator_result {
+    WL_ITERATOR_STOP = 0,
+    WL_ITERATOR_CONTINUE = 1,
+}
 
 #[repr(C)]
 pub struct wl_listener {
@@ -47,10 +55,14 @@ external_library!(WaylandServer, "wayland-server",
         fn wl_client_destroy(*mut wl_client) -> (),
         fn wl_client_get_display(*mut wl_client) -> *mut wl_display,
         fn wl_client_get_credentials(*mut wl_client, *mut pid_t, *mut uid_t, *mut gid_t) -> (),
+        fn wl_client_get_fd(*mut wl_client) -> c_int,
         fn wl_client_get_object(*mut wl_client, u32) -> *mut wl_resource,
         fn wl_client_add_destroy_listener(*mut wl_client, *mut wl_listener) -> (),
         fn wl_client_get_destroy_listener(*mut wl_client, wl_notify_func_t) -> *mut wl_listener,
         fn wl_client_post_no_memory(*mut wl_client) -> (),
+        fn wl_client_get_link(*mut wl_client) -> *mut wl_list,
+        fn wl_client_from_link(*mut wl_list) -> *mut wl_client,
+        fn wl_client_for_each_resource(*mut wl_client, wl_client_for_each_resource_iterator_func_t, *mut c_void) -> (),
         fn wl_resource_create(*mut wl_client, *const wl_interface, c_int, u32) -> *mut wl_resource,
     // wl_display
         fn wl_client_create(*mut wl_display, c_int) -> *mut wl_client,
@@ -73,6 +85,7 @@ external_library!(WaylandServer, "wayland-server",
         fn wl_display_init_shm(*mut wl_display) -> c_int,
         fn wl_display_add_client_created_listener(*mut wl_display, *mut wl_listener) -> (),
         fn wl_display_set_global_filter(*mut wl_display, wl_display_global_filter_func_t, *mut c_void) -> (),
+        fn wl_display_get_client_list(*mut wl_display) -> *mut wl_list,
     // wl_event_loop
         fn wl_event_loop_create() -> *mut wl_event_loop,
         fn wl_event_loop_destroy(*mut wl_event_loop) -> (),
@@ -108,9 +121,10 @@ external_library!(WaylandServer, "wayland-server",
         fn wl_resource_set_user_data(*mut wl_resource, *mut c_void) -> (),
         fn wl_resource_get_user_data(*mut wl_resource) -> *mut c_void,
         fn wl_resource_get_version(*mut wl_resource) -> c_int,
+        fn wl_resource_get_class(*mut wl_resource) -> *const c_char,
         fn wl_resource_set_destructor(*mut wl_resource, Option<wl_resource_destroy_func_t>) -> (),
         fn wl_resource_instance_of(*mut wl_resource, *const wl_interface, *const c_void) -> c_int,
-        fn wl_resource_add_destroy_listener(*mut wl_resource, wl_notify_func_t) -> (),
+        fn wl_resource_add_destroy_listener(*mut wl_resource, *mut wl_listener) -> (),
         fn wl_resource_get_destroy_listener(*mut wl_resource,wl_notify_func_t) -> *mut wl_listener,
     // wl_shm
         fn wl_shm_buffer_begin_access(*mut wl_shm_buffer) -> (),